@@ -1,9 +1,9 @@
-from rustlibfilt import Biquad, Harmonic, OnePole, Narrow, TwoZeroTwoPole, Zavalishin, Butter
+from rustlibfilt import Biquad, Harmonic, OnePole, Narrow, TwoZeroTwoPole, Zavalishin, Butter, Bessel, Chebyshev1, Chebyshev2, Elliptic, Fir
 import numpy as np
 
 
 class Rustlibfilt():
-    def __init__(self, fs: int, family: str, mode: str, buffer_lenght: int|None = None, order: int|None = None) -> None:
+    def __init__(self, fs: int, family: str, mode: str, buffer_lenght: int|None = None, order: int|None = None, dtype: str = "float64", topology: str = "df1") -> None:
         
         """
         INIT FILTER
@@ -14,7 +14,7 @@ class Rustlibfilt():
                 sampling rate
                 
             family: str
-                filter family: [biquad, harmonic, onePole, narrow, twoZeroTwoPole, zavalishin, butter]
+                filter family: [biquad, harmonic, onePole, narrow, twoZeroTwoPole, zavalishin, butter, bessel, chebyshev1, chebyshev2, elliptic, fir]
                 
             mode: str
                 filter type (for family):
@@ -25,12 +25,25 @@ class Rustlibfilt():
                     - twozerotwopole: [bp, notch]
                     - zavalishin: [zdf, naive, trap, svf]
                     - butter: [lp, hp, bp, br]
+                    - bessel: [lp, hp]
+                    - chebyshev1: [lp, hp, bp, br]
+                    - chebyshev2: [lp, hp, bp, br]
+                    - elliptic: [lp, hp, bp, br]
+                    - fir: [lp, hp, bp, br]
                     
             buffer_length: int|None
                 delay buffer length (for harmonic family)
                 
             order: int|None
-                order of filter (for onepole, narrow, butter)
+                order of filter (for onepole, narrow, butter, bessel, chebyshev1, chebyshev2, elliptic)
+                number of taps (for fir, default 101)
+                
+            dtype: str
+                processing precision: [float64, float32]
+                (coefficients are always designed in float64)
+                
+            topology: str
+                biquad realization: [df1, df2, tdf2, lattice, ss]
         """
         
         self.fs = fs
@@ -38,35 +51,51 @@ class Rustlibfilt():
         self.filter_type = None
         self.mode = mode
         self.coeffs = None
+        self.dtype = dtype
         n_order = 1 if order is None else order
 
         match family:
             case "biquad":
                 assert self.mode in ["lp", "hp", "bp0db", "bpsg", "notch", "ap", "peq", "lpshelf", "hpshelf"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = Biquad(fs=self.fs)
+                self.filter_type = Biquad(fs=self.fs, dtype=self.dtype, topology=topology)
             case "harmonic":
                 assert self.mode in ["combf", "combi", "allpass", "allpassfreev", "lpcombi", "lpallpass"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = Harmonic(mode=self.mode, buffer_delay=buffer_lenght, fs=self.fs)
+                self.filter_type = Harmonic(mode=self.mode, buffer_delay=buffer_lenght, fs=self.fs, dtype=self.dtype)
             case "onepole":
                 assert self.mode in ["lp", "hp"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = OnePole(fs=self.fs, order=n_order)
+                self.filter_type = OnePole(fs=self.fs, order=n_order, dtype=self.dtype)
             case "narrow":
                 assert self.mode in ["bp", "notch"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = Narrow(fs=self.fs, order=n_order)
+                self.filter_type = Narrow(fs=self.fs, order=n_order, dtype=self.dtype)
             case "twozerotwopole":
                 assert self.mode in ["bp", "notch"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = TwoZeroTwoPole(fs=self.fs)
+                self.filter_type = TwoZeroTwoPole(fs=self.fs, dtype=self.dtype)
             case "zavalishin":
                 assert self.mode in ["zdf", "naive", "trap", "svf"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = Zavalishin(fs=self.fs)
+                self.filter_type = Zavalishin(fs=self.fs, dtype=self.dtype)
             case "butter":
                 assert self.mode in ["lp", "hp", "bp", "br"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
-                self.filter_type = Butter(fs=self.fs, order=n_order)
+                self.filter_type = Butter(fs=self.fs, order=n_order, dtype=self.dtype)
+            case "bessel":
+                assert self.mode in ["lp", "hp"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
+                self.filter_type = Bessel(fs=self.fs, order=n_order, dtype=self.dtype)
+            case "chebyshev1":
+                assert self.mode in ["lp", "hp", "bp", "br"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
+                self.filter_type = Chebyshev1(fs=self.fs, order=n_order, dtype=self.dtype)
+            case "chebyshev2":
+                assert self.mode in ["lp", "hp", "bp", "br"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
+                self.filter_type = Chebyshev2(fs=self.fs, order=n_order, dtype=self.dtype)
+            case "elliptic":
+                assert self.mode in ["lp", "hp", "bp", "br"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
+                self.filter_type = Elliptic(fs=self.fs, order=n_order, dtype=self.dtype)
+            case "fir":
+                assert self.mode in ["lp", "hp", "bp", "br"], f"\n[ERROR] Wrong filter mode {self.mode} for {family} family!\n"
+                self.filter_type = Fir(fs=self.fs, n_taps=101 if order is None else order, dtype=self.dtype)
             case _:
                 print(f"[ERROR] Wrong family {family} type!\n")
                 exit(1)
     
-    def design_filter(self, fc: float|None = None, q: float|None = None, dbgain: float|None = None, t60: float|None = None, bw: float|None = None, fc_spread: float|None = None, fc_lp: float|None = None) -> None:
+    def design_filter(self, fc: float|None = None, q: float|None = None, dbgain: float|None = None, t60: float|None = None, bw: float|None = None, fc_spread: float|None = None, fc_lp: float|None = None, ripple: float|None = None, attenuation: float|None = None, norm: str = "phase", window: str = "hamming") -> None:
         
         """
         DESIGN FILTER
@@ -86,13 +115,25 @@ class Rustlibfilt():
                 reverb time in s. (for harmonic)
                 
             bw: float|None
-                band width (for narrow, twozerotwopole and butter)
+                band width (for narrow, twozerotwopole, butter, chebyshev1, chebyshev2, elliptic and fir)
                 
             fc_spread: float|None
                 value in Hz, in zavalishin svf fhigh = fc + fc_spread, bw = fhigh - fc
             
             fc_lp: float|None
                 low pass cut off frequency in harmonic family for lpcombi and lpallpass only 
+            
+            ripple: float|None
+                pass band ripple in dB (for chebyshev1 and elliptic)
+            
+            attenuation: float|None
+                minimum stop band attenuation in dB (for chebyshev2, elliptic and fir kaiser window)
+            
+            norm: str
+                cut off normalization (for bessel): [phase, delay, mag]
+            
+            window: str
+                windowed sinc taper (for fir): [hann, hamming, blackman, kaiser]
         """
         
         coeffs = None
@@ -111,6 +152,16 @@ class Rustlibfilt():
                 self.filter_type.design_filter(mode=self.mode, fc=fc, fc_spread=fc_spread)
             case "butter":
                 coeffs = self.filter_type.design_filter(mode=self.mode, fc=fc, bw=bw)
+            case "bessel":
+                coeffs = self.filter_type.design_filter(mode=self.mode, fc=fc, norm=norm)
+            case "chebyshev1":
+                coeffs = self.filter_type.design_filter(mode=self.mode, fc=fc, ripple=ripple, bw=bw)
+            case "chebyshev2":
+                coeffs = self.filter_type.design_filter(mode=self.mode, fc=fc, attenuation=attenuation, bw=bw)
+            case "elliptic":
+                coeffs = self.filter_type.design_filter(mode=self.mode, fc=fc, ripple=ripple, attenuation=attenuation, bw=bw)
+            case "fir":
+                self.filter_type.design_filter(mode=self.mode, fc=fc, bw=bw, window=window, attenuation=attenuation)
             case _:
                 print(f"[ERROR] Wrong family {self.family} type!\n")
                 exit(1)
@@ -164,7 +215,7 @@ class Rustlibfilt():
             y = self.filter_type.filt_frame(frame=frame, coeffs=tuple(self.coeffs))
         else:
             y = self.filter_type.filt_frame(frame=frame)
-        return y
+        return np.asarray(y, dtype=self.dtype)
     
     def clear_delayed_samples_cache(self) -> None:
         
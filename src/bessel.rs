@@ -0,0 +1,118 @@
+use super::{sample::Sample, filtertype::{FilterType, BesselFilterType, BesselNorm}, iirdesign::{Band, bessel_prototype, design_sos, MAX_BESSEL_ORDER}, iirfilter::{IirFilter, IirFamily}, error::{Result, FilterError, ModeName}};
+use alloc::vec::Vec;
+
+impl BesselNorm {
+    ///
+    /// PARSE NORMALIZATION NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         phase = magnitude asymptotes of a Butterworth at fc
+    ///         delay = group delay of 1 / (2 * pi * fc) sec. at DC
+    ///         mag = -3 dB at fc
+    ///
+    /// Return
+    /// ------
+    ///     Result<BesselNorm>
+    ///         FilterError on unknown name
+    ///
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "phase" => Ok(BesselNorm::Phase),
+            "delay" => Ok(BesselNorm::Delay),
+            "mag" => Ok(BesselNorm::Magnitude),
+            _ => Err(FilterError::UnknownNormalization(ModeName::new(name)))
+        }
+    }
+}
+
+/// Bessel (Thomson) family of IirFilter, order 1..=MAX_BESSEL_ORDER
+#[derive(Clone, Copy, Debug)]
+pub struct BesselFamily;
+
+impl IirFamily for BesselFamily {
+    const NAME: &'static str = "bessel";
+    const INITIAL_MODE: FilterType = FilterType::BesselType(BesselFilterType::Lp);
+}
+
+pub type Bessel<T = f64> = IirFilter<BesselFamily, T>;
+
+impl<T: Sample> IirFilter<BesselFamily, T> {
+    ///
+    /// GENERATE BESSEL (THOMSON) FILTER COEFFICIENTS
+    /// maximally flat group delay, for transients without overshoot and
+    /// ringing. Designed from the analog prototype through the bilinear
+    /// transform (prewarped fc) and stored as cascaded second order sections.
+    /// The bilinear transform flattens the delay less and less towards Nyquist,
+    /// keep fc well below fs / 2 for a constant delay
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///     fc: f64
+    ///         cut off frequency in Hz, meaning set by norm
+    ///     norm: BesselNorm
+    ///         Phase = magnitude asymptotes of a Butterworth at fc
+    ///         Delay = group delay of 1 / (2 * pi * fc) sec. at DC (lp)
+    ///         Magnitude = -3 dB at fc
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///         FilterError on unknown mode, order outside 1..=MAX_BESSEL_ORDER, invalid fc
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, norm: BesselNorm) -> Result<Vec<f64>> {
+        let band = match mode {
+            "lp" | "hp" => Band::from_mode(mode, fc, None, self.fs)?,
+            _ => None
+        }.ok_or(FilterError::UnknownMode { family: BesselFamily::NAME, mode: ModeName::new(mode) })?;
+        if !(1..=MAX_BESSEL_ORDER).contains(&self.order) {
+            return Err(FilterError::InvalidOrder { order: self.order, max: MAX_BESSEL_ORDER })
+        }
+
+        let sections = design_sos(&bessel_prototype(self.order, norm)?, band, self.fs);
+        let filter_type = FilterType::BesselType(match band {
+            Band::Highpass(_) => BesselFilterType::Hp,
+            _ => BesselFilterType::Lp
+        });
+        Ok(self.set_design(&sections, filter_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::freqz::FrequencyResponse;
+
+    const FS: f64 = 48000.0;
+    // 20 * log10(1 / sqrt(2))
+    const HALF_POWER_DB: f64 = -3.010299956639812;
+
+    #[test]
+    fn bessel_magnitude_norm_is_3_db_down_at_fc() {
+        for order in 1..=MAX_BESSEL_ORDER {
+            for (mode, fc) in [("lp", 1000.0), ("hp", 2000.0)] {
+                let mut bessel: Bessel = Bessel::new(FS, order);
+                bessel.design_filter(mode, fc, BesselNorm::Magnitude).unwrap();
+                let db = bessel.freqz(&[fc]).magnitude_db()[0];
+                assert!((db - HALF_POWER_DB).abs() < 1e-6, "{mode} order {order}: {db} dB at fc");
+            }
+        }
+    }
+
+    #[test]
+    fn bessel_delay_norm_sets_the_dc_group_delay() {
+        let fc = 100.0;
+        for order in 1..=MAX_BESSEL_ORDER {
+            let mut bessel: Bessel = Bessel::new(FS, order);
+            bessel.design_filter("lp", fc, BesselNorm::Delay).unwrap();
+            let delay = bessel.freqz(&[0.0]).group_delay[0];
+            let expected = FS / (2.0 * core::f64::consts::PI * fc);
+            assert!((delay / expected - 1.0).abs() < 1e-3, "order {order}: {delay} samples, expected {expected}");
+        }
+    }
+}
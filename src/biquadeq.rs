@@ -1,8 +1,15 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use pyo3::prelude::*;
-use super::{filtertype::{FilterType, BiquadFilterType}, coeffstruct::BiquadCoeffs, delayline::DelayLine};
+use super::{sample::Sample, filtertype::{FilterType, BiquadFilterType, BiquadTopology}, coeffstruct::BiquadCoeffs, realization::BiquadRealization, smoothing::{Smoother, SmoothedParam}, fastmath::{prewarp, fc_ratio, MIN_Q}, filter::Filter, error::{Result, FilterError, ModeName, check_fc, check_q, check_length, require}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::sos::SecondOrderSections;
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
 struct DesignBiquadFilter {
     mode: FilterType,
@@ -16,13 +23,10 @@ struct DesignBiquadFilter {
 
 impl DesignBiquadFilter {
     fn new(mode: FilterType, fc: f64, fs: f64, q: f64, dbgain: Option<f64>) -> Self {
-        const TWOPI: f64 = 2.0 * std::f64::consts::PI;
-        let filt_coeffs = BiquadCoeffs::new();
+        const TWOPI: f64 = 2.0 * core::f64::consts::PI;
         let w = TWOPI * fc / fs;
-        let theta_sine = w.sin();
-        let theta_cosine = w.cos();
-        
-        let alpha = theta_sine / (2.0 * q);
+        let theta_sine = libm::sin(w);
+        let theta_cosine = libm::cos(w);
 
         // manual implementation of Option.map
         // self.a = match dbgain {            
@@ -30,8 +34,23 @@ impl DesignBiquadFilter {
         //     None => None
         // };
 
-        let a = dbgain.map(|db_value| 10_f64.powf(db_value / 40_f64));
-        let beta = a.map(|a_value| a_value.sqrt() / q);
+        let a = dbgain.map(|db_value| libm::pow(10_f64, db_value / 40_f64));
+
+        Self::with_trig(mode, theta_sine, theta_cosine, q, a)
+    }
+
+    // modulated path: sin(w) and cos(w) from t = tan(w / 2), no transcendental call
+    fn with_prewarp(mode: FilterType, t: f64, q: f64, a: Option<f64>) -> Self {
+        let t2 = t * t;
+        let theta_sine = 2.0 * t / (1.0 + t2);
+        let theta_cosine = (1.0 - t2) / (1.0 + t2);
+        Self::with_trig(mode, theta_sine, theta_cosine, q, a)
+    }
+
+    fn with_trig(mode: FilterType, theta_sine: f64, theta_cosine: f64, q: f64, a: Option<f64>) -> Self {
+        let filt_coeffs = BiquadCoeffs::new();
+        let alpha = theta_sine / (2.0 * q);
+        let beta = a.map(|a_value| libm::sqrt(a_value) / q);
 
         Self {
             mode,
@@ -179,42 +198,96 @@ impl DesignBiquadFilter {
 }
 
 
-#[pyclass]
-pub struct Biquad {
+fn parse_mode(mode: &str) -> Result<FilterType> {
+    match mode {
+        "lp" => Ok(FilterType::BiquadType(BiquadFilterType::Lp)),
+        "hp" => Ok(FilterType::BiquadType(BiquadFilterType::Hp)),
+        "bp0db" => Ok(FilterType::BiquadType(BiquadFilterType::Bp0dB)),
+        "bpsg" => Ok(FilterType::BiquadType(BiquadFilterType::Bpsg)),
+        "notch" => Ok(FilterType::BiquadType(BiquadFilterType::Notch)),
+        "ap" => Ok(FilterType::BiquadType(BiquadFilterType::Ap)),
+        "peq" => Ok(FilterType::BiquadType(BiquadFilterType::Peq)),
+        "lps" => Ok(FilterType::BiquadType(BiquadFilterType::LpShelf)),
+        "hps" => Ok(FilterType::BiquadType(BiquadFilterType::HpShelf)),
+        _ => Err(FilterError::UnknownMode { family: "biquad", mode: ModeName::new(mode) })
+    }
+}
+
+fn design_coeffs(mode: FilterType, fc: f64, fs: f64, q: f64, dbgain: Option<f64>) -> [f64; 6] {
+    let mut design_filter: DesignBiquadFilter = DesignBiquadFilter::new(mode, fc, fs, q, dbgain);
+    design_filter.coeffs();
+    let (b0, b1, b2, a0, a1, a2) = design_filter.filt_coeffs.get_coeffs();
+    [b0, b1, b2, a0, a1, a2]
+}
+
+fn design_coeffs_modulated(mode: FilterType, fc: f64, fs: f64, q: f64, a: Option<f64>) -> (f64, f64, f64, f64, f64, f64) {
+    let mut design_filter = DesignBiquadFilter::with_prewarp(mode, prewarp(fc, fs), q.max(MIN_Q), a);
+    design_filter.coeffs();
+    design_filter.filt_coeffs.get_coeffs()
+}
+
+
+#[derive(Clone)]
+pub struct Biquad<T: Sample = f64> {
     fs: f64,
-    x1: DelayLine,
-    x2: DelayLine,
-    y1: DelayLine,
-    y2: DelayLine
+    coeffs: BiquadCoeffs<T>,
+    realization: BiquadRealization<T>,
+    mode: Option<FilterType>,
+    // fc, q, dbgain
+    smoother: Smoother<3, 6>
 }
 
-#[pymethods]
-impl Biquad {
-    
-    #[new]
+impl<T: Sample> Biquad<T> {
     
     ///
     /// INIT BIQUAD CLASS
+    /// (pass-through until a design or external coefficients are set)
     /// 
     /// Args
     /// ----
     ///     fs: f64
     ///         sampling rate
     /// 
-    
-    #[pyo3(text_signature = "(fs: float) -> None")]
     pub fn new(fs: f64) -> Self {
-        Self { 
-            fs, 
-            x1: DelayLine::new(1), 
-            x2: DelayLine::new(2), 
-            y1: DelayLine::new(1), 
-            y2: DelayLine::new(2) 
+        Self::with_topology(fs, BiquadTopology::DirectForm1)
+    }
+
+    ///
+    /// INIT BIQUAD CLASS WITH A GIVEN TOPOLOGY
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     topology: BiquadTopology
+    ///         DirectForm1 (as new), DirectForm2, TransposedDirectForm2,
+    ///         Lattice (normalized, robust at low fc and under modulation) or
+    ///         StateSpace (normal form, low coefficient sensitivity)
+    ///
+    pub fn with_topology(fs: f64, topology: BiquadTopology) -> Self {
+        Self {
+            fs,
+            coeffs: BiquadCoeffs::with_coeffs((1.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+            realization: BiquadRealization::new(topology),
+            mode: None,
+            smoother: Smoother::new(
+                [SmoothedParam::new(1000.0, true), SmoothedParam::new(0.707, true), SmoothedParam::new(0.0, false)],
+                [1.0, 0.0, 0.0, 1.0, 0.0, 0.0]
+            )
         }
     }
+
+    ///
+    /// GET TOPOLOGY
+    ///
+    pub fn topology(&self) -> BiquadTopology {
+        self.realization.topology()
+    }
     
     ///
     /// GENERATE BIQUAD FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame, applied at
+    /// once: use set_target to glide to new parameters)
     ///
     /// Args
     /// ----
@@ -238,125 +311,504 @@ impl Biquad {
     /// 
     /// Return
     /// ------
-    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///     Result<tuple> -> (f64, f64, f64, f64, f64, f64):
     ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///         FilterError on unknown mode, invalid fc or q, missing dbgain
     ///         
+    pub fn design_filter(&mut self, mode: &str, fc: f64, q: f64, dbgain: Option<f64>) -> Result<(f64, f64, f64, f64, f64, f64)> {
 
-    #[pyo3(text_signature = "(mode: str, fc: float, q: float, dbgain: float|None) -> tuple[float, float, float, float, float, float]")]
-    pub fn design_filter(&self, mode: &str, fc: f64, q: f64, dbgain: Option<f64>) -> (f64, f64, f64, f64, f64, f64) {
-
-        let filt_type: FilterType = match mode {
-            "lp" => FilterType::BiquadType(BiquadFilterType::Lp),
-            "hp" => FilterType::BiquadType(BiquadFilterType::Hp),
-            "bp0db" => FilterType::BiquadType(BiquadFilterType::Bp0dB),
-            "bpsg" => FilterType::BiquadType(BiquadFilterType::Bpsg),
-            "notch" => FilterType::BiquadType(BiquadFilterType::Notch),
-            "ap" => FilterType::BiquadType(BiquadFilterType::Ap),
-            "peq" => FilterType::BiquadType(BiquadFilterType::Peq),
-            "lps" => FilterType::BiquadType(BiquadFilterType::LpShelf),
-            "hps" => FilterType::BiquadType(BiquadFilterType::HpShelf),
-            _ => {
-                println!("[ERROR] Filt mode not allowed!");
-                std::process::exit(1)
-            }
-        };
-    
-        let mut design_filter: DesignBiquadFilter = DesignBiquadFilter::new(filt_type, fc, self.fs, q, dbgain);
-        design_filter.coeffs();
-        let coeffs = design_filter.filt_coeffs;
-        
-        let b0 = coeffs.b0;
-        let b1 = coeffs.b1;
-        let b2 = coeffs.b2;
-        let a0 = coeffs.a0;
-        let a1 = coeffs.a1;
-        let a2 = coeffs.a2;
-    
-        (b0, b1, b2, a0, a1, a2)
+        let filt_type = parse_mode(mode)?;
+
+        check_fc(fc, self.fs)?;
+        check_q(q)?;
+        if let FilterType::BiquadType(BiquadFilterType::Peq | BiquadFilterType::LpShelf | BiquadFilterType::HpShelf) = filt_type {
+            require(dbgain, mode, "dbgain")?;
+        }
+
+        let [b0, b1, b2, a0, a1, a2] = design_coeffs(filt_type, fc, self.fs, q, dbgain);
+        let coeffs = (b0, b1, b2, a0, a1, a2);
+        self.load_coeffs(coeffs);
+        self.mode = Some(filt_type);
+        self.smoother.snap([fc, q, dbgain.unwrap_or(0.0)], [b0, b1, b2, a0, a1, a2]);
+
+        Ok(coeffs)
     
     }
 
     ///
-    /// APPLY FILTER SAMPLE BY SAMPLE
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design (stops any glide)
     ///
     /// Args
     /// ----
-    ///     sample: f64
-    ///         input sample
     ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
     ///         filter coefficients (b0, b1, b2, a0, a1, a2)
     ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
+        self.load_coeffs(coeffs);
+        let (b0, b1, b2, a0, a1, a2) = coeffs;
+        self.smoother.snap_coeffs([b0, b1, b2, a0, a1, a2]);
+    }
+
+    fn load_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
+        self.coeffs.set_coeffs(coeffs);
+        self.realization.set_coeffs(coeffs);
+    }
+
+    ///
+    /// SET GLIDE TIME
+    /// used by set_target: the parameters glide over `time`, the coefficients
+    /// are designed every `control_interval` samples and linearly
+    /// interpolated in between (no zipper noise, no coefficient jumps in the
+    /// filter state)
+    ///
+    /// Args
+    /// ----
+    ///     time: f64
+    ///         glide time in sec. (0.0 = no smoothing, default)
+    ///     control_interval: usize
+    ///         samples between two coefficient designs (DEFAULT_CONTROL_INTERVAL = 32)
+    ///
     /// Return
     /// ------
-    ///     f64
-    ///         filtered sample
+    ///     Result<()>
+    ///         FilterError on negative time
     ///
+    pub fn set_glide(&mut self, time: f64, control_interval: usize) -> Result<()> {
+        self.smoother.set_time(time, self.fs, control_interval)
+    }
+
     ///
-    
-    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float, float, float, float, float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: (f64, f64, f64, f64, f64, f64)) -> f64 {
+    /// GLIDE TO NEW PARAMETERS
+    /// (same mode as the last design_filter, fc and q glide on a log scale,
+    /// dbgain linearly in dB)
+    ///
+    /// Args
+    /// ----
+    ///     fc: f64
+    ///         target corner/cutoff frequency in Hz
+    ///     q: f64
+    ///         target Q factor
+    ///     dbgain: Optional<f64>
+    ///         target dB value for peaking and shelf filters (None = keep)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if no design was set, on invalid fc or q
+    ///
+    pub fn set_target(&mut self, fc: f64, q: f64, dbgain: Option<f64>) -> Result<()> {
+        let mode = self.mode.ok_or(FilterError::MissingDesign)?;
+        check_fc(fc, self.fs)?;
+        check_q(q)?;
+        let dbgain = dbgain.unwrap_or(self.smoother.targets()[2]);
+
+        if !self.smoother.set_targets([fc, q, dbgain]) {
+            let coeffs = design_coeffs(mode, fc, self.fs, q, Some(dbgain));
+            let [b0, b1, b2, a0, a1, a2] = coeffs;
+            self.load_coeffs((b0, b1, b2, a0, a1, a2));
+            self.smoother.snap([fc, q, dbgain], coeffs);
+        }
+        Ok(())
+    }
 
-        let y: f64 = (
+    ///
+    /// GET CURRENT PARAMETERS
+    ///
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64, f64):
+    ///         fc, q and dbgain reached by the glide
+    ///
+    pub fn params(&self) -> (f64, f64, f64) {
+        let [fc, q, dbgain] = self.smoother.values();
+        (fc, q, dbgain)
+    }
 
-            coeffs.0 * sample + 
-            coeffs.1 * self.x1.read() + 
-            coeffs.2 * self.x2.read() - 
-            coeffs.4 * self.y1.read() -
-            coeffs.5 * self.y2.read()
-    
-        ) / coeffs.3;
+    pub fn is_gliding(&self) -> bool {
+        self.smoother.is_smoothing()
+    }
 
-        self.x1.write_and_advance(&sample);
-        self.x2.write_and_advance(&sample);
-        self.y1.write_and_advance(&y);
-        self.y2.write_and_advance(&y);
+    ///
+    /// SKIP THE GLIDE
+    /// jump to the target parameters of set_target
+    ///
+    pub fn skip_glide(&mut self) {
+        if let (Some(mode), true) = (self.mode, self.smoother.is_smoothing()) {
+            let [fc, q, dbgain] = self.smoother.targets();
+            let coeffs = design_coeffs(mode, fc, self.fs, q, Some(dbgain));
+            let [b0, b1, b2, a0, a1, a2] = coeffs;
+            self.load_coeffs((b0, b1, b2, a0, a1, a2));
+            self.smoother.snap([fc, q, dbgain], coeffs);
+        }
+    }
 
-        y
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///
+    pub fn coeffs(&self) -> (f64, f64, f64, f64, f64, f64) {
+        self.coeffs.get_coeffs()
     }
 
     ///
-    /// APPLY FILTER ON FRAME OR SIGNAL
+    /// SET FILTER STATE
+    /// arbitrary initial state, in the layout of the topology
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     state: [T; 4]
+    ///         df1 = [x[n - 1], x[n - 2], y[n - 1], y[n - 2]]
+    ///         df2 = [w[n - 1], w[n - 2], 0, 0]
+    ///         tdf2 = [s1, s2, 0, 0]
+    ///         lattice = [g0[n - 1], g1[n - 1], 0, 0]
+    ///         ss = [x1, x2, 0, 0]
+    ///
+    pub fn set_state(&mut self, state: [T; 4]) {
+        self.realization.set_state(state);
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the current coefficients once a unit step has settled
+    /// (as scipy.signal.lfilter_zi), in the layout of set_state.
+    /// Zero state when a pole sits at DC
+    ///
+    /// Return
+    /// ------
+    ///     [T; 4]
+    ///         state for a unit input, scale it by the input level
+    ///
+    pub fn step_state(&self) -> [T; 4] {
+        self.realization.step_state()
+    }
+
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input x0, so that a signal
+    /// starting at x0 (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: T
+    ///         input level, usually the first sample of the signal
+    ///
+    pub fn set_steady_state(&mut self, x0: T) {
+        self.set_state(self.step_state().map(|value| value * x0));
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
     ///         input sample
-    ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
-    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
-    ///         filtered frame
+    ///     T
+    ///         filtered sample
     ///
     ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        if let Some(mode) = self.mode {
+            let fs = self.fs;
+            if let Some([b0, b1, b2, a0, a1, a2]) = self.smoother.tick(|[fc, q, dbgain]| design_coeffs(mode, fc, fs, q, Some(dbgain))) {
+                self.load_coeffs((b0, b1, b2, a0, a1, a2));
+            }
+        }
+        self.realization.process(sample)
+    }
 
-    #[pyo3(text_signature = "(frame: list[float], coeffs: tuple[float, float, float, float, float, float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: (f64, f64, f64, f64, f64, f64)) -> Vec<f64> {
+    ///
+    /// APPLY FILTER WITH AUDIO-RATE MODULATION
+    /// the coefficients of the last design_filter mode are recomputed at
+    /// every sample from fc[n] (and q[n]), through a tan approximation (no
+    /// transcendental call per sample). dbgain is kept from the last design.
+    /// fc is clamped to [MIN_FC_RATIO * fs, MAX_FC_RATIO * fs], q to at
+    /// least MIN_Q (NaN to the lowest value). The last coefficients stay as
+    /// the stored design
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         input frame
+    ///     fc: &[f64]
+    ///         cutoff frequency in Hz per sample (same length as input)
+    ///     q: Option<&[f64]>
+    ///         Q factor per sample (None = keep the designed Q)
+    ///     output: &mut [T]
+    ///         filtered frame (same length as input)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if no design was set or on mismatched lengths
+    ///
+    pub fn filt_modulated(&mut self, input: &[T], fc: &[f64], q: Option<&[f64]>, output: &mut [T]) -> Result<()> {
+        let mode = self.mode.ok_or(FilterError::MissingDesign)?;
+        check_length(input.len(), fc.len())?;
+        check_length(input.len(), output.len())?;
+        if let Some(q) = q {
+            check_length(input.len(), q.len())?;
+        }
+        if input.is_empty() {
+            return Ok(())
+        }
+
+        let [_, q_design, dbgain] = self.smoother.values();
+        let a = Some(libm::pow(10_f64, dbgain / 40_f64));
+        let mut coeffs = self.coeffs();
+        let mut q_last = q_design;
+        for (n, (&x, y)) in input.iter().zip(output.iter_mut()).enumerate() {
+            q_last = q.map_or(q_design, |q| q[n].max(MIN_Q));
+            coeffs = design_coeffs_modulated(mode, fc[n], self.fs, q_last, a);
+            self.realization.set_coeffs(coeffs);
+            *y = self.realization.process(x);
+        }
+
+        self.coeffs.set_coeffs(coeffs);
+        let fc_last = fc_ratio(fc[fc.len() - 1], self.fs) * self.fs;
+        let (b0, b1, b2, a0, a1, a2) = coeffs;
+        self.smoother.snap([fc_last, q_last, dbgain], [b0, b1, b2, a0, a1, a2]);
+        Ok(())
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: &[f64]
+    ///         input frame
+    ///
+    /// Return
+    /// ------
+    ///     Vec<T>
+    ///         filtered frame
+    ///
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
         
-        let y: Vec<f64> = frame
+        let y: Vec<T> = frame
             .iter()
-            .map(|&x| self.filt_sample(x, coeffs))
+            .map(|&x| self.filt_sample(x))
             .collect();
         y
     }
 
     ///
     /// CLEAR DELAYED SAMPLES CACHE
-    /// set:
-    ///     x[n - 1] = 0.0
-    ///     x[n - 2] = 0.0
-    ///     y[n - 1] = 0.0
-    ///     y[n - 2] = 0.0
+    /// set the realization state to 0.0
+    /// (x[n - 1], x[n - 2], y[n - 1], y[n - 2] in direct form I)
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
-        self.x1.clear();
-        self.x2.clear();
-        self.y1.clear();
-        self.y2.clear();
-        println!("[DONE] cache cleared!")
+        self.reset();
+    }
+
+
+}
+
+impl<T: Sample> Filter<T> for Biquad<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
     }
 
+    fn reset(&mut self) {
+        self.realization.reset();
+    }
+}
 
-}
\ No newline at end of file
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for Biquad<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        let (b0, b1, b2, a0, a1, a2) = self.coeffs();
+        vec![Section::new(vec![b0, b1, b2], vec![a0, a1, a2])]
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample> SecondOrderSections for Biquad<T> {
+    fn sos(&self) -> Vec<[f64; 6]> {
+        let (b0, b1, b2, a0, a1, a2) = self.coeffs();
+        vec![[b0 / a0, b1 / a0, b2 / a0, 1.0, a1 / a0, a2 / a0]]
+    }
+}
+
+// topology, realization state and running glide (the restored filter
+// continues with the coefficients reached by the glide)
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for Biquad<T> {
+    const FAMILY: &'static str = "biquad";
+
+    fn save_state(&self, state: &mut FilterState) {
+        state.push(self.topology() as usize as f64);
+        self.realization.state().into_iter().for_each(|value| state.push(value));
+        self.smoother.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        reader.expect(self.topology() as usize)?;
+        let mut values = [T::zero(); 4];
+        for value in values.iter_mut() {
+            *value = reader.read()?;
+        }
+        self.smoother.load_state(reader)?;
+        let [b0, b1, b2, a0, a1, a2] = self.smoother.coeffs();
+        self.load_coeffs((b0, b1, b2, a0, a1, a2));
+        self.realization.set_state(values);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f64 = 48000.0;
+    const TOPOLOGIES: [BiquadTopology; 5] = [
+        BiquadTopology::DirectForm1,
+        BiquadTopology::DirectForm2,
+        BiquadTopology::TransposedDirectForm2,
+        BiquadTopology::Lattice,
+        BiquadTopology::StateSpace
+    ];
+
+    // deterministic broadband input: impulse, then two sines
+    fn signal(n: usize) -> f64 {
+        let t = n as f64;
+        if n == 0 { 1.0 } else { libm::sin(0.05 * t) + 0.5 * libm::sin(1.3 * t + 1.0) }
+    }
+
+    #[test]
+    fn topologies_produce_the_same_output() {
+        let designs = [("lp", 1000.0, 0.707, None), ("hp", 50.0, 2.0, None), ("peq", 3000.0, 4.0, Some(-12.0)), ("hps", 8000.0, 0.5, Some(6.0))];
+        for (mode, fc, q, dbgain) in designs {
+            let mut filters = TOPOLOGIES.map(|topology| {
+                let mut bq: Biquad = Biquad::with_topology(FS, topology);
+                bq.design_filter(mode, fc, q, dbgain).unwrap();
+                bq
+            });
+            for n in 0..2000 {
+                let x = signal(n);
+                let reference = filters[0].filt_sample(x);
+                for bq in filters.iter_mut().skip(1) {
+                    let y = bq.filt_sample(x);
+                    assert!((y - reference).abs() <= 1e-9 * (1.0 + reference.abs()), "{mode} {:?} sample {n}: {y} != {reference}", bq.topology());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn steady_state_gives_a_flat_output_for_dc_input() {
+        let x0 = 0.3;
+        // lp and notch pass DC, the low shelf scales it by its gain
+        for (mode, dbgain, dc_gain) in [("lp", None, 1.0), ("notch", None, 1.0), ("lps", Some(-6.0), libm::pow(10.0, -6.0 / 20.0))] {
+            for topology in TOPOLOGIES {
+                let mut bq: Biquad = Biquad::with_topology(FS, topology);
+                bq.design_filter(mode, 500.0, 0.9, dbgain).unwrap();
+                bq.set_steady_state(x0);
+                for n in 0..200 {
+                    let y = bq.filt_sample(x0);
+                    assert!((y - dc_gain * x0).abs() < 1e-12, "{mode} {topology:?} sample {n}: {y}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_coeffs_stops_the_glide() {
+        let external = (0.2, 0.3, 0.2, 1.0, -0.4, 0.1);
+        let mut bq: Biquad = Biquad::new(FS);
+        bq.design_filter("lp", 1000.0, 0.707, None).unwrap();
+        bq.set_glide(0.05, 32).unwrap();
+        bq.set_target(4000.0, 2.0, None).unwrap();
+        bq.filt_sample(1.0);
+        bq.set_coeffs(external);
+        assert!(!bq.is_gliding());
+        for n in 0..100 {
+            bq.filt_sample(signal(n));
+            assert_eq!(bq.coeffs(), external, "sample {n}");
+        }
+    }
+
+    #[test]
+    fn glide_is_zipper_free_and_reaches_the_target() {
+        let (time, interval) = (0.01, 32);
+        let n_samples = libm::round(time * FS) as usize;
+        let mut bq: Biquad = Biquad::new(FS);
+        let start = bq.design_filter("lp", 500.0, 0.707, None).unwrap();
+        let mut reference: Biquad = Biquad::new(FS);
+        let target = reference.design_filter("lp", 4000.0, 2.0, None).unwrap();
+        let start = [start.0, start.1, start.2, start.3, start.4, start.5];
+        let target = [target.0, target.1, target.2, target.3, target.4, target.5];
+
+        bq.set_glide(time, interval).unwrap();
+        bq.set_target(4000.0, 2.0, None).unwrap();
+        let mut previous = start;
+        for n in 0..n_samples {
+            bq.filt_sample(signal(n));
+            let (b0, b1, b2, a0, a1, a2) = bq.coeffs();
+            let current = [b0, b1, b2, a0, a1, a2];
+            // no step larger than a few times the mean step of the glide
+            for k in 0..6 {
+                let mean_step = (target[k] - start[k]).abs() / n_samples as f64;
+                assert!((current[k] - previous[k]).abs() <= 8.0 * mean_step + 1e-15, "coefficient {k} sample {n}");
+            }
+            previous = current;
+        }
+        assert!(!bq.is_gliding());
+        assert_eq!(previous, target);
+    }
+
+    const N_MODULATED: usize = 1000;
+
+    // exponential sweep from 200 Hz to 6 kHz
+    fn sweep() -> [f64; N_MODULATED] {
+        core::array::from_fn(|n| 200.0 * libm::pow(30.0, n as f64 / N_MODULATED as f64))
+    }
+
+    #[test]
+    fn modulated_matches_per_sample_design() {
+        let fc = sweep();
+        let q: [f64; N_MODULATED] = core::array::from_fn(|n| 0.5 + n as f64 / 200.0);
+        let input: [f64; N_MODULATED] = core::array::from_fn(signal);
+        for (mode, dbgain) in [("lp", None), ("bp0db", None), ("peq", Some(6.0))] {
+            let mut bq: Biquad = Biquad::new(FS);
+            bq.design_filter(mode, 1000.0, 0.707, dbgain).unwrap();
+            let mut output = [0.0; N_MODULATED];
+            bq.filt_modulated(&input, &fc, Some(&q), &mut output).unwrap();
+
+            let mut reference: Biquad = Biquad::new(FS);
+            for n in 0..N_MODULATED {
+                reference.design_filter(mode, fc[n], q[n], dbgain).unwrap();
+                let y = reference.filt_sample(input[n]);
+                assert!((output[n] - y).abs() < 1e-6, "{mode} sample {n}: {} != {y}", output[n]);
+            }
+        }
+    }
+
+    #[test]
+    fn modulated_clamps_nan_and_out_of_range_fc() {
+        let mut bq: Biquad = Biquad::new(FS);
+        bq.design_filter("lp", 1000.0, 0.707, None).unwrap();
+        let fc = [f64::NAN, 0.0, -10.0, f64::INFINITY, 1e9, f64::NAN];
+        let q = [f64::NAN, 1.0, 1.0, 1.0, 1.0, 1.0];
+        let mut output = [0.0; 6];
+        bq.filt_modulated(&[1.0; 6], &fc, Some(&q), &mut output).unwrap();
+        assert!(output.iter().all(|y| y.is_finite()));
+        let (fc_last, _, _) = bq.params();
+        assert!(fc_last > 0.0 && fc_last.is_finite());
+    }
+}
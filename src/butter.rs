@@ -1,294 +1,116 @@
-#![allow(clippy::wrong_self_convention)]
-#![allow(clippy::new_without_default)]
+use super::{sample::Sample, filtertype::{FilterType, ButterFilterType}, iirdesign::{Band, butterworth_prototype, design_sos, check_design_order}, iirfilter::{IirFilter, IirFamily}, error::{Result, FilterError, ModeName}};
+use alloc::vec::Vec;
 
-use pyo3::prelude::*;
-use super::{coeffstruct::OnePoleCoeffs, filtertype::{FilterType, ButterFilterType}, delayline::DelayLine};
+/// Butterworth family of IirFilter
+#[derive(Clone, Copy, Debug)]
+pub struct ButterFamily;
 
-struct DesignButterFilter {
-    filt_coeffs: OnePoleCoeffs,
-}
-
-impl DesignButterFilter {
-    fn new() -> Self {
-        let filt_coeffs = OnePoleCoeffs::new();
-        Self {
-            filt_coeffs
-        }
-    }
-
-    fn coeffs(&mut self, mode: FilterType, fc: f64, fs: f64) {
-        let twopi = 2.0 * std::f64::consts::PI;
-        let wc = twopi * fc;
-        let ts = 1.0 / fs;
-        let wtan = 2.0 * (wc * ts / 2.0).tan();
-        match mode {
-            FilterType::ButterType(ButterFilterType::Lp) => {
-                let b0 = wtan / (2.0 + wtan);
-                let b1 = b0;
-                let a1 = (wtan - 2.0) / (2.0 + wtan);
-
-                self.filt_coeffs.set_coeffs((b0, b1, a1))
-            },
-            FilterType::ButterType(ButterFilterType::Hp) => {
-                let b0 = 2.0 / (2.0 + wtan);
-                let b1 = -b0;
-                let a1 = (wtan - 2.0) / (2.0 + wtan);
-
-                self.filt_coeffs.set_coeffs((b0, b1, a1))
-            },
-            _ => {}
-        }
-    }
+impl IirFamily for ButterFamily {
+    const NAME: &'static str = "butter";
+    const INITIAL_MODE: FilterType = FilterType::ButterType(ButterFilterType::Lp);
 }
 
+pub type Butter<T = f64> = IirFilter<ButterFamily, T>;
 
-#[pyclass]
-pub struct Butter {
-    mode: String,
-    fs: f64,
-    order: usize,
-    xtemp: DelayLine,
-    ytemp: DelayLine,
-    x1: DelayLine,
-    y1: DelayLine,
-}
-
-#[pymethods]
-impl Butter {
-    #[new]
-
-    ///
-    /// INIT BUTTER CLASS
-    /// 
-    /// Args
-    /// ----
-    ///     fs: f64
-    ///         sampling rate
-    ///     order: usize
-    ///         filter order
-    ///
-
-    #[pyo3(text_signature = "(fs: float, order: int = 1) -> None")]
-    pub fn new(fs: f64, order: Option<usize>) -> Self {
-        let filt_order = match order {
-            Some(order_value) => { order_value },
-            None => { 1 }
-        };
-
-        Self { 
-            mode: String::from(""), 
-            fs, 
-            order: filt_order, 
-            xtemp: DelayLine::new(filt_order), 
-            ytemp: DelayLine::new(filt_order), 
-            x1: DelayLine::new(filt_order), 
-            y1: DelayLine::new(filt_order),
-        }
-    }
-
+impl<T: Sample> IirFilter<ButterFamily, T> {
     ///
     /// GENERATE BUTTERWORTH FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame). Butterworth
+    /// poles of the analog prototype, band transform and bilinear transform
+    /// (prewarped band edges), stored as cascaded second order sections (odd
+    /// orders end with a first order section, b2 = a2 = 0)
     ///
     /// Args
     /// ----
     ///     mode: &str
-    ///         lp = first order butterworth low pass filter IIR
-    ///         hp = first order butterworth high pass filter IIR
-    ///         bp = band pass from lp + hp
-    ///         br = notch from hp + lp
+    ///         lp = butterworth low pass filter IIR, -3 dB at fc
+    ///         hp = butterworth high pass filter IIR, -3 dB at fc
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2 (-3 dB edges)
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2 (-3 dB edges)
     ///     fc: f64
-    ///         cut off frequency in Hz
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
-    ///         [b0, b1, a1] or [b0lp, b1lp, a1lp, b0hp, b1hp, a1hp] or [b0hp, b1hp, a1hp, b0lp, b1lp, a1lp]
-    ///
-    ///
-
-    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: Option<f64>) -> Vec<f64> {
-        
-        let mut design_filter = DesignButterFilter::new();
-
-        let coeffs = match mode {
-            "lp" => { 
-                design_filter.coeffs(FilterType::ButterType(ButterFilterType::Lp), fc, self.fs);
-                let b0 = design_filter.filt_coeffs.b0; 
-                let b1 = design_filter.filt_coeffs.b1;
-                let a1 = design_filter.filt_coeffs.a1;
-
-                vec![b0, b1, a1]
-            },
-            "hp" => {
-                design_filter.coeffs(FilterType::ButterType(ButterFilterType::Hp), fc, self.fs);
-                let b0 = design_filter.filt_coeffs.b0; 
-                let b1 = design_filter.filt_coeffs.b1;
-                let a1 = design_filter.filt_coeffs.a1;
+    ///     Result<Vec<f64>>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///         FilterError on unknown mode, order 0, invalid fc or bw, missing bw
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: Option<f64>) -> Result<Vec<f64>> {
+        let band = Band::from_mode(mode, fc, bw, self.fs)?
+            .ok_or(FilterError::UnknownMode { family: ButterFamily::NAME, mode: ModeName::new(mode) })?;
+        check_design_order(self.order)?;
+
+        let sections = design_sos(&butterworth_prototype(self.order), band, self.fs);
+        let filter_type = FilterType::ButterType(match band {
+            Band::Lowpass(_) => ButterFilterType::Lp,
+            Band::Highpass(_) => ButterFilterType::Hp,
+            Band::Bandpass(..) => ButterFilterType::Bp,
+            Band::Bandstop(..) => ButterFilterType::Notch
+        });
+        Ok(self.set_design(&sections, filter_type))
+    }
+}
 
-                vec![b0, b1, a1]
-            },
-            "bp" => { 
-                match bw { 
-                    Some(bw_value) => { 
-                        design_filter.coeffs(FilterType::ButterType(ButterFilterType::Lp), fc + bw_value / 2.0, self.fs);
-                        let b0lp = design_filter.filt_coeffs.b0; 
-                        let b1lp = design_filter.filt_coeffs.b1;
-                        let a1lp = design_filter.filt_coeffs.a1;
-        
-                        design_filter.coeffs(FilterType::ButterType(ButterFilterType::Hp), fc - bw_value / 2.0, self.fs);
-                        let b0hp = design_filter.filt_coeffs.b0; 
-                        let b1hp = design_filter.filt_coeffs.b1;
-                        let a1hp = design_filter.filt_coeffs.a1;
-                        
-                        vec![b0lp, b1lp, a1lp, b0hp, b1hp, a1hp]  
-                    }
-                
-                    None => {
-                        println!("[ERROR] Band width not defined!");
-                        std::process::exit(1)
-                    }
-                }
-            },
-            "br" => { 
-                match bw { 
-                    Some(bw_value) => { 
-                        design_filter.coeffs(FilterType::ButterType(ButterFilterType::Lp), fc - bw_value / 2.0, self.fs);
-                        let b0lp = design_filter.filt_coeffs.b0; 
-                        let b1lp = design_filter.filt_coeffs.b1;
-                        let a1lp = design_filter.filt_coeffs.a1;
-        
-                        design_filter.coeffs(FilterType::ButterType(ButterFilterType::Hp), fc + bw_value / 2.0, self.fs);
-                        let b0hp = design_filter.filt_coeffs.b0; 
-                        let b1hp = design_filter.filt_coeffs.b1;
-                        let a1hp = design_filter.filt_coeffs.a1;
-                        
-                        vec![b0lp, b1lp, a1lp, b0hp, b1hp, a1hp]  
-                    }
-                
-                    None => {
-                        println!("[ERROR] Band width not defined!");
-                        std::process::exit(1)
-                    }
-                }
-            },
-            _ => {
-                println!("[ERROR] Filter mode not allowed!");
-                std::process::exit(1)
-            }
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::freqz::FrequencyResponse;
 
-        self.mode = String::from(mode);
+    const FS: f64 = 48000.0;
+    // 20 * log10(1 / sqrt(2))
+    const HALF_POWER_DB: f64 = -3.010299956639812;
 
-        coeffs
+    fn magnitude_db(filter: &Butter, f: f64) -> f64 {
+        filter.freqz(&[f]).magnitude_db()[0]
     }
 
-    ///
-    /// APPLY FILTER SAMPLE BY SAMPLE
-    ///
-    /// Args
-    /// ----
-    ///     sample: f64
-    ///         input sample
-    ///     coeffs: Vec<f64>
-    ///         filter coefficients from fn design_filter
-    ///
-    /// Return
-    /// ------
-    ///     f64
-    ///         filtered sample
-    ///
-    ///
-
-    #[pyo3(text_signature = "(sample: float, coeffs: list[float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: Vec<f64>) -> f64 {
-
-        let mut x1 = sample;
-        let mut x2 = sample;
-        let mut y: f64 = 0.0;
-        
-        if self.mode.eq("bp") {
-            for _ in 0..self.order {
-                let _ytemp = coeffs[0] * x1 + coeffs[1] * self.xtemp.read() - coeffs[2] * self.ytemp.read(); 
-                y = coeffs[3] * _ytemp + coeffs[4] * self.x1.read() - coeffs[5] * self.y1.read();
-                self.xtemp.write_and_advance(&x1);
-                self.ytemp.write_and_advance(&_ytemp);
-                self.x1.write_and_advance(&_ytemp);
-                self.y1.write_and_advance(&y);
-                x1 = _ytemp;
-            }
-            y
-        } else if self.mode.eq("br") {
-            for _ in 0..self.order {
-                let _ylp = coeffs[0] * x1 + coeffs[1] * self.xtemp.read() - coeffs[2] * self.ytemp.read();
-                let _yhp = coeffs[3] * x2 + coeffs[4] * self.x1.read() - coeffs[5] * self.y1.read();
-                self.xtemp.write_and_advance(&x1);
-                self.ytemp.write_and_advance(&_ylp);
-                self.x1.write_and_advance(&x2);
-                self.y1.write_and_advance(&_yhp);
-                y = _ylp + _yhp;
-                x1 = _ylp;
-                x2 = _yhp;
-            }
-            y
-        } else {
-            for _ in 0..self.order {
-                y = coeffs[0] * x1 + coeffs[1] * self.x1.read() - coeffs[2] * self.y1.read();
-                self.x1.write_and_advance(&x1);
-                self.y1.write_and_advance(&y);
-                x1 = y;
+    #[test]
+    fn butter_is_3_db_down_at_the_band_edges() {
+        for order in 1..=10 {
+            let mut lp: Butter = Butter::new(FS, order);
+            lp.design_filter("lp", 1000.0, None).unwrap();
+            assert!((magnitude_db(&lp, 1000.0) - HALF_POWER_DB).abs() < 1e-6, "lp order {order}: {} dB at fc", magnitude_db(&lp, 1000.0));
+            assert!(magnitude_db(&lp, 0.0).abs() < 1e-9);
+
+            let mut hp: Butter = Butter::new(FS, order);
+            hp.design_filter("hp", 3000.0, None).unwrap();
+            assert!((magnitude_db(&hp, 3000.0) - HALF_POWER_DB).abs() < 1e-6, "hp order {order}: {} dB at fc", magnitude_db(&hp, 3000.0));
+
+            for mode in ["bp", "br"] {
+                let mut band: Butter = Butter::new(FS, order);
+                band.design_filter(mode, 2000.0, Some(1000.0)).unwrap();
+                for edge in [1500.0, 2500.0] {
+                    assert!((magnitude_db(&band, edge) - HALF_POWER_DB).abs() < 1e-6, "{mode} order {order}: {} dB at {edge} Hz", magnitude_db(&band, edge));
+                }
             }
-            y
         }
-
-        // self.xtemp = xtemp;
-        // self.ytemp = ytemp;
-        // self.x1 = x1;
-        // self.y1 = y1;
-        
     }
 
-    ///
-    /// APPLY FILTER ON FRAME OR SIGNAL
-    ///
-    /// Args
-    /// ----
-    ///     frame: Vec<f64>
-    ///         input frame
-    ///     coeffs: Vec<f64>
-    ///         filter coefficients from design_filter
-    ///
-    /// Return
-    /// ------
-    ///     Vec<f64>
-    ///         filtered frame
-    ///
-    ///
-
-    #[pyo3(text_signature = "(frame: list[float], coeffs: list[float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: Vec<f64>) -> Vec<f64> {
-
-        let y = frame
-            .iter()
-            .map(|&x| self.filt_sample(x, coeffs.to_vec()))
-            .collect();
-        y
-
+    #[test]
+    fn butter_rolls_off_6_db_per_octave_and_order() {
+        for order in 1..=8 {
+            let mut lp: Butter = Butter::new(FS, order);
+            lp.design_filter("lp", 100.0, None).unwrap();
+            // far from Nyquist the bilinear transform barely warps the slope
+            let slope = magnitude_db(&lp, 1600.0) - magnitude_db(&lp, 3200.0);
+            assert!((slope - 6.0206 * order as f64).abs() < 0.1 * order as f64, "order {order}: {slope} dB per octave");
+        }
     }
 
-    ///
-    /// CLEAR DELAYED SAMPLES CACHE
-    /// set:
-    ///     xtemp[n - 1] = 0.0
-    ///     ytemp[n - 1] = 0.0
-    ///     x1[n - 1] = 0.0
-    ///     y1[n - 1] = 0.0
-    ///
-
-    pub fn clear_delayed_samples_cache(&mut self) {
-        self.xtemp.clear();
-        self.ytemp.clear();
-        self.x1.clear();
-        self.y1.clear();
-        println!("[DONE] cache cleared!")
+    #[test]
+    fn steady_state_gives_a_flat_output_for_dc_input() {
+        let x0 = 1.5;
+        for order in [1, 2, 7] {
+            let mut lp: Butter = Butter::new(FS, order);
+            lp.design_filter("lp", 300.0, None).unwrap();
+            lp.set_steady_state(x0);
+            for n in 0..200 {
+                let y = lp.filt_sample(x0);
+                assert!((y - x0).abs() < 1e-12, "order {order} sample {n}: {y}");
+            }
+        }
     }
-}
\ No newline at end of file
+}
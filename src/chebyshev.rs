@@ -0,0 +1,164 @@
+use super::{sample::Sample, filtertype::{FilterType, ChebyshevFilterType}, iirdesign::{Band, chebyshev1_prototype, chebyshev2_prototype, design_sos, check_design_order}, iirfilter::{IirFilter, IirFamily}, error::{Result, FilterError, ModeName, check_ripple, check_attenuation}};
+use alloc::vec::Vec;
+
+fn chebyshev_type(band: &Band) -> FilterType {
+    FilterType::ChebyshevType(match band {
+        Band::Lowpass(_) => ChebyshevFilterType::Lp,
+        Band::Highpass(_) => ChebyshevFilterType::Hp,
+        Band::Bandpass(..) => ChebyshevFilterType::Bp,
+        Band::Bandstop(..) => ChebyshevFilterType::Br
+    })
+}
+
+/// Chebyshev type I family of IirFilter
+#[derive(Clone, Copy, Debug)]
+pub struct Chebyshev1Family;
+
+impl IirFamily for Chebyshev1Family {
+    const NAME: &'static str = "chebyshev1";
+    const INITIAL_MODE: FilterType = FilterType::ChebyshevType(ChebyshevFilterType::Lp);
+}
+
+/// Chebyshev type II family of IirFilter
+#[derive(Clone, Copy, Debug)]
+pub struct Chebyshev2Family;
+
+impl IirFamily for Chebyshev2Family {
+    const NAME: &'static str = "chebyshev2";
+    const INITIAL_MODE: FilterType = FilterType::ChebyshevType(ChebyshevFilterType::Lp);
+}
+
+pub type Chebyshev1<T = f64> = IirFilter<Chebyshev1Family, T>;
+pub type Chebyshev2<T = f64> = IirFilter<Chebyshev2Family, T>;
+
+impl<T: Sample> IirFilter<Chebyshev1Family, T> {
+    ///
+    /// GENERATE CHEBYSHEV TYPE I FILTER COEFFICIENTS
+    /// equiripple pass band, monotonic stop band. Designed from the analog
+    /// prototype through the bilinear transform (prewarped band edges) and
+    /// stored as cascaded second order sections
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz, the pass
+    ///         band edge where the response leaves the ripple band
+    ///     ripple: f64
+    ///         pass band ripple in dB
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///         FilterError on unknown mode, order 0, invalid fc, bw or ripple, missing bw
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, ripple: f64, bw: Option<f64>) -> Result<Vec<f64>> {
+        let band = Band::from_mode(mode, fc, bw, self.fs)?
+            .ok_or(FilterError::UnknownMode { family: Chebyshev1Family::NAME, mode: ModeName::new(mode) })?;
+        check_design_order(self.order)?;
+        check_ripple(ripple)?;
+
+        let sections = design_sos(&chebyshev1_prototype(self.order, ripple), band, self.fs);
+        Ok(self.set_design(&sections, chebyshev_type(&band)))
+    }
+}
+
+impl<T: Sample> IirFilter<Chebyshev2Family, T> {
+    ///
+    /// GENERATE CHEBYSHEV TYPE II FILTER COEFFICIENTS
+    /// (inverse Chebyshev) monotonic pass band, equiripple stop band.
+    /// Designed from the analog prototype through the bilinear transform
+    /// (prewarped band edges) and stored as cascaded second order sections
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///         bp = band pass, stop band edges at fc - bw / 2 and fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2
+    ///     fc: f64
+    ///         stop band edge (lp, hp) or center (bp, br) frequency in Hz,
+    ///         where the attenuation first reaches attenuation dB
+    ///     attenuation: f64
+    ///         minimum stop band attenuation in dB
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///         FilterError on unknown mode, order 0, invalid fc, bw or attenuation, missing bw
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, attenuation: f64, bw: Option<f64>) -> Result<Vec<f64>> {
+        let band = Band::from_mode(mode, fc, bw, self.fs)?
+            .ok_or(FilterError::UnknownMode { family: Chebyshev2Family::NAME, mode: ModeName::new(mode) })?;
+        check_design_order(self.order)?;
+        check_attenuation(attenuation)?;
+
+        let sections = design_sos(&chebyshev2_prototype(self.order, attenuation), band, self.fs);
+        Ok(self.set_design(&sections, chebyshev_type(&band)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::freqz::FrequencyResponse;
+
+    const FS: f64 = 48000.0;
+
+    fn magnitude_db(filter: &impl FrequencyResponse, f: f64) -> f64 {
+        filter.freqz(&[f]).magnitude_db()[0]
+    }
+
+    #[test]
+    fn chebyshev1_ripple_at_the_band_edge() {
+        for (order, ripple) in [(2, 0.5), (5, 1.0), (8, 3.0)] {
+            let mut lp: Chebyshev1 = Chebyshev1::new(FS, order);
+            lp.design_filter("lp", 1000.0, ripple, None).unwrap();
+            assert!((magnitude_db(&lp, 1000.0) + ripple).abs() < 1e-6, "order {order}: {} dB at fc", magnitude_db(&lp, 1000.0));
+            // the pass band stays in the ripple band
+            for k in 0..100 {
+                let db = magnitude_db(&lp, 10.0 * k as f64);
+                assert!(db <= 1e-9 && db >= -ripple - 1e-9, "order {order}: {db} dB in the pass band");
+            }
+
+            let mut hp: Chebyshev1 = Chebyshev1::new(FS, order);
+            hp.design_filter("hp", 5000.0, ripple, None).unwrap();
+            assert!((magnitude_db(&hp, 5000.0) + ripple).abs() < 1e-6);
+
+            let mut bp: Chebyshev1 = Chebyshev1::new(FS, order);
+            bp.design_filter("bp", 2000.0, ripple, Some(1000.0)).unwrap();
+            for edge in [1500.0, 2500.0] {
+                assert!((magnitude_db(&bp, edge) + ripple).abs() < 1e-6, "order {order}: {} dB at {edge} Hz", magnitude_db(&bp, edge));
+            }
+        }
+    }
+
+    #[test]
+    fn chebyshev2_attenuation_at_the_stop_band_edge() {
+        for (order, attenuation) in [(3, 40.0), (6, 60.0)] {
+            let mut lp: Chebyshev2 = Chebyshev2::new(FS, order);
+            lp.design_filter("lp", 2000.0, attenuation, None).unwrap();
+            assert!((magnitude_db(&lp, 2000.0) + attenuation).abs() < 1e-6, "order {order}: {} dB at fc", magnitude_db(&lp, 2000.0));
+            // the stop band stays below the attenuation
+            for k in 0..200 {
+                let db = magnitude_db(&lp, 2000.0 + 110.0 * k as f64);
+                assert!(db <= -attenuation + 1e-6, "order {order}: {db} dB in the stop band");
+            }
+
+            let mut hp: Chebyshev2 = Chebyshev2::new(FS, order);
+            hp.design_filter("hp", 500.0, attenuation, None).unwrap();
+            assert!((magnitude_db(&hp, 500.0) + attenuation).abs() < 1e-6);
+        }
+    }
+}
@@ -1,46 +1,86 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-pub struct BiquadCoeffs {
-    pub b0: f64,
-    pub b1: f64,
-    pub b2: f64,
-    pub a0: f64,
-    pub a1: f64,
-    pub a2: f64
+use super::sample::Sample;
+
+///
+/// BIQUAD COEFFICIENTS
+/// stored in the processing precision T, set and read in f64 (design precision)
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoeffs<T: Sample = f64> {
+    pub b0: T,
+    pub b1: T,
+    pub b2: T,
+    pub a0: T,
+    pub a1: T,
+    pub a2: T
 }
 
-impl BiquadCoeffs {
+impl<T: Sample> BiquadCoeffs<T> {
     pub fn new() -> Self {
-        Self { b0: 0.0, b1: 0.0, b2: 0.0, a0: 0.0, a1: 0.0, a2: 0.0 }
+        Self { b0: T::zero(), b1: T::zero(), b2: T::zero(), a0: T::zero(), a1: T::zero(), a2: T::zero() }
+    }
+
+    pub fn with_coeffs(coeffs: (f64, f64, f64, f64, f64, f64)) -> Self {
+        let mut filt_coeffs = Self::new();
+        filt_coeffs.set_coeffs(coeffs);
+        filt_coeffs
     }
 
     pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
-        self.b0 = coeffs.0;
-        self.b1 = coeffs.1;
-        self.b2 = coeffs.2;
-        self.a0 = coeffs.3;
-        self.a1 = coeffs.4;
-        self.a2 = coeffs.5;
+        self.b0 = T::from_f64(coeffs.0);
+        self.b1 = T::from_f64(coeffs.1);
+        self.b2 = T::from_f64(coeffs.2);
+        self.a0 = T::from_f64(coeffs.3);
+        self.a1 = T::from_f64(coeffs.4);
+        self.a2 = T::from_f64(coeffs.5);
+    }
+
+    pub fn get_coeffs(&self) -> (f64, f64, f64, f64, f64, f64) {
+        (self.b0.to_f64(), self.b1.to_f64(), self.b2.to_f64(), self.a0.to_f64(), self.a1.to_f64(), self.a2.to_f64())
+    }
+
+    pub fn cast<U: Sample>(&self) -> BiquadCoeffs<U> {
+        BiquadCoeffs::with_coeffs(self.get_coeffs())
     }
 
 }
 
-pub struct OnePoleCoeffs {
-    pub b0: f64,
-    pub b1: f64,
-    pub a1: f64
+///
+/// ONE POLE COEFFICIENTS
+/// stored in the processing precision T, set and read in f64 (design precision)
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OnePoleCoeffs<T: Sample = f64> {
+    pub b0: T,
+    pub b1: T,
+    pub a1: T
 }
 
-impl OnePoleCoeffs {
+impl<T: Sample> OnePoleCoeffs<T> {
     pub fn new() -> Self {
-        Self { b0: 0.0, b1: 0.0, a1: 0.0 }
+        Self { b0: T::zero(), b1: T::zero(), a1: T::zero() }
+    }
+
+    pub fn with_coeffs(coeffs: (f64, f64, f64)) -> Self {
+        let mut filt_coeffs = Self::new();
+        filt_coeffs.set_coeffs(coeffs);
+        filt_coeffs
     }
 
     pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64)) {
-        self.b0 = coeffs.0;
-        self.b1 = coeffs.1;
-        self.a1 = coeffs.2;
+        self.b0 = T::from_f64(coeffs.0);
+        self.b1 = T::from_f64(coeffs.1);
+        self.a1 = T::from_f64(coeffs.2);
+    }
+
+    pub fn get_coeffs(&self) -> (f64, f64, f64) {
+        (self.b0.to_f64(), self.b1.to_f64(), self.a1.to_f64())
     }
 
-}
\ No newline at end of file
+    pub fn cast<U: Sample>(&self) -> OnePoleCoeffs<U> {
+        OnePoleCoeffs::with_coeffs(self.get_coeffs())
+    }
+
+}
@@ -0,0 +1,304 @@
+#![allow(clippy::wrong_self_convention)]
+#![allow(clippy::new_without_default)]
+
+use core::marker::PhantomData;
+use alloc::{vec, vec::Vec};
+use num_complex::Complex64;
+use super::{sample::Sample, fft::RealFft, filter::Filter, error::{Result, FilterError}};
+use super::freqz::{FrequencyResponse, Section};
+use super::snapshot::{Snapshot, FilterState, StateReader};
+
+#[derive(Clone)]
+pub struct Convolver<T: Sample = f64> {
+    fs: f64,
+    block_size: usize,
+    impulse_response: Vec<f64>,
+    fft: RealFft,
+    // spectra of the impulse response partitions, block_size + 1 bins each
+    partitions: Vec<Vec<Complex64>>,
+    // frequency domain delay line: spectra of the last input windows, the
+    // newest at head
+    spectra: Vec<Vec<Complex64>>,
+    head: usize,
+    // previous block | current block
+    input: Vec<f64>,
+    // output of the last processed block
+    output: Vec<f64>,
+    position: usize,
+    accumulator: Vec<Complex64>,
+    window: Vec<f64>,
+    sample: PhantomData<T>
+}
+
+impl<T: Sample> Convolver<T> {
+    ///
+    /// INIT CONVOLVER CLASS
+    /// uniformly partitioned overlap-save convolution: the impulse response
+    /// is split into blocks of block_size taps, every block of input costs
+    /// one FFT, one inverse FFT and a spectral product per partition. The
+    /// output is delayed by block_size samples (latency)
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     impulse_response: &[f64]
+    ///         FIR taps or measured impulse response
+    ///     block_size: usize
+    ///         partition and processing block size, power of two
+    ///
+    /// Return
+    /// ------
+    ///     Result<Convolver>
+    ///         FilterError on empty impulse response or block size not a power of two
+    ///
+    pub fn new(fs: f64, impulse_response: &[f64], block_size: usize) -> Result<Self> {
+        if !block_size.is_power_of_two() {
+            return Err(FilterError::InvalidBlockSize(block_size))
+        }
+        let mut convolver = Self {
+            fs,
+            block_size,
+            impulse_response: Vec::new(),
+            fft: RealFft::new(2 * block_size),
+            partitions: Vec::new(),
+            spectra: Vec::new(),
+            head: 0,
+            input: vec![0.0; 2 * block_size],
+            output: vec![0.0; block_size],
+            position: 0,
+            accumulator: vec![Complex64::new(0.0, 0.0); block_size + 1],
+            window: vec![0.0; 2 * block_size],
+            sample: PhantomData
+        };
+        convolver.set_impulse_response(impulse_response)?;
+        Ok(convolver)
+    }
+
+    ///
+    /// SET IMPULSE RESPONSE
+    /// (the state is kept when the number of partitions doesn't change)
+    ///
+    /// Args
+    /// ----
+    ///     impulse_response: &[f64]
+    ///         FIR taps or measured impulse response
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on empty impulse response
+    ///
+    pub fn set_impulse_response(&mut self, impulse_response: &[f64]) -> Result<()> {
+        if impulse_response.is_empty() {
+            return Err(FilterError::EmptyImpulseResponse)
+        }
+        let block_size = self.block_size;
+        let mut padded = vec![0.0; 2 * block_size];
+        self.partitions = impulse_response.chunks(block_size).map(|taps| {
+            padded.fill(0.0);
+            padded[..taps.len()].copy_from_slice(taps);
+            let mut spectrum = vec![Complex64::new(0.0, 0.0); block_size + 1];
+            self.fft.forward(&padded, &mut spectrum);
+            spectrum
+        }).collect();
+        self.impulse_response = impulse_response.to_vec();
+
+        if self.spectra.len() != self.partitions.len() {
+            self.spectra = vec![vec![Complex64::new(0.0, 0.0); block_size + 1]; self.partitions.len()];
+            self.reset();
+        }
+        Ok(())
+    }
+
+    pub fn impulse_response(&self) -> &[f64] {
+        &self.impulse_response
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    ///
+    /// LATENCY
+    /// block_size samples, added to the delay of the impulse response
+    ///
+    pub fn latency(&self) -> usize {
+        self.block_size
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    /// (a block is convolved every block_size samples)
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample, block_size samples late
+    ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        let y = self.output[self.position];
+        self.input[self.block_size + self.position] = sample.to_f64();
+        self.position += 1;
+        if self.position == self.block_size {
+            self.process_block();
+            self.position = 0;
+        }
+        T::from_f64(y)
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    /// frames of any length, the block boundaries are kept across calls
+    ///
+    /// Args
+    /// ----
+    ///     frame: &[T]
+    ///         input frame
+    ///
+    /// Return
+    /// ------
+    ///     Vec<T>
+    ///         filtered frame
+    ///
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
+        frame.iter().map(|&x| self.filt_sample(x)).collect()
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set the input history, the delay line spectra and the pending output
+    /// to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+
+    // overlap-save step: the last 2 block_size inputs against every
+    // partition, the last block_size samples of the circular convolution
+    // are the linear convolution
+    fn process_block(&mut self) {
+        let n_partitions = self.partitions.len();
+        self.head = (self.head + n_partitions - 1) % n_partitions;
+        self.fft.forward(&self.input, &mut self.spectra[self.head]);
+
+        self.accumulator.fill(Complex64::new(0.0, 0.0));
+        for (p, partition) in self.partitions.iter().enumerate() {
+            let spectrum = &self.spectra[(self.head + p) % n_partitions];
+            for ((acc, &h), &x) in self.accumulator.iter_mut().zip(partition).zip(spectrum) {
+                *acc += h * x;
+            }
+        }
+        self.fft.inverse(&self.accumulator, &mut self.window);
+
+        self.output.copy_from_slice(&self.window[self.block_size..]);
+        self.input.copy_within(self.block_size.., 0);
+    }
+}
+
+impl<T: Sample> Filter<T> for Convolver<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
+        self.spectra.iter_mut().for_each(|spectrum| spectrum.fill(Complex64::new(0.0, 0.0)));
+        self.input.fill(0.0);
+        self.output.fill(0.0);
+        self.head = 0;
+        self.position = 0;
+    }
+}
+
+// response of the impulse response, the block latency is not included
+impl<T: Sample> FrequencyResponse for Convolver<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        vec![Section::new(self.impulse_response.clone(), vec![1.0])]
+    }
+}
+
+// block size, number of partitions, position in the block, input and
+// pending output blocks, delay line spectra from the newest
+impl<T: Sample> Snapshot for Convolver<T> {
+    const FAMILY: &'static str = "convolver";
+
+    fn save_state(&self, state: &mut FilterState) {
+        let n_partitions = self.spectra.len();
+        state.push(self.block_size as f64);
+        state.push(n_partitions as f64);
+        state.push(self.position as f64);
+        self.input.iter().chain(&self.output).for_each(|&x| state.push(x));
+        for p in 0..n_partitions {
+            for bin in &self.spectra[(self.head + p) % n_partitions] {
+                state.push(bin.re);
+                state.push(bin.im);
+            }
+        }
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        reader.expect(self.block_size)?;
+        reader.expect(self.spectra.len())?;
+        self.position = reader.read_index(self.block_size)?;
+        for x in self.input.iter_mut().chain(self.output.iter_mut()) {
+            *x = reader.read()?;
+        }
+        self.head = 0;
+        for spectrum in self.spectra.iter_mut() {
+            for bin in spectrum.iter_mut() {
+                *bin = Complex64::new(reader.read()?, reader.read()?);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // deterministic pseudo random values in [-1, 1)
+    fn noise(n: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..n).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+        }).collect()
+    }
+
+    fn direct_convolution(x: &[f64], h: &[f64]) -> Vec<f64> {
+        (0..x.len()).map(|n| h.iter().enumerate().take(n + 1).map(|(k, &h)| h * x[n - k]).sum()).collect()
+    }
+
+    #[test]
+    fn matches_direct_convolution() {
+        // impulse responses shorter, longer and not a multiple of the block
+        for (ir_length, block_size) in [(50, 128), (4097, 256), (300, 64), (1, 16), (128, 128)] {
+            let h = noise(ir_length, ir_length as u64);
+            let x = noise(3000, 7);
+            let mut convolver: Convolver = Convolver::new(48000.0, &h, block_size).unwrap();
+            let y = convolver.filt_frame(&x);
+            let expected = direct_convolution(&x, &h);
+            // the output is block_size samples late
+            for n in block_size..x.len() {
+                assert!((y[n] - expected[n - block_size]).abs() < 1e-10, "{ir_length} taps, block {block_size}, sample {n}: {} != {}", y[n], expected[n - block_size]);
+            }
+            assert!(y[..block_size].iter().all(|&y| y == 0.0));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        assert_eq!(Convolver::<f64>::new(48000.0, &[1.0], 100).err(), Some(FilterError::InvalidBlockSize(100)));
+        assert_eq!(Convolver::<f64>::new(48000.0, &[], 64).err(), Some(FilterError::EmptyImpulseResponse));
+    }
+}
@@ -2,8 +2,13 @@
 #![allow(clippy::new_without_default)]
 #![allow(clippy::single_match)]
 
-use super::{filtertype::{FilterType, DcBlockFilterType}, coeffstruct::OnePoleCoeffs, delayline::DelayLine};
-use pyo3::prelude::*;
+use super::{sample::Sample, filtertype::{FilterType, DcBlockFilterType}, coeffstruct::OnePoleCoeffs, delayline::DelayLine, filter::Filter, error::{Result, FilterError, ModeName, check_fc}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
 struct DesignDcFilter {
     mode: FilterType,
@@ -14,7 +19,7 @@ struct DesignDcFilter {
 impl DesignDcFilter {
     fn new(mode: FilterType, fc: f64, fs: f64) -> Self {
         let filt_coeffs = OnePoleCoeffs::new();
-        let twopi = 2.0 * std::f64::consts::PI;
+        let twopi = 2.0 * core::f64::consts::PI;
         let w = twopi * fc / fs;
         let r = 1.0 - w;
         
@@ -41,17 +46,15 @@ fn _filt_sample(x: &f64, coeffs: &(f64, f64), x1: f64, y1: f64) -> f64 {
     coeffs.0 * x - x1 + coeffs.1 * y1
 }
 
-#[pyclass]
-pub struct DcFilter {
+#[derive(Clone)]
+pub struct DcFilter<T: Sample = f64> {
     fs: f64,
-    _x: DelayLine,
-    _y: DelayLine
+    coeffs: OnePoleCoeffs<T>,
+    _x: DelayLine<T>,
+    _y: DelayLine<T>
 }
 
-#[pymethods]
-impl DcFilter {
-    #[new]
-
+impl<T: Sample> DcFilter<T> {
     ///
     /// INIT DCFILTER CLASS
     ///
@@ -60,13 +63,13 @@ impl DcFilter {
     ///     fs: f64
     ///         sampling rate 
     ///
-    
     pub fn new(fs: f64) -> Self {
-        Self { fs, _x: DelayLine::new(1), _y: DelayLine::new(1) }
+        Self { fs, coeffs: OnePoleCoeffs::with_coeffs((1.0, 0.0, 1.0)), _x: DelayLine::new_unchecked(1), _y: DelayLine::new_unchecked(1) }
     }
 
     ///
     /// GENERATE DC FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame)
     ///
     /// Args
     /// ----
@@ -78,48 +81,69 @@ impl DcFilter {
     /// 
     /// Return
     /// ------
-    ///     tuple -> (f64, f64):
+    ///     Result<tuple> -> (f64, f64):
     ///         filter coefficients (b0, a1)
+    ///         FilterError on unknown mode or invalid fc
     ///
-
-    #[pyo3(text_signature = "(mode: str, fc: float) -> tuple[float, float]")]
-    pub fn design_filter(&mut self, mode: &str, fc: f64) -> (f64, f64) {
+    pub fn design_filter(&mut self, mode: &str, fc: f64) -> Result<(f64, f64)> {
 
         let filt_type: FilterType = match mode {
             "dcj" => FilterType::DcBlockType(DcBlockFilterType::DcBlockJulius),
-            _ => {
-                println!("[ERROR] Filt mode not allowed!");
-                std::process::exit(1)
-            }          
+            _ => return Err(FilterError::UnknownMode { family: "dc", mode: ModeName::new(mode) })
         };
 
+        check_fc(fc, self.fs)?;
+
         let mut design_filter = DesignDcFilter::new(filt_type, fc, self.fs);
         design_filter.coeffs();
 
-        (design_filter.filt_coeffs.b0, design_filter.filt_coeffs.a1)
+        self.coeffs = design_filter.filt_coeffs.cast();
+
+        Ok((design_filter.filt_coeffs.b0, design_filter.filt_coeffs.a1))
 
     }
 
     ///
-    /// APPLY FILTER SAMPLE BY SAMPLE
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design
     ///
     /// Args
     /// ----
-    ///     sample: f64
-    ///         input sample
     ///     coeffs: tuple(f64, f64)
     ///         filter coefficients (b0, a1)
     ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64)) {
+        self.coeffs.set_coeffs((coeffs.0, 0.0, coeffs.1));
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
     /// Return
     /// ------
-    ///     f64
+    ///     tuple -> (f64, f64):
+    ///         filter coefficients (b0, a1)
+    ///
+    pub fn coeffs(&self) -> (f64, f64) {
+        (self.coeffs.b0.to_f64(), self.coeffs.a1.to_f64())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
     ///         filtered sample
     ///
     ///
-
-    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: (f64, f64)) -> f64 {
-        let y = coeffs.0 * sample - self._x.read() + coeffs.1 * self._y.read();
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        let y = self.coeffs.b0 * sample - self._x.read() + self.coeffs.a1 * self._y.read();
         self._x.write_and_advance(&sample);
         self._y.write_and_advance(&y);
         y
@@ -130,24 +154,21 @@ impl DcFilter {
     ///
     /// Args
     /// ----
-    ///     frame: Vec<f64>
+    ///     frame: &[f64]
     ///         input frame
-    ///     coeffs: tuple(f64, f64)
-    ///         filter coefficients (b0, a1)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
+    ///     Vec<T>
     ///         filtered frame
     ///
     ///
-
-    #[pyo3(text_signature = "(frame: list[float], coeffs: tuple[float, float, float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: (f64, f64)) -> Vec<f64> {
+    #[cfg(feature = "alloc")]
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
 
         let y = frame
             .iter()
-            .map(|&x| self.filt_sample(x, coeffs))
+            .map(|&x| self.filt_sample(x))
             .collect();
 
         y
@@ -159,13 +180,51 @@ impl DcFilter {
     /// set:
     ///     y[n - 1] = 0.0
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+
+
+
+}
+
+impl<T: Sample> Filter<T> for DcFilter<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
         self._x.clear();
         self._y.clear();
-        println!("[DONE] cache cleared!")
     }
+}
 
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for DcFilter<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
 
+    // y[n] = b0 x[n] - x[n - 1] + a1 y[n - 1]
+    fn sections(&self) -> Vec<Section> {
+        let (b0, a1) = self.coeffs();
+        vec![Section::new(vec![b0, -1.0], vec![1.0, -a1])]
+    }
+}
 
-}
\ No newline at end of file
+// x[n - 1] and y[n - 1] delay lines
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for DcFilter<T> {
+    const FAMILY: &'static str = "dc";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self._x.save_state(state);
+        self._y.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self._x.load_state(reader)?;
+        self._y.load_state(reader)?;
+        Ok(())
+    }
+}
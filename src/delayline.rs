@@ -1,30 +1,139 @@
-pub struct DelayLine {
-    buffer: Vec<f64>,
+use core::marker::PhantomData;
+use super::{sample::Sample, error::{Result, FilterError}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::snapshot::{FilterState, StateReader};
+
+///
+/// MAXIMUM CASCADE ORDER
+/// capacity of the fixed delay storage used by the filter kernels
+/// (one slot per cascaded section)
+///
+pub const MAX_ORDER: usize = 16;
+
+///
+/// DELAY LINE
+/// circular buffer of delayed samples. The storage is a fixed-capacity
+/// array by default (no heap, no_std), or a Vec for long buffers (alloc)
+///
+#[derive(Clone)]
+pub struct DelayLine<T: Sample = f64, B = [T; MAX_ORDER]> {
+    buffer: B,
     length: usize,
-    index: usize
+    index: usize,
+    _sample: PhantomData<T>
+}
+
+///
+/// HEAP DELAY LINE
+/// delay line with runtime length, used by long buffers (Harmonic)
+///
+#[cfg(feature = "alloc")]
+pub type HeapDelayLine<T = f64> = DelayLine<T, Vec<T>>;
+
+impl<T: Sample, const N: usize> DelayLine<T, [T; N]> {
+    ///
+    /// INIT FIXED CAPACITY DELAY LINE
+    ///
+    /// Args
+    /// ----
+    ///     buffer_length: usize
+    ///         delay length in samples (1..=N)
+    ///
+    /// Return
+    /// ------
+    ///     Result<DelayLine>
+    ///         FilterError if buffer_length is 0 or above the capacity N
+    ///
+    pub fn new(buffer_length: usize) -> Result<Self> {
+        if (1..=N).contains(&buffer_length) {
+            Ok(Self::new_unchecked(buffer_length))
+        } else {
+            Err(FilterError::InvalidDelayLength { length: buffer_length, max: N })
+        }
+    }
+
+    // length already checked by the caller (1..=N)
+    pub(crate) fn new_unchecked(buffer_length: usize) -> Self {
+        debug_assert!((1..=N).contains(&buffer_length));
+        Self { buffer: [T::zero(); N], length: buffer_length, index: 0, _sample: PhantomData }
+    }
 }
 
-impl DelayLine {
-    pub fn new(buffer_length: usize) -> Self{
-        Self { buffer: vec![0.0; buffer_length], length: buffer_length, index: 0 }
+#[cfg(feature = "alloc")]
+impl<T: Sample> DelayLine<T, Vec<T>> {
+    ///
+    /// INIT HEAP DELAY LINE
+    ///
+    /// Args
+    /// ----
+    ///     buffer_length: usize
+    ///         delay length in samples (at least 1)
+    ///
+    /// Return
+    /// ------
+    ///     Result<HeapDelayLine>
+    ///         FilterError if buffer_length is 0
+    ///
+    pub fn with_length(buffer_length: usize) -> Result<Self> {
+        if buffer_length == 0 {
+            return Err(FilterError::InvalidBufferLength(buffer_length))
+        }
+        Ok(Self { buffer: vec![T::zero(); buffer_length], length: buffer_length, index: 0, _sample: PhantomData })
     }
-    
-    pub fn read(&self) -> f64 {
-        self.buffer[self.index]
+}
+
+impl<T: Sample, B: AsRef<[T]> + AsMut<[T]>> DelayLine<T, B> {
+    pub fn read(&self) -> T {
+        self.buffer.as_ref()[self.index]
     }
 
-    pub fn write_and_advance(&mut self, sample: &f64) {
-        self.buffer[self.index] = *sample;
+    pub fn write_and_advance(&mut self, sample: &T) {
+        self.buffer.as_mut()[self.index] = *sample;
         self.index += 1;
         self.index %= self.length;
     }
 
     pub fn clear(&mut self) {
-        for value in self.buffer.iter_mut() {
-            *value = 0.0;
+        for value in self.buffer.as_mut().iter_mut() {
+            *value = T::zero();
         }
         self.index = 0;
     }
 
 
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample, B: AsRef<[T]> + AsMut<[T]>> DelayLine<T, B> {
+    // length, write position and the delayed samples
+    pub(crate) fn save_state(&self, state: &mut FilterState) {
+        state.push(self.length as f64);
+        state.push(self.index as f64);
+        self.buffer.as_ref()[..self.length].iter().for_each(|&value| state.push(value));
+    }
+
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        reader.expect(self.length)?;
+        self.index = reader.read_index(self.length)?;
+        for value in self.buffer.as_mut()[..self.length].iter_mut() {
+            *value = reader.read()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        assert_eq!(DelayLine::<f64>::new(0).err(), Some(FilterError::InvalidDelayLength { length: 0, max: MAX_ORDER }));
+        assert_eq!(DelayLine::<f64>::new(MAX_ORDER + 1).err(), Some(FilterError::InvalidDelayLength { length: MAX_ORDER + 1, max: MAX_ORDER }));
+        assert!(DelayLine::<f64>::new(MAX_ORDER).is_ok());
+        #[cfg(feature = "alloc")]
+        assert_eq!(HeapDelayLine::<f64>::with_length(0).err(), Some(FilterError::InvalidBufferLength(0)));
+    }
+}
@@ -0,0 +1,100 @@
+use super::{sample::Sample, filtertype::{FilterType, EllipticFilterType}, iirdesign::{Band, elliptic_prototype, design_sos, check_design_order}, iirfilter::{IirFilter, IirFamily}, error::{Result, FilterError, ModeName, check_ripple, check_attenuation}};
+use alloc::vec::Vec;
+
+fn elliptic_type(band: &Band) -> FilterType {
+    FilterType::EllipticType(match band {
+        Band::Lowpass(_) => EllipticFilterType::Lp,
+        Band::Highpass(_) => EllipticFilterType::Hp,
+        Band::Bandpass(..) => EllipticFilterType::Bp,
+        Band::Bandstop(..) => EllipticFilterType::Br
+    })
+}
+
+/// elliptic (Cauer) family of IirFilter
+#[derive(Clone, Copy, Debug)]
+pub struct EllipticFamily;
+
+impl IirFamily for EllipticFamily {
+    const NAME: &'static str = "elliptic";
+    const INITIAL_MODE: FilterType = FilterType::EllipticType(EllipticFilterType::Lp);
+}
+
+pub type Elliptic<T = f64> = IirFilter<EllipticFamily, T>;
+
+impl<T: Sample> IirFilter<EllipticFamily, T> {
+    ///
+    /// GENERATE ELLIPTIC (CAUER) FILTER COEFFICIENTS
+    /// equiripple pass and stop bands, the steepest transition for a given
+    /// order. Designed from the analog prototype through the bilinear
+    /// transform (prewarped band edges) and stored as cascaded second order
+    /// sections
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz, the pass
+    ///         band edge where the response leaves the ripple band
+    ///     ripple: f64
+    ///         pass band ripple in dB
+    ///     attenuation: f64
+    ///         minimum stop band attenuation in dB
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///         FilterError on unknown mode, order 0, invalid fc, bw, ripple or
+    ///         attenuation (ripple must be below attenuation), missing bw
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, ripple: f64, attenuation: f64, bw: Option<f64>) -> Result<Vec<f64>> {
+        let band = Band::from_mode(mode, fc, bw, self.fs)?
+            .ok_or(FilterError::UnknownMode { family: EllipticFamily::NAME, mode: ModeName::new(mode) })?;
+        check_design_order(self.order)?;
+        check_ripple(ripple)?;
+        check_attenuation(attenuation)?;
+        if ripple >= attenuation {
+            return Err(FilterError::RippleAboveAttenuation { ripple, attenuation })
+        }
+
+        let sections = design_sos(&elliptic_prototype(self.order, ripple, attenuation), band, self.fs);
+        Ok(self.set_design(&sections, elliptic_type(&band)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::freqz::FrequencyResponse;
+
+    const FS: f64 = 48000.0;
+
+    fn magnitude_db(filter: &impl FrequencyResponse, f: f64) -> f64 {
+        filter.freqz(&[f]).magnitude_db()[0]
+    }
+
+    #[test]
+    fn elliptic_ripple_and_attenuation() {
+        for (order, ripple, attenuation) in [(3, 1.0, 40.0), (4, 0.5, 60.0), (7, 0.1, 80.0)] {
+            let mut lp: Elliptic = Elliptic::new(FS, order);
+            lp.design_filter("lp", 1000.0, ripple, attenuation, None).unwrap();
+            assert!((magnitude_db(&lp, 1000.0) + ripple).abs() < 1e-6, "order {order}: {} dB at fc", magnitude_db(&lp, 1000.0));
+
+            let grid: Vec<f64> = (0..2400).map(|k| 10.0 * k as f64).collect();
+            let db = lp.freqz(&grid).magnitude_db();
+            for &level in &db[..=100] {
+                assert!(level <= 1e-9 && level >= -ripple - 1e-9, "order {order}: {level} dB in the pass band");
+            }
+            // equiripple stop band: once the attenuation is reached the
+            // response stays below it
+            let stop = db.iter().position(|&level| level <= -attenuation).unwrap();
+            assert!(db[stop..].iter().all(|&level| level <= -attenuation + 1e-6), "order {order}: stop band above -{attenuation} dB");
+        }
+    }
+}
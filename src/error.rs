@@ -0,0 +1,176 @@
+use core::fmt;
+use super::delayline::MAX_ORDER;
+
+pub type Result<T> = core::result::Result<T, FilterError>;
+
+const MODE_NAME_CAPACITY: usize = 24;
+
+///
+/// MODE NAME
+/// filter mode as passed by the caller, stored inline (truncated to
+/// 24 bytes) so that errors do not need an allocator
+///
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ModeName {
+    bytes: [u8; MODE_NAME_CAPACITY],
+    length: usize
+}
+
+impl ModeName {
+    pub fn new(mode: &str) -> Self {
+        let mut length = mode.len().min(MODE_NAME_CAPACITY);
+        while !mode.is_char_boundary(length) {
+            length -= 1;
+        }
+        let mut bytes = [0; MODE_NAME_CAPACITY];
+        bytes[..length].copy_from_slice(&mode.as_bytes()[..length]);
+        Self { bytes, length }
+    }
+
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.bytes[..self.length]).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ModeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for ModeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+///
+/// FILTER ERROR
+/// returned by designs and constructors on invalid input
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterError {
+    UnknownMode { family: &'static str, mode: ModeName },
+    UnknownTopology(ModeName),
+    UnknownNormalization(ModeName),
+    UnknownPadType(ModeName),
+    UnknownWindow(ModeName),
+    MissingParameter { mode: ModeName, parameter: &'static str },
+    InvalidSampleRate(f64),
+    NonPositiveFrequency(f64),
+    FrequencyAboveNyquist { fc: f64, nyquist: f64 },
+    NonPositiveQ(f64),
+    NonPositiveBandwidth(f64),
+    NonPositiveT60(f64),
+    InvalidBufferLength(usize),
+    InvalidDelayLength { length: usize, max: usize },
+    InvalidOrder { order: usize, max: usize },
+    InvalidCoefficients { expected: usize, got: usize },
+    InvalidSection(usize),
+    InvalidTaps { taps: usize, mode: ModeName },
+    InvalidBands(&'static str),
+    RemezNotConverged(usize),
+    RootsNotConverged(usize),
+    InvalidBlockSize(usize),
+    EmptyImpulseResponse,
+    NegativeGlideTime(f64),
+    MissingDesign,
+    LengthMismatch { expected: usize, got: usize },
+    InvalidChannelLength { length: usize, n_channels: usize },
+    SignalTooShort { length: usize, padlen: usize },
+    InvalidState(&'static str),
+    UnstableDesign { family: &'static str, radius: f64 },
+    InvalidDesignOrder(usize),
+    NonPositiveRipple(f64),
+    NonPositiveAttenuation(f64),
+    RippleAboveAttenuation { ripple: f64, attenuation: f64 }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::UnknownMode { family, mode } => write!(f, "filter mode `{}` not allowed for {} family", mode, family),
+            FilterError::UnknownTopology(name) => write!(f, "biquad topology `{}` not allowed (df1, df2, tdf2, lattice, ss)", name),
+            FilterError::UnknownPadType(name) => write!(f, "pad type `{}` not allowed (odd, even, constant, none)", name),
+            FilterError::UnknownWindow(name) => write!(f, "window `{}` not allowed (hann, hamming, blackman, kaiser)", name),
+            FilterError::UnknownNormalization(name) => write!(f, "bessel normalization `{}` not allowed (phase, delay, mag)", name),
+            FilterError::MissingParameter { mode, parameter } => write!(f, "`{}` must be specified in `{}` mode", parameter, mode),
+            FilterError::InvalidSampleRate(fs) => write!(f, "sampling rate must be positive, got {}", fs),
+            FilterError::NonPositiveFrequency(fc) => write!(f, "frequency must be positive, got {} Hz", fc),
+            FilterError::FrequencyAboveNyquist { fc, nyquist } => write!(f, "frequency {} Hz is above Nyquist ({} Hz)", fc, nyquist),
+            FilterError::NonPositiveQ(q) => write!(f, "Q factor must be positive, got {}", q),
+            FilterError::NonPositiveBandwidth(bw) => write!(f, "band width must be positive, got {} Hz", bw),
+            FilterError::NonPositiveT60(t60) => write!(f, "t60 must be positive, got {} sec.", t60),
+            FilterError::InvalidBufferLength(length) => write!(f, "buffer length must be at least 1 sample, got {}", length),
+            FilterError::InvalidDelayLength { length, max } => write!(f, "delay length must be in 1..={} samples, got {}", max, length),
+            FilterError::InvalidOrder { order, max } => write!(f, "filter order must be in 1..={}, got {}", max, order),
+            FilterError::InvalidCoefficients { expected, got } => write!(f, "expected {} filter coefficients, got {}", expected, got),
+            FilterError::InvalidSection(index) => write!(f, "second order section {} must have finite coefficients and a0 != 0", index),
+            FilterError::InvalidTaps { taps, mode } => write!(f, "FIR `{}` needs an odd number of taps, got {}", mode, taps),
+            FilterError::InvalidBands(reason) => write!(f, "invalid remez bands: {}", reason),
+            FilterError::RemezNotConverged(iterations) => write!(f, "remez exchange did not converge in {} iterations (use more taps or wider transition bands)", iterations),
+            FilterError::RootsNotConverged(iterations) => write!(f, "polynomial root search did not converge in {} iterations", iterations),
+            FilterError::InvalidBlockSize(size) => write!(f, "block size must be a power of two, got {}", size),
+            FilterError::EmptyImpulseResponse => write!(f, "impulse response must have at least 1 sample"),
+            FilterError::NegativeGlideTime(time) => write!(f, "glide time must be positive or 0.0, got {} sec.", time),
+            FilterError::MissingDesign => write!(f, "no filter design set, call design_filter first"),
+            FilterError::LengthMismatch { expected, got } => write!(f, "expected {} samples (input length), got {}", expected, got),
+            FilterError::InvalidChannelLength { length, n_channels } => write!(f, "block length {} is not a multiple of the number of channels ({})", length, n_channels),
+            FilterError::SignalTooShort { length, padlen } => write!(f, "signal length ({} samples) must be greater than padlen ({})", length, padlen),
+            FilterError::InvalidState(reason) => write!(f, "filter state does not match the filter: {}", reason),
+            FilterError::UnstableDesign { family, radius } => write!(f, "unstable {} design, pole radius {} (must be below 1.0)", family, radius),
+            FilterError::InvalidDesignOrder(order) => write!(f, "filter order must be at least 1, got {}", order),
+            FilterError::NonPositiveRipple(ripple) => write!(f, "ripple must be positive, got {} dB", ripple),
+            FilterError::NonPositiveAttenuation(attenuation) => write!(f, "stop band attenuation must be positive, got {} dB", attenuation),
+            FilterError::RippleAboveAttenuation { ripple, attenuation } => write!(f, "pass band ripple ({} dB) must be below the stop band attenuation ({} dB)", ripple, attenuation)
+        }
+    }
+}
+
+impl core::error::Error for FilterError {}
+
+pub(crate) fn check_fs(fs: f64) -> Result<()> {
+    if fs > 0.0 && fs.is_finite() { Ok(()) } else { Err(FilterError::InvalidSampleRate(fs)) }
+}
+
+pub(crate) fn check_fc(fc: f64, fs: f64) -> Result<()> {
+    check_fs(fs)?;
+    let nyquist = fs / 2.0;
+    if fc.is_nan() || fc <= 0.0 {
+        Err(FilterError::NonPositiveFrequency(fc))
+    } else if fc >= nyquist {
+        Err(FilterError::FrequencyAboveNyquist { fc, nyquist })
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn check_q(q: f64) -> Result<()> {
+    if q > 0.0 && q.is_finite() { Ok(()) } else { Err(FilterError::NonPositiveQ(q)) }
+}
+
+pub(crate) fn check_bw(bw: f64) -> Result<()> {
+    if bw > 0.0 && bw.is_finite() { Ok(()) } else { Err(FilterError::NonPositiveBandwidth(bw)) }
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn check_ripple(ripple: f64) -> Result<()> {
+    if ripple > 0.0 && ripple.is_finite() { Ok(()) } else { Err(FilterError::NonPositiveRipple(ripple)) }
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) fn check_attenuation(attenuation: f64) -> Result<()> {
+    if attenuation > 0.0 && attenuation.is_finite() { Ok(()) } else { Err(FilterError::NonPositiveAttenuation(attenuation)) }
+}
+
+pub(crate) fn require<T>(value: Option<T>, mode: &str, parameter: &'static str) -> Result<T> {
+    value.ok_or_else(|| FilterError::MissingParameter { mode: ModeName::new(mode), parameter })
+}
+
+pub(crate) fn check_order(order: usize) -> Result<()> {
+    if (1..=MAX_ORDER).contains(&order) { Ok(()) } else { Err(FilterError::InvalidOrder { order, max: MAX_ORDER }) }
+}
+
+pub(crate) fn check_length(expected: usize, got: usize) -> Result<()> {
+    if expected == got { Ok(()) } else { Err(FilterError::LengthMismatch { expected, got }) }
+}
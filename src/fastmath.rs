@@ -0,0 +1,99 @@
+///
+/// HIGHEST MODULATED FREQUENCY
+/// modulated cutoffs are clamped to [MIN_FC_RATIO * fs, MAX_FC_RATIO * fs]
+///
+pub const MAX_FC_RATIO: f64 = 0.499;
+
+///
+/// LOWEST MODULATED FREQUENCY
+/// keeps the poles of the modulated designs off z = 1
+///
+pub const MIN_FC_RATIO: f64 = 1e-6;
+
+///
+/// LOWEST MODULATED Q
+/// modulated Q factors are clamped to at least MIN_Q
+///
+pub const MIN_Q: f64 = 1e-3;
+
+///
+/// TAN APPROXIMATION
+/// Padé (5, 4) rational approximation, no transcendental call. Relative
+/// error below 1e-9 up to x = 0.2 * pi, 4e-6 at 0.4 * pi, 3e-4 at 0.49 * pi
+/// and 4e-3 at MAX_FC_RATIO * pi
+///
+/// Args
+/// ----
+///     x: f64
+///         angle in [0.0, pi / 2)
+///
+/// Return
+/// ------
+///     f64
+///         tan(x)
+///
+#[inline]
+pub fn tan_approx(x: f64) -> f64 {
+    let x2 = x * x;
+    x * (945.0 - 105.0 * x2 + x2 * x2) / (945.0 - 420.0 * x2 + 15.0 * x2 * x2)
+}
+
+///
+/// EXP APPROXIMATION
+/// range reduction to |r| <= ln(2) / 2 and a degree 8 Taylor polynomial,
+/// no transcendental call. Relative error below 1e-9
+///
+/// Args
+/// ----
+///     x: f64
+///         exponent
+///
+/// Return
+/// ------
+///     f64
+///         exp(x)
+///
+#[inline]
+pub fn exp_approx(x: f64) -> f64 {
+    let k = libm::round(x * core::f64::consts::LOG2_E);
+    let r = x - k * core::f64::consts::LN_2;
+    let mut p = 1.0;
+    for n in (1..=8).rev() {
+        p = 1.0 + r * p / n as f64;
+    }
+    libm::scalbn(p, k as i32)
+}
+
+///
+/// MODULATED CUTOFF RATIO
+/// fc / fs clamped to [MIN_FC_RATIO, MAX_FC_RATIO], NaN gives MIN_FC_RATIO
+///
+/// Args
+/// ----
+///     fc: f64
+///         cutoff frequency in Hz
+///     fs: f64
+///         sampling rate in Hz
+///
+#[inline]
+pub fn fc_ratio(fc: f64, fs: f64) -> f64 {
+    let ratio = fc / fs;
+    if ratio.is_nan() { MIN_FC_RATIO } else { ratio.clamp(MIN_FC_RATIO, MAX_FC_RATIO) }
+}
+
+///
+/// PREWARPED GAIN
+/// tan(pi * fc / fs) with fc clamped by fc_ratio, the bilinear transform
+/// gain used by the modulated (audio-rate) designs
+///
+/// Args
+/// ----
+///     fc: f64
+///         cutoff frequency in Hz
+///     fs: f64
+///         sampling rate in Hz
+///
+#[inline]
+pub fn prewarp(fc: f64, fs: f64) -> f64 {
+    tan_approx(core::f64::consts::PI * fc_ratio(fc, fs))
+}
@@ -0,0 +1,108 @@
+use alloc::{vec, vec::Vec};
+use num_complex::Complex64;
+
+const PI: f64 = core::f64::consts::PI;
+
+///
+/// REAL FFT
+/// power of two transform of real signals, computed as a complex radix-2
+/// transform of half the size (even samples as real part, odd samples as
+/// imaginary part). Tables and scratch are allocated once, so the
+/// transforms do not allocate
+///
+#[derive(Clone)]
+pub(crate) struct RealFft {
+    size: usize,
+    // e^(-j 2 pi k / size), k < size / 2
+    twiddles: Vec<Complex64>,
+    bit_reverse: Vec<usize>,
+    scratch: Vec<Complex64>
+}
+
+impl RealFft {
+    // size: power of two, at least 2
+    pub(crate) fn new(size: usize) -> Self {
+        let half = size / 2;
+        let bits = half.trailing_zeros();
+        let bit_reverse = (0..half)
+            .map(|n| if bits == 0 { 0 } else { n.reverse_bits() >> (usize::BITS - bits) })
+            .collect();
+        let twiddles = (0..half)
+            .map(|k| Complex64::from_polar(1.0, -2.0 * PI * k as f64 / size as f64))
+            .collect();
+        Self { size, twiddles, bit_reverse, scratch: vec![Complex64::new(0.0, 0.0); half] }
+    }
+
+    ///
+    /// FORWARD TRANSFORM
+    /// input: size real samples, spectrum: bins 0 ..= size / 2
+    ///
+    pub(crate) fn forward(&mut self, input: &[f64], spectrum: &mut [Complex64]) {
+        let half = self.size / 2;
+        for (n, z) in self.scratch.iter_mut().enumerate() {
+            *z = Complex64::new(input[2 * n], input[2 * n + 1]);
+        }
+        transform(&mut self.scratch, &self.bit_reverse, &self.twiddles, false);
+
+        for (k, bin) in spectrum.iter_mut().enumerate().take(half + 1) {
+            let z = self.scratch[k % half];
+            let mirror = self.scratch[(half - k) % half].conj();
+            let even = (z + mirror) * 0.5;
+            let odd = (z - mirror) * Complex64::new(0.0, -0.5);
+            *bin = even + self.twiddle(k) * odd;
+        }
+    }
+
+    ///
+    /// INVERSE TRANSFORM
+    /// spectrum: bins 0 ..= size / 2, output: size real samples (scaled by
+    /// 1 / size, inverse of forward)
+    ///
+    pub(crate) fn inverse(&mut self, spectrum: &[Complex64], output: &mut [f64]) {
+        let half = self.size / 2;
+        for k in 0..half {
+            let x = spectrum[k];
+            let mirror = spectrum[half - k].conj();
+            let even = (x + mirror) * 0.5;
+            let odd = (x - mirror) * 0.5 * self.twiddle(k).conj();
+            self.scratch[k] = even + Complex64::new(0.0, 1.0) * odd;
+        }
+        transform(&mut self.scratch, &self.bit_reverse, &self.twiddles, true);
+
+        let scale = 1.0 / half as f64;
+        for (n, z) in self.scratch.iter().enumerate() {
+            output[2 * n] = z.re * scale;
+            output[2 * n + 1] = z.im * scale;
+        }
+    }
+
+    // e^(-j 2 pi k / size) for k <= size / 2
+    fn twiddle(&self, k: usize) -> Complex64 {
+        if k < self.twiddles.len() { self.twiddles[k] } else { Complex64::new(-1.0, 0.0) }
+    }
+}
+
+// in place iterative radix-2 transform of size half = twiddles.len(), the
+// twiddles of twice the size are taken with a stride of 2 (unscaled inverse)
+fn transform(buffer: &mut [Complex64], bit_reverse: &[usize], twiddles: &[Complex64], inverse: bool) {
+    let n = buffer.len();
+    for (i, &j) in bit_reverse.iter().enumerate() {
+        if i < j {
+            buffer.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= n {
+        let stride = 2 * n / length;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let w = if inverse { twiddles[k * stride].conj() } else { twiddles[k * stride] };
+                let a = buffer[start + k];
+                let b = buffer[start + k + length / 2] * w;
+                buffer[start + k] = a + b;
+                buffer[start + k + length / 2] = a - b;
+            }
+        }
+        length *= 2;
+    }
+}
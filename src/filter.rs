@@ -0,0 +1,59 @@
+use super::sample::Sample;
+
+///
+/// COMMON FILTER INTERFACE
+/// implemented by every filter family for f32 and f64 samples. Processing uses the coefficients
+/// of the last design_filter call (identity/pass-through before the first design)
+///
+pub trait Filter<T: Sample = f64> {
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample
+    ///
+    fn process_sample(&mut self, sample: T) -> T;
+
+    ///
+    /// APPLY FILTER ON BLOCK
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         input block
+    ///     output: &mut [T]
+    ///         output block (only min(input.len(), output.len()) samples are written)
+    ///
+    fn process_block(&mut self, input: &[T], output: &mut [T]) {
+        for (y, &x) in output.iter_mut().zip(input.iter()) {
+            *y = self.process_sample(x);
+        }
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    ///
+    /// Args
+    /// ----
+    ///     buffer: &mut [T]
+    ///         input block, overwritten with the filtered block
+    ///
+    fn process_inplace(&mut self, buffer: &mut [T]) {
+        for x in buffer.iter_mut() {
+            *x = self.process_sample(*x);
+        }
+    }
+
+    ///
+    /// RESET FILTER STATE
+    /// set all delayed samples to zero, the design is kept
+    ///
+    fn reset(&mut self);
+}
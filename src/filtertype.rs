@@ -1,5 +1,4 @@
-// TODO: Chebisev
-
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BiquadFilterType {
     Lp,
     Hp,
@@ -12,10 +11,43 @@ pub enum BiquadFilterType {
     HpShelf
 }
 
+///
+/// BIQUAD TOPOLOGY
+/// realization structure used to run a biquad
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BiquadTopology {
+    #[default]
+    DirectForm1,
+    DirectForm2,
+    TransposedDirectForm2,
+    Lattice,
+    StateSpace
+}
+
+///
+/// EDGE PADDING
+/// extension of the signal ends before forward-backward filtering
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PadType {
+    // point reflection about the end sample, 2 * x[0] - x[n]
+    #[default]
+    Odd,
+    // mirror, x[n]
+    Even,
+    // repeated end sample
+    Constant,
+    // no padding
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum TwoZeroTwoPoleFilterType {
     Notch,
     Bp,
 }
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum HarmonicFilterType {
     CombFIR,
     CombFreeverbFIR,
@@ -26,20 +58,24 @@ pub enum HarmonicFilterType {
     LPFBAllpassFilter
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OnePoleFilterType {
     LowPass,
     HighPass
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum DcBlockFilterType {
     DcBlockJulius
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NarrowFilterType {
     Bp,
     Notch
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ZavalishinFilterType {
     OnePoleZeroDelay,
     NaiveOnePole,
@@ -47,6 +83,7 @@ pub enum ZavalishinFilterType {
     StateVariable
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ButterFilterType {
     Lp,
     Hp,
@@ -54,6 +91,83 @@ pub enum ButterFilterType {
     Notch
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BesselFilterType {
+    Lp,
+    Hp
+}
+
+///
+/// BESSEL NORMALIZATION
+/// meaning of the Bessel cut off frequency
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BesselNorm {
+    // same magnitude asymptotes as a Butterworth at fc (phase midpoint close to fc)
+    #[default]
+    Phase,
+    // group delay at DC is 1 / (2 * pi * fc)
+    Delay,
+    // magnitude response is -3 dB at fc
+    Magnitude
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChebyshevFilterType {
+    Lp,
+    Hp,
+    Bp,
+    Br
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EllipticFilterType {
+    Lp,
+    Hp,
+    Bp,
+    Br
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FirFilterType {
+    Lp,
+    Hp,
+    Bp,
+    Br,
+    Remez(RemezType)
+}
+
+///
+/// REMEZ DESIGN TYPE
+/// symmetric taps for piecewise constant bands, antisymmetric taps
+/// (90 degrees phase shift) for the differentiator and the Hilbert transformer
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RemezType {
+    Bandpass,
+    Differentiator,
+    Hilbert
+}
+
+///
+/// FIR WINDOW
+/// taper applied to the ideal (sinc) impulse response, trading transition
+/// width for stop band attenuation
+///
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum FirWindow {
+    // raised cosine, about 44 dB of stop band attenuation
+    Hann,
+    // about 53 dB (as scipy.signal.firwin)
+    #[default]
+    Hamming,
+    // about 74 dB, wider transition
+    Blackman,
+    // shape parameter beta, see FirWindow::kaiser
+    Kaiser(f64)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FilterType {
     BiquadType(BiquadFilterType),
     TwoZeroTwoPoleType(TwoZeroTwoPoleFilterType),
@@ -62,5 +176,9 @@ pub enum FilterType {
     DcBlockType(DcBlockFilterType),
     NarrowType(NarrowFilterType),
     ZavalishinType(ZavalishinFilterType),
-    ButterType(ButterFilterType)
+    ButterType(ButterFilterType),
+    BesselType(BesselFilterType),
+    ChebyshevType(ChebyshevFilterType),
+    EllipticType(EllipticFilterType),
+    FirType(FirFilterType)
 }
\ No newline at end of file
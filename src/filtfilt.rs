@@ -0,0 +1,220 @@
+use alloc::vec::Vec;
+use super::{sample::Sample, filtertype::PadType, iirdesign::Cascade, sos::{SecondOrderSections, check_sos}, error::{Result, FilterError, ModeName}};
+
+impl PadType {
+    ///
+    /// PARSE PAD TYPE NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         odd = point reflection about the end samples
+    ///         even = mirror about the end samples
+    ///         constant = repeated end samples
+    ///         none = no padding
+    ///
+    /// Return
+    /// ------
+    ///     Result<PadType>
+    ///         FilterError on unknown name
+    ///
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "odd" => Ok(PadType::Odd),
+            "even" => Ok(PadType::Even),
+            "constant" => Ok(PadType::Constant),
+            "none" => Ok(PadType::None),
+            _ => Err(FilterError::UnknownPadType(ModeName::new(name)))
+        }
+    }
+}
+
+///
+/// STEP RESPONSE STEADY STATE
+/// transposed direct form II state of every section after a unit step has
+/// settled (as scipy.signal.sosfilt_zi): starting from it, a constant input
+/// gives a constant output. Sections with a pole at DC get a zero state
+///
+/// Args
+/// ----
+///     sos: &[[f64; 6]]
+///         sections [b0, b1, b2, a0, a1, a2]
+///
+/// Return
+/// ------
+///     Vec<[f64; 2]>
+///         state of every section for a unit input, scale it by the input level
+///
+pub fn sos_zi(sos: &[[f64; 6]]) -> Vec<[f64; 2]> {
+    let mut scale = 1.0;
+    sos.iter().map(|s| {
+        let (b0, b1, b2, a1, a2) = (s[0] / s[3], s[1] / s[3], s[2] / s[3], s[4] / s[3], s[5] / s[3]);
+        let gain = (b0 + b1 + b2) / (1.0 + a1 + a2);
+        if !gain.is_finite() {
+            scale = 0.0;
+            return [0.0, 0.0]
+        }
+        let zi = [scale * (gain - b0), scale * (b2 - a2 * gain)];
+        scale *= gain;
+        zi
+    }).collect()
+}
+
+///
+/// DEFAULT PAD LENGTH
+/// 3 times the number of taps of the cascade, as scipy.signal.sosfiltfilt
+///
+pub fn default_padlen(sos: &[[f64; 6]]) -> usize {
+    let zero_b2 = sos.iter().filter(|s| s[2] == 0.0).count();
+    let zero_a2 = sos.iter().filter(|s| s[5] == 0.0).count();
+    3 * (2 * sos.len() + 1 - zero_b2.min(zero_a2))
+}
+
+// signal extended by padlen samples at both ends
+fn extend<T: Sample>(x: &[T], padtype: PadType, padlen: usize) -> Vec<T> {
+    let (first, last) = (x[0], x[x.len() - 1]);
+    let two = T::from_f64(2.0);
+    let n = x.len();
+    let head = (1..=padlen).rev().map(|i| match padtype {
+        PadType::Odd => two * first - x[i],
+        PadType::Even => x[i],
+        PadType::Constant | PadType::None => first
+    });
+    let tail = (1..=padlen).map(|i| match padtype {
+        PadType::Odd => two * last - x[n - 1 - i],
+        PadType::Even => x[n - 1 - i],
+        PadType::Constant | PadType::None => last
+    });
+    head.chain(x.iter().copied()).chain(tail).collect()
+}
+
+///
+/// ZERO PHASE FILTERING OF SECOND ORDER SECTIONS
+/// the signal is filtered forward and backward (zero phase, squared
+/// magnitude response). The ends are padded and both passes start from the
+/// steady state of the first sample they see, so the output starts and ends
+/// without a transient (as scipy.signal.sosfiltfilt)
+///
+/// Args
+/// ----
+///     sos: &[[f64; 6]]
+///         sections [b0, b1, b2, a0, a1, a2]
+///     x: &[T]
+///         input signal
+///     padtype: PadType
+///         Odd (scipy default), Even, Constant or None
+///     padlen: Option<usize>
+///         samples added at each end (None = default_padlen, 0 with PadType::None)
+///
+/// Return
+/// ------
+///     Result<Vec<T>>
+///         filtered signal, same length as x
+///         FilterError if x is not longer than padlen, on invalid sections
+///
+pub fn sosfiltfilt<T: Sample>(sos: &[[f64; 6]], x: &[T], padtype: PadType, padlen: Option<usize>) -> Result<Vec<T>> {
+    check_sos(sos)?;
+    let padlen = match padtype {
+        PadType::None => 0,
+        _ => padlen.unwrap_or_else(|| default_padlen(sos))
+    };
+    if x.len() <= padlen || x.is_empty() {
+        return Err(FilterError::SignalTooShort { length: x.len(), padlen })
+    }
+
+    let mut cascade = Cascade::new(sos.len());
+    cascade.set_sections(sos);
+    let zi = sos_zi(sos);
+    let mut pass = |signal: &mut [T]| {
+        let level = signal[0].to_f64();
+        let state: Vec<[T; 2]> = zi.iter().map(|z| [T::from_f64(z[0] * level), T::from_f64(z[1] * level)]).collect();
+        cascade.set_state(&state);
+        signal.iter_mut().for_each(|sample| *sample = cascade.process(*sample));
+    };
+
+    let mut y = extend(x, padtype, padlen);
+    pass(&mut y);
+    y.reverse();
+    pass(&mut y);
+    y.reverse();
+    Ok(y[padlen..padlen + x.len()].to_vec())
+}
+
+///
+/// ZERO PHASE FILTERING
+/// sosfiltfilt with the stored design of a filter (its own state is not used)
+///
+/// Args
+/// ----
+///     filter: &F
+///         any filter exporting second order sections (Biquad, OnePole,
+///         Butter, Bessel, Chebyshev1, Chebyshev2, Elliptic, Sos)
+///     x: &[T]
+///         input signal
+///     padtype: PadType
+///         Odd (scipy default), Even, Constant or None
+///     padlen: Option<usize>
+///         samples added at each end (None = 3 times the number of taps)
+///
+/// Return
+/// ------
+///     Result<Vec<T>>
+///         filtered signal, same length as x
+///         FilterError if x is not longer than padlen
+///
+pub fn filtfilt<T: Sample, F: SecondOrderSections + ?Sized>(filter: &F, x: &[T], padtype: PadType, padlen: Option<usize>) -> Result<Vec<T>> {
+    sosfiltfilt(&filter.sos(), x, padtype, padlen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use crate::butter::Butter;
+
+    const FS: f64 = 48000.0;
+
+    fn lowpass_sos() -> Vec<[f64; 6]> {
+        let mut butter: Butter = Butter::new(FS, 5);
+        butter.design_filter("lp", 1000.0, None).unwrap();
+        butter.sos()
+    }
+
+    #[test]
+    fn sos_zi_starts_a_constant_input_in_steady_state() {
+        let sos = lowpass_sos();
+        let level = 0.75;
+        let mut cascade = Cascade::new(sos.len());
+        cascade.set_sections(&sos);
+        let state: Vec<[f64; 2]> = sos_zi(&sos).iter().map(|z| [z[0] * level, z[1] * level]).collect();
+        cascade.set_state(&state);
+        for n in 0..500 {
+            let y = cascade.process(level);
+            assert!((y - level).abs() < 1e-12, "sample {n}: {y}");
+        }
+    }
+
+    #[test]
+    fn sosfiltfilt_has_no_transient_on_a_constant_input() {
+        let sos = lowpass_sos();
+        let x: Vec<f64> = vec![-0.5; 300];
+        for padtype in [PadType::Odd, PadType::Even, PadType::Constant, PadType::None] {
+            let y = sosfiltfilt(&sos, &x, padtype, None).unwrap();
+            assert_eq!(y.len(), x.len());
+            assert!(y.iter().all(|&y| (y + 0.5).abs() < 1e-12), "{padtype:?}: transient in the output");
+        }
+    }
+
+    #[test]
+    fn sosfiltfilt_is_zero_phase() {
+        let sos = lowpass_sos();
+        let w = 2.0 * core::f64::consts::PI * 200.0 / FS;
+        let x: Vec<f64> = (0..4800).map(|n| libm::sin(w * n as f64)).collect();
+        let y = sosfiltfilt(&sos, &x, PadType::Odd, None).unwrap();
+        // squared magnitude of the 5th order Butterworth at fc / 5
+        let gain = 1.0 / (1.0 + libm::pow(libm::tan(w / 2.0) / libm::tan(2.0 * core::f64::consts::PI * 1000.0 / FS / 2.0), 10.0));
+        for n in 1000..3800 {
+            assert!((y[n] - gain * x[n]).abs() < 1e-6, "sample {n}: {} != {}", y[n], gain * x[n]);
+        }
+    }
+}
@@ -0,0 +1,454 @@
+#![allow(clippy::wrong_self_convention)]
+#![allow(clippy::new_without_default)]
+
+use super::{sample::Sample, filtertype::{FilterType, FirFilterType, FirWindow, RemezType}, remez::remez, iirdesign::Band, filter::Filter, error::{Result, FilterError, ModeName, check_attenuation, require}};
+use alloc::{vec, vec::Vec};
+use super::freqz::{FrequencyResponse, Section};
+use super::snapshot::{Snapshot, FilterState, StateReader};
+
+const PI: f64 = core::f64::consts::PI;
+
+impl FirWindow {
+    ///
+    /// PARSE WINDOW NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         hann, hamming, blackman or kaiser
+    ///     attenuation: Option<f64>
+    ///         stop band attenuation in dB (required only for kaiser)
+    ///
+    /// Return
+    /// ------
+    ///     Result<FirWindow>
+    ///         FilterError on unknown name, missing or invalid attenuation
+    ///
+    pub fn from_name(name: &str, attenuation: Option<f64>) -> Result<Self> {
+        match name {
+            "hann" => Ok(FirWindow::Hann),
+            "hamming" => Ok(FirWindow::Hamming),
+            "blackman" => Ok(FirWindow::Blackman),
+            "kaiser" => FirWindow::kaiser(require(attenuation, name, "attenuation")?),
+            _ => Err(FilterError::UnknownWindow(ModeName::new(name)))
+        }
+    }
+
+    ///
+    /// KAISER WINDOW FROM ATTENUATION
+    /// beta reaching `attenuation` dB in the stop band (Kaiser's empirical
+    /// formula, as scipy.signal.kaiser_beta)
+    ///
+    /// Args
+    /// ----
+    ///     attenuation: f64
+    ///         stop band attenuation in dB
+    ///
+    /// Return
+    /// ------
+    ///     Result<FirWindow>
+    ///         FilterError on non positive attenuation
+    ///
+    pub fn kaiser(attenuation: f64) -> Result<Self> {
+        check_attenuation(attenuation)?;
+        let beta = if attenuation > 50.0 {
+            0.1102 * (attenuation - 8.7)
+        } else if attenuation >= 21.0 {
+            0.5842 * libm::pow(attenuation - 21.0, 0.4) + 0.07886 * (attenuation - 21.0)
+        } else {
+            0.0
+        };
+        Ok(FirWindow::Kaiser(beta))
+    }
+
+    ///
+    /// WINDOW VALUES
+    /// symmetric window of n_taps points
+    ///
+    pub fn values(&self, n_taps: usize) -> Vec<f64> {
+        if n_taps < 2 {
+            return vec![1.0; n_taps]
+        }
+        let m = (n_taps - 1) as f64;
+        (0..n_taps).map(|n| {
+            let phase = 2.0 * PI * n as f64 / m;
+            match *self {
+                FirWindow::Hann => 0.5 - 0.5 * libm::cos(phase),
+                FirWindow::Hamming => 0.54 - 0.46 * libm::cos(phase),
+                FirWindow::Blackman => 0.42 - 0.5 * libm::cos(phase) + 0.08 * libm::cos(2.0 * phase),
+                FirWindow::Kaiser(beta) => {
+                    let r = 2.0 * n as f64 / m - 1.0;
+                    bessel_i0(beta * libm::sqrt((1.0 - r * r).max(0.0))) / bessel_i0(beta)
+                }
+            }
+        }).collect()
+    }
+}
+
+// modified Bessel function of the first kind, order 0 (power series)
+fn bessel_i0(x: f64) -> f64 {
+    let q = x * x / 4.0;
+    let (mut sum, mut term, mut k) = (1.0, 1.0, 1.0);
+    while term > 1e-17 * sum {
+        term *= q / (k * k);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 { 1.0 } else { libm::sin(PI * x) / (PI * x) }
+}
+
+///
+/// WINDOWED SINC DESIGN
+/// ideal impulse response of the band (difference of sinc low passes)
+/// centered on (n_taps - 1) / 2, tapered by the window and scaled to unit
+/// gain at DC (lp, br), Nyquist (hp) or the band center (bp), as
+/// scipy.signal.firwin. Symmetric taps: linear phase
+///
+/// Args
+/// ----
+///     n_taps: usize
+///         number of taps (odd for hp and br, the response of even lengths
+///         is 0 at Nyquist)
+///     band: Band
+///         edges in Hz
+///     window: FirWindow
+///         taper of the impulse response
+///     fs: f64
+///         sampling rate
+///
+/// Return
+/// ------
+///     Vec<f64>
+///         taps h[0], ..., h[n_taps - 1]
+///
+pub fn windowed_sinc(n_taps: usize, band: Band, window: FirWindow, fs: f64) -> Vec<f64> {
+    // pass bands in cycles per sample, reference frequency of the unit gain
+    let (bands, reference) = match band {
+        Band::Lowpass(fc) => ([(0.0, fc / fs), (0.0, 0.0)], 0.0),
+        Band::Highpass(fc) => ([(fc / fs, 0.5), (0.0, 0.0)], 0.5),
+        Band::Bandpass(low, high) => ([(low / fs, high / fs), (0.0, 0.0)], (low + high) / (2.0 * fs)),
+        Band::Bandstop(low, high) => ([(0.0, low / fs), (high / fs, 0.5)], 0.0)
+    };
+    let center = n_taps.saturating_sub(1) as f64 / 2.0;
+    let lowpass = |cutoff: f64, t: f64| 2.0 * cutoff * sinc(2.0 * cutoff * t);
+
+    let mut taps: Vec<f64> = window.values(n_taps).iter().enumerate().map(|(n, w)| {
+        let t = n as f64 - center;
+        w * bands.iter().map(|&(low, high)| lowpass(high, t) - lowpass(low, t)).sum::<f64>()
+    }).collect();
+
+    let gain: f64 = taps.iter().enumerate().map(|(n, h)| h * libm::cos(2.0 * PI * reference * (n as f64 - center))).sum();
+    taps.iter_mut().for_each(|h| *h /= gain);
+    taps
+}
+
+#[derive(Clone)]
+pub struct Fir<T: Sample = f64> {
+    fs: f64,
+    mode: FilterType,
+    taps: Vec<T>,
+    // last inputs stored twice, x[n], x[n - 1], ... contiguous from index
+    history: Vec<T>,
+    index: usize
+}
+
+impl<T: Sample> Fir<T> {
+    ///
+    /// INIT FIR CLASS
+    /// (pass through until design_filter)
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     n_taps: usize
+    ///         number of taps (at least 1), order + 1
+    ///
+    pub fn new(fs: f64, n_taps: usize) -> Self {
+        let n_taps = n_taps.max(1);
+        let mut taps = vec![T::zero(); n_taps];
+        taps[0] = T::from_f64(1.0);
+        Self {
+            fs,
+            mode: FilterType::FirType(FirFilterType::Lp),
+            taps,
+            history: vec![T::zero(); 2 * n_taps],
+            index: 0
+        }
+    }
+
+    ///
+    /// GENERATE WINDOWED SINC FIR TAPS
+    /// (the design is stored and used by filt_sample/filt_frame). Linear
+    /// phase, delay of (n_taps - 1) / 2 samples
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass, -6 dB at fc
+    ///         hp = high pass, -6 dB at fc (odd number of taps)
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2 (odd number of taps)
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///     window: FirWindow
+    ///         Hann, Hamming, Blackman or Kaiser (FirWindow::kaiser(attenuation))
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         taps h[0], ..., h[n_taps - 1]
+    ///         FilterError on unknown mode, invalid fc or bw, missing bw,
+    ///         even number of taps for hp and br
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: Option<f64>, window: FirWindow) -> Result<Vec<f64>> {
+        let band = Band::from_mode(mode, fc, bw, self.fs)?
+            .ok_or(FilterError::UnknownMode { family: "fir", mode: ModeName::new(mode) })?;
+        let n_taps = self.taps.len();
+        if n_taps.is_multiple_of(2) && matches!(band, Band::Highpass(_) | Band::Bandstop(..)) {
+            return Err(FilterError::InvalidTaps { taps: n_taps, mode: ModeName::new(mode) })
+        }
+
+        let taps = windowed_sinc(n_taps, band, window, self.fs);
+        self.mode = FilterType::FirType(match band {
+            Band::Lowpass(_) => FirFilterType::Lp,
+            Band::Highpass(_) => FirFilterType::Hp,
+            Band::Bandpass(..) => FirFilterType::Bp,
+            Band::Bandstop(..) => FirFilterType::Br
+        });
+        self.taps = taps.iter().map(|&h| T::from_f64(h)).collect();
+        Ok(taps)
+    }
+
+    ///
+    /// GENERATE EQUIRIPPLE FIR TAPS
+    /// Parks-McClellan design with the number of taps of the filter
+    /// (the design is stored and used by filt_sample/filt_frame)
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         bandpass = piecewise constant gain of every band (multiband)
+    ///         differentiator = gain desired * f / fs, 90 degrees phase lead
+    ///         hilbert = 90 degrees phase lag (-j) over the bands
+    ///     bands: &[f64]
+    ///         band edges in Hz, [low, high] pairs increasing from 0 to fs / 2
+    ///     desired: &[f64]
+    ///         gain (slope for the differentiator) of every band
+    ///     weights: Option<&[f64]>
+    ///         relative weight of the error in every band (default 1.0)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Vec<f64>>
+    ///         taps h[0], ..., h[n_taps - 1]
+    ///         FilterError on unknown mode, invalid bands, gains or weights,
+    ///         too few taps or no convergence (see remez)
+    ///
+    pub fn design_remez(&mut self, mode: &str, bands: &[f64], desired: &[f64], weights: Option<&[f64]>) -> Result<Vec<f64>> {
+        let kind = RemezType::from_name(mode)?;
+        let taps = remez(self.taps.len(), bands, desired, weights, kind, self.fs)?;
+        self.mode = FilterType::FirType(FirFilterType::Remez(kind));
+        self.taps = taps.iter().map(|&h| T::from_f64(h)).collect();
+        Ok(taps)
+    }
+
+    ///
+    /// SET EXTERNAL FILTER TAPS
+    /// override the stored design
+    ///
+    /// Args
+    /// ----
+    ///     taps: &[f64]
+    ///         h[0], ..., h[n_taps - 1]
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the number of taps does not match
+    ///
+    pub fn set_taps(&mut self, taps: &[f64]) -> Result<()> {
+        if taps.len() != self.taps.len() {
+            return Err(FilterError::InvalidCoefficients { expected: self.taps.len(), got: taps.len() })
+        }
+        self.taps = taps.iter().map(|&h| T::from_f64(h)).collect();
+        Ok(())
+    }
+
+    ///
+    /// GET FILTER TAPS
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         h[0], ..., h[n_taps - 1]
+    ///
+    pub fn taps(&self) -> Vec<f64> {
+        self.taps.iter().map(|h| h.to_f64()).collect()
+    }
+
+    pub fn n_taps(&self) -> usize {
+        self.taps.len()
+    }
+
+    pub fn mode(&self) -> FilterType {
+        self.mode
+    }
+
+    ///
+    /// GROUP DELAY
+    /// (n_taps - 1) / 2 samples for the linear phase designs
+    ///
+    pub fn delay(&self) -> f64 {
+        (self.taps.len() - 1) as f64 / 2.0
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    /// direct form, y[n] = h[0] x[n] + ... + h[N - 1] x[n - N + 1]
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample
+    ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        let n_taps = self.taps.len();
+        self.index = if self.index == 0 { n_taps - 1 } else { self.index - 1 };
+        self.history[self.index] = sample;
+        self.history[self.index + n_taps] = sample;
+        self.taps.iter()
+            .zip(&self.history[self.index..self.index + n_taps])
+            .fold(T::zero(), |y, (&h, &x)| y + h * x)
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: &[T]
+    ///         input frame
+    ///
+    /// Return
+    /// ------
+    ///     Vec<T>
+    ///         filtered frame
+    ///
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
+        frame.iter().map(|&x| self.filt_sample(x)).collect()
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set x[n - 1], ..., x[n - N + 1] to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+}
+
+impl<T: Sample> Filter<T> for Fir<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
+        self.history.fill(T::zero());
+        self.index = 0;
+    }
+}
+
+impl<T: Sample> FrequencyResponse for Fir<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        vec![Section::new(self.taps(), vec![1.0])]
+    }
+}
+
+// number of taps and the last inputs, x[n - 1] first
+impl<T: Sample> Snapshot for Fir<T> {
+    const FAMILY: &'static str = "fir";
+
+    fn save_state(&self, state: &mut FilterState) {
+        let n_taps = self.taps.len();
+        state.push(n_taps as f64);
+        self.history[self.index..self.index + n_taps].iter().for_each(|&x| state.push(x));
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        let n_taps = self.taps.len();
+        reader.expect(n_taps)?;
+        self.index = 0;
+        for n in 0..n_taps {
+            let x = reader.read()?;
+            self.history[n] = x;
+            self.history[n + n_taps] = x;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FS: f64 = 48000.0;
+
+    #[test]
+    fn windows_are_symmetric() {
+        let windows = [FirWindow::Hann, FirWindow::Hamming, FirWindow::Blackman, FirWindow::kaiser(60.0).unwrap()];
+        for window in windows {
+            for n_taps in [2, 7, 64, 101] {
+                let values = window.values(n_taps);
+                for n in 0..n_taps {
+                    assert!((values[n] - values[n_taps - 1 - n]).abs() < 1e-14, "{window:?} {n_taps} taps: asymmetric at {n}");
+                }
+                if n_taps % 2 == 1 {
+                    assert!((values[n_taps / 2] - 1.0).abs() < 1e-14, "{window:?}: center {}", values[n_taps / 2]);
+                }
+            }
+        }
+        assert!(FirWindow::Hann.values(31)[0].abs() < 1e-15);
+        assert!((FirWindow::Hamming.values(31)[0] - 0.08).abs() < 1e-15);
+        assert!(FirWindow::Blackman.values(31)[0].abs() < 1e-15);
+    }
+
+    #[test]
+    fn kaiser_beta_follows_the_empirical_formula() {
+        // scipy.signal.kaiser_beta
+        for (attenuation, beta) in [(65.0, 6.20426), (60.0, 5.65326), (40.0, 3.3953210522614574), (21.0, 0.0), (10.0, 0.0)] {
+            match FirWindow::kaiser(attenuation).unwrap() {
+                FirWindow::Kaiser(b) => assert!((b - beta).abs() < 1e-12, "{attenuation} dB: beta {b} != {beta}"),
+                window => panic!("{window:?}")
+            }
+        }
+        assert!(FirWindow::kaiser(0.0).is_err());
+        // the ends of the window are 1 / I0(beta)
+        let values = FirWindow::Kaiser(5.0).values(11);
+        assert!((values[0] - 1.0 / 27.239871823604442).abs() < 1e-15);
+    }
+
+    #[test]
+    fn kaiser_design_reaches_the_attenuation() {
+        let mut fir: Fir = Fir::new(FS, 101);
+        fir.design_filter("lp", 4800.0, None, FirWindow::kaiser(60.0).unwrap()).unwrap();
+        let stop: Vec<f64> = (0..100).map(|k| 6600.0 + 170.0 * k as f64).collect();
+        let db = fir.freqz(&stop).magnitude_db();
+        assert!(db.iter().all(|&level| level < -58.0), "stop band peak {} dB", db.iter().cloned().fold(f64::MIN, f64::max));
+    }
+}
@@ -0,0 +1,278 @@
+use alloc::{vec, vec::Vec};
+use num_complex::Complex64;
+
+///
+/// TRANSFER FUNCTION SECTION
+/// H(z) = (b[0] + b[1] z^-1 + ...) / (a[0] + a[1] z^-1 + ...), with the
+/// feedback sign of the difference equation y[n] = (sum b[k] x[n - k] -
+/// sum a[k] y[n - k]) / a[0]. Each filter converts its own convention
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Section {
+    pub b: Vec<f64>,
+    pub a: Vec<f64>
+}
+
+impl Section {
+    pub fn new(b: Vec<f64>, a: Vec<f64>) -> Self {
+        Self { b, a }
+    }
+
+    ///
+    /// COMPLEX RESPONSE
+    ///
+    /// Args
+    /// ----
+    ///     w: f64
+    ///         normalized angular frequency in rad/sample
+    ///
+    pub fn response(&self, w: f64) -> Complex64 {
+        polyval(&self.b, w) / polyval(&self.a, w)
+    }
+
+    ///
+    /// GROUP DELAY
+    /// in samples, 0.0 where the numerator or denominator vanishes
+    ///
+    /// Args
+    /// ----
+    ///     w: f64
+    ///         normalized angular frequency in rad/sample
+    ///
+    pub fn group_delay(&self, w: f64) -> f64 {
+        poly_group_delay(&self.b, w) - poly_group_delay(&self.a, w)
+    }
+}
+
+// p(e^jw) = sum p[k] e^(-jwk)
+fn polyval(p: &[f64], w: f64) -> Complex64 {
+    let z = Complex64::from_polar(1.0, -w);
+    p.iter().rev().fold(Complex64::new(0.0, 0.0), |acc, &value| acc * z + value)
+}
+
+// Re(sum k p[k] e^(-jwk) / sum p[k] e^(-jwk))
+fn poly_group_delay(p: &[f64], w: f64) -> f64 {
+    let z = Complex64::from_polar(1.0, -w);
+    let ramp = p.iter().enumerate().rev().fold(Complex64::new(0.0, 0.0), |acc, (k, &value)| acc * z + value * k as f64);
+    let value = polyval(p, w);
+    if value.norm() < 1e-12 * p.iter().map(|c| c.abs()).sum::<f64>().max(f64::MIN_POSITIVE) {
+        0.0
+    } else {
+        (ramp / value).re
+    }
+}
+
+///
+/// POLYNOMIAL PRODUCT
+/// coefficients of p(z^-1) * q(z^-1)
+///
+pub fn poly_mul(p: &[f64], q: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; p.len() + q.len() - 1];
+    for (i, &pi) in p.iter().enumerate() {
+        for (j, &qj) in q.iter().enumerate() {
+            product[i + j] += pi * qj;
+        }
+    }
+    product
+}
+
+///
+/// FREQUENCY RESPONSE
+/// evaluated on a set of frequencies
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub frequencies: Vec<f64>,
+    pub response: Vec<Complex64>,
+    pub group_delay: Vec<f64>
+}
+
+impl Response {
+    ///
+    /// MAGNITUDE IN dB
+    /// 20 * log10(|H|)
+    ///
+    pub fn magnitude_db(&self) -> Vec<f64> {
+        self.response.iter().map(|h| 20.0 * libm::log10(h.norm())).collect()
+    }
+
+    ///
+    /// UNWRAPPED PHASE
+    /// in radians, jumps larger than pi between consecutive frequencies are
+    /// removed
+    ///
+    pub fn phase(&self) -> Vec<f64> {
+        let two_pi = 2.0 * core::f64::consts::PI;
+        let mut offset = 0.0;
+        let mut previous: Option<f64> = None;
+        self.response.iter().map(|h| {
+            let wrapped = h.arg();
+            if let Some(last) = previous {
+                let jump = wrapped - last;
+                offset -= two_pi * libm::round(jump / two_pi);
+            }
+            previous = Some(wrapped);
+            wrapped + offset
+        }).collect()
+    }
+}
+
+///
+/// LINEAR FREQUENCY GRID
+/// n_points frequencies from 0.0 (included) to fs / 2 (excluded)
+///
+pub fn linear_grid(fs: f64, n_points: usize) -> Vec<f64> {
+    (0..n_points).map(|k| k as f64 * fs / (2.0 * n_points as f64)).collect()
+}
+
+///
+/// LOGARITHMIC FREQUENCY GRID
+/// n_points frequencies from f_min to f_max (both included)
+///
+pub fn log_grid(f_min: f64, f_max: f64, n_points: usize) -> Vec<f64> {
+    match n_points {
+        0 => Vec::new(),
+        1 => vec![f_min],
+        _ => {
+            let ratio = libm::log(f_max / f_min) / (n_points - 1) as f64;
+            (0..n_points).map(|k| f_min * libm::exp(ratio * k as f64)).collect()
+        }
+    }
+}
+
+///
+/// FREQUENCY RESPONSE OF A DESIGN
+/// implemented by every filter from its stored coefficients. Cascaded
+/// sections (order > 1, band filters) are multiplied together
+///
+pub trait FrequencyResponse {
+    fn sampling_rate(&self) -> f64;
+
+    ///
+    /// TRANSFER FUNCTION
+    /// cascade of sections run by filt_sample
+    ///
+    fn sections(&self) -> Vec<Section>;
+
+    ///
+    /// EVALUATE FREQUENCY RESPONSE
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: &[f64]
+    ///         frequencies in Hz
+    ///
+    /// Return
+    /// ------
+    ///     Response
+    ///         complex response and group delay (samples) at every frequency
+    ///
+    fn freqz(&self, frequencies: &[f64]) -> Response {
+        let sections = self.sections();
+        let fs = self.sampling_rate();
+        let two_pi = 2.0 * core::f64::consts::PI;
+
+        let mut response = Vec::with_capacity(frequencies.len());
+        let mut group_delay = Vec::with_capacity(frequencies.len());
+        for &f in frequencies {
+            let w = two_pi * f / fs;
+            response.push(sections.iter().map(|section| section.response(w)).product());
+            group_delay.push(sections.iter().map(|section| section.group_delay(w)).sum());
+        }
+        Response { frequencies: frequencies.to_vec(), response, group_delay }
+    }
+
+    ///
+    /// EVALUATE FREQUENCY RESPONSE ON A LOG GRID
+    ///
+    /// Args
+    /// ----
+    ///     f_min: f64
+    ///         lowest frequency in Hz
+    ///     n_points: usize
+    ///         number of log spaced frequencies from f_min to fs / 2
+    ///
+    fn freqz_log(&self, f_min: f64, n_points: usize) -> Response {
+        self.freqz(&log_grid(f_min, self.sampling_rate() / 2.0, n_points))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::f64::consts::PI;
+
+    const FS: f64 = 48000.0;
+
+    // z^-delay (delay + 1 taps)
+    struct Delay(usize);
+
+    impl FrequencyResponse for Delay {
+        fn sampling_rate(&self) -> f64 {
+            FS
+        }
+
+        fn sections(&self) -> Vec<Section> {
+            let mut b = vec![0.0; self.0 + 1];
+            b[self.0] = 1.0;
+            vec![Section::new(b, vec![1.0])]
+        }
+    }
+
+    #[test]
+    fn polyval_matches_the_expanded_sum() {
+        let p = [0.5, -1.25, 2.0, 0.75];
+        for w in [0.0, 0.3, 1.7, PI] {
+            let expected: Complex64 = p.iter().enumerate().map(|(k, &c)| c * Complex64::from_polar(1.0, -w * k as f64)).sum();
+            assert!((polyval(&p, w) - expected).norm() < 1e-12, "w {w}");
+        }
+    }
+
+    #[test]
+    fn one_pole_magnitude_and_group_delay() {
+        // H(z) = (1 - a) / (1 - a z^-1)
+        let a = 0.8;
+        let section = Section::new(vec![1.0 - a], vec![1.0, -a]);
+        for w in [0.0, 0.1, 0.9, 2.5, PI] {
+            let den = 1.0 - 2.0 * a * libm::cos(w) + a * a;
+            let magnitude = (1.0 - a) / libm::sqrt(den);
+            let group_delay = (a * libm::cos(w) - a * a) / den;
+            assert!((section.response(w).norm() - magnitude).abs() < 1e-12, "w {w}");
+            assert!((section.group_delay(w) - group_delay).abs() < 1e-12, "w {w}");
+            assert!((poly_group_delay(&[1.0, -a], w) + group_delay).abs() < 1e-12, "w {w}");
+        }
+    }
+
+    #[test]
+    fn pure_delay_has_constant_group_delay_and_unwrapped_linear_phase() {
+        let delay = 5;
+        let frequencies = linear_grid(FS, 64);
+        let response = Delay(delay).freqz(&frequencies);
+        let phase = response.phase();
+        for (k, &f) in frequencies.iter().enumerate() {
+            let w = 2.0 * PI * f / FS;
+            assert!((response.response[k].norm() - 1.0).abs() < 1e-12);
+            assert!((response.group_delay[k] - delay as f64).abs() < 1e-9, "f {f}");
+            assert!((phase[k] + w * delay as f64).abs() < 1e-9, "f {f}: {} != {}", phase[k], -w * delay as f64);
+        }
+    }
+
+    #[test]
+    fn grids() {
+        let grid = linear_grid(FS, 8);
+        assert_eq!(grid.len(), 8);
+        assert_eq!(grid[0], 0.0);
+        for (k, &f) in grid.iter().enumerate() {
+            assert!((f - k as f64 * 3000.0).abs() < 1e-9);
+        }
+
+        let grid = log_grid(20.0, 20000.0, 4);
+        assert_eq!(grid.len(), 4);
+        for (k, &f) in grid.iter().enumerate() {
+            let expected = 20.0 * libm::pow(10.0, k as f64);
+            assert!((f - expected).abs() < 1e-9 * expected, "{f} != {expected}");
+        }
+        assert!(log_grid(20.0, 20000.0, 0).is_empty());
+        assert_eq!(log_grid(20.0, 20000.0, 1), vec![20.0]);
+    }
+}
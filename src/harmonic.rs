@@ -1,64 +1,55 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use super::{filtertype::{FilterType, OnePoleFilterType}, onepole::DesignOnePoleFilter, coeffstruct::OnePoleCoeffs, delayline::DelayLine};
-use pyo3::prelude::*;
+use super::{sample::Sample, filtertype::{FilterType, OnePoleFilterType, HarmonicFilterType}, onepole::DesignOnePoleFilter, coeffstruct::OnePoleCoeffs, delayline::{DelayLine, HeapDelayLine}, filter::Filter, error::{Result, FilterError, ModeName, check_fs, check_fc, require}};
+use alloc::{vec, vec::Vec};
+use super::freqz::{FrequencyResponse, Section, poly_mul};
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
-fn _filt_sample_lowpass(x: &f64, coeffs: &(f64, f64), y1: f64) -> f64 {
-    coeffs.0 * x + coeffs.1 * y1
+fn _filt_sample_lowpass<T: Sample>(x: &T, coeffs: &(T, T), y1: T) -> T {
+    coeffs.0 * *x + coeffs.1 * y1
 }
 
-fn _filt_sample_comb(mode: &str, x: &f64, g: &f64, _sample: f64) -> f64 {
+fn _filt_sample_comb<T: Sample>(mode: &HarmonicFilterType, x: &T, g: &T, _sample: T) -> T {
     match mode {
-        "fir" => x + g * _sample,
-        "fir_freev" => -x + (1.0 + g) * _sample,
-        "iir" => x - g * _sample,
-        _ => {
-            println!("[ERROR] Filt mode not allowed!");
-            std::process::exit(1)
-        }
+        HarmonicFilterType::CombFreeverbFIR => -*x + (T::from_f64(1.0) + *g) * _sample,
+        HarmonicFilterType::CombIIR => *x - *g * _sample,
+        _ => *x + *g * _sample
     }
 }
 
-fn _filt_sample_comb_lp(x: &f64, g: &f64, &lp_coeffs: &(f64, f64), x_lp: f64, y_lp: f64) -> (f64, f64) {
+fn _filt_sample_comb_lp<T: Sample>(x: &T, g: &T, &lp_coeffs: &(T, T), x_lp: T, y_lp: T) -> (T, T) {
     let y_low_pass = _filt_sample_lowpass(&x_lp, &lp_coeffs, y_lp);
-    let y = x - g * y_low_pass;
+    let y = *x - *g * y_low_pass;
     (y, y_low_pass)
 }
 
-fn _filt_sample_allpass(mode: &str, x: &f64, g: &f64, x1: f64, y1: f64) -> f64 {
+fn _filt_sample_allpass<T: Sample>(mode: &HarmonicFilterType, x: &T, g: &T, x1: T, y1: T) -> T {
     match mode {
-        "freev" => -x + (1.0 + g) * x1 - g * y1,
-        "naive" => g * x + x1 - g * y1,
-        _ => {
-            println!("[ERROR] Filt mode not allowed!");
-            std::process::exit(1)
-        }
+        HarmonicFilterType::AllpassFreeverb => -*x + (T::from_f64(1.0) + *g) * x1 - *g * y1,
+        _ => *g * *x + x1 - *g * y1
     }
 }
 
-fn _filt_sample_allpass_lp(x: &f64, g: &f64, &lp_coeffs: &(f64, f64), x1: f64, x_lp: f64, y_lp: f64) -> (f64, f64) {
+fn _filt_sample_allpass_lp<T: Sample>(x: &T, g: &T, &lp_coeffs: &(T, T), x1: T, x_lp: T, y_lp: T) -> (T, T) {
     let y_low_pass = _filt_sample_lowpass(&x_lp, &lp_coeffs, y_lp);
-    let y = g * x + x1 - g * y_low_pass;
+    let y = *g * *x + x1 - *g * y_low_pass;
     (y, y_low_pass)
 }
 
-#[pyclass]
-pub struct Harmonic {
+#[derive(Clone)]
+pub struct Harmonic<T: Sample = f64> {
     fs: f64,
     buffer_delay: usize,
-    mode: String,
-    g: f64,
-    x: DelayLine,
-    y: DelayLine,
-    ylp: DelayLine,
-    low_pass_coeffs: OnePoleCoeffs
+    mode: HarmonicFilterType,
+    g: T,
+    x: HeapDelayLine<T>,
+    y: HeapDelayLine<T>,
+    ylp: DelayLine<T>,
+    low_pass_coeffs: OnePoleCoeffs<T>
 }
 
-#[pymethods]
-impl Harmonic {
-    #[new]
-
+impl<T: Sample> Harmonic<T> {
     ///
     /// INIT HARMONIC FILTER
     ///
@@ -78,23 +69,38 @@ impl Harmonic {
     ///     fs: f64
     ///         sampling rate
     ///
-    
-    #[pyo3(text_signature = "(mode: str, buffer_delay: int, fs: float) -> None")]
-    pub fn new(mode: &str, buffer_delay: usize, fs: f64) -> Self {
-        let mode = String::from(mode);
-        let g = 0.0;
+    /// Return
+    /// ------
+    ///     Result<Harmonic>
+    ///         FilterError on unknown mode, empty buffer or invalid fs
+    ///
+    pub fn new(mode: &str, buffer_delay: usize, fs: f64) -> Result<Self> {
+        let mode = match mode {
+            "combf" => HarmonicFilterType::CombFIR,
+            "combfreev" => HarmonicFilterType::CombFreeverbFIR,
+            "combi" => HarmonicFilterType::CombIIR,
+            "lpcombi" => HarmonicFilterType::LPFBCombFilter,
+            "allpass" => HarmonicFilterType::Allpass,
+            "allpassfreev" => HarmonicFilterType::AllpassFreeverb,
+            "lpallpass" => HarmonicFilterType::LPFBAllpassFilter,
+            _ => return Err(FilterError::UnknownMode { family: "harmonic", mode: ModeName::new(mode) })
+        };
+
+        check_fs(fs)?;
+
+        let g = T::zero();
         let low_pass_coeffs = OnePoleCoeffs::new();
 
-        Self {
+        Ok(Self {
             fs,
             buffer_delay,
             mode,
             g,
-            x: DelayLine::new(buffer_delay),
-            y: DelayLine::new(buffer_delay),
-            ylp: DelayLine::new(1),
+            x: HeapDelayLine::with_length(buffer_delay)?,
+            y: HeapDelayLine::with_length(buffer_delay)?,
+            ylp: DelayLine::new_unchecked(1),
             low_pass_coeffs
-        }
+        })
     }
 
     ///
@@ -105,13 +111,26 @@ impl Harmonic {
     ///     t60: f64
     ///         reverb time in sec.
     ///     fc: Optional<f64>
-    ///         low pass cut off frequency (optional, only for lpcombi and lpallpass)
+    ///         low pass cut off frequency (required only for lpcombi and lpallpass)
     ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on non positive or non finite t60, missing or invalid fc
+    ///
+    pub fn design_filter(&mut self, t60: f64, fc: Option<f64>) -> Result<()> {
+        if !(t60 > 0.0 && t60.is_finite()) {
+            return Err(FilterError::NonPositiveT60(t60))
+        }
+
+        match self.mode {
+            HarmonicFilterType::LPFBCombFilter => { check_fc(require(fc, "lpcombi", "fc")?, self.fs)? },
+            HarmonicFilterType::LPFBAllpassFilter => { check_fc(require(fc, "lpallpass", "fc")?, self.fs)? },
+            _ => {}
+        }
 
-    #[pyo3(text_signature = "(t60: float, fc: float|None) -> None")]
-    pub fn design_filter(&mut self, t60: f64, fc: Option<f64>) {
         let d_time: f64 = (self.buffer_delay as f64) / self.fs;
-        self.g = 10.0_f64.powf(-3.0 * d_time / t60);
+        self.g = T::from_f64(libm::pow(10.0_f64, -3.0 * d_time / t60));
 
         let (b0, a1) = match fc {
             Some(cutoff) => {
@@ -122,6 +141,7 @@ impl Harmonic {
             None => { (0.0, 0.0) }             
         };
         self.low_pass_coeffs.set_coeffs((b0, 0.0, a1));
+        Ok(())
     }
 
     ///
@@ -129,37 +149,29 @@ impl Harmonic {
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     /// 
     /// Return
     /// ------
-    ///     f64
+    ///     T
     ///         filtered sample
     ///
     ///
-
-    #[pyo3(text_signature = "(sample: float) -> float")]
-    pub fn filt_sample(&mut self, sample: f64) -> f64 {
+    pub fn filt_sample(&mut self, sample: T) -> T {
         
         let lp_coeffs = (self.low_pass_coeffs.b0, self.low_pass_coeffs.a1);
         
-        let (yout, ylpass) = match &self.mode[..] {
-            "combf" => { (_filt_sample_comb("fir", &sample, &self.g, self.x.read()), 0.0) },
-            "combfreev" => { (_filt_sample_comb("fir_freev", &sample, &self.g, self.x.read()), 0.0) },
-            "combi" => { (_filt_sample_comb("iir", &sample, &self.g, self.y.read()), 0.0) },
-            "allpass" => { (_filt_sample_allpass("naive", &sample, &self.g, self.x.read(), self.y.read()), 0.0) },
-            "allpassfreev" => { (_filt_sample_allpass("freev", &sample, &self.g, self.x.read(), self.y.read()), 0.0) },
-            "lpcombi" => {
+        let (yout, ylpass) = match self.mode {
+            HarmonicFilterType::CombFIR | HarmonicFilterType::CombFreeverbFIR => { (_filt_sample_comb(&self.mode, &sample, &self.g, self.x.read()), T::zero()) },
+            HarmonicFilterType::CombIIR => { (_filt_sample_comb(&self.mode, &sample, &self.g, self.y.read()), T::zero()) },
+            HarmonicFilterType::Allpass | HarmonicFilterType::AllpassFreeverb => { (_filt_sample_allpass(&self.mode, &sample, &self.g, self.x.read(), self.y.read()), T::zero()) },
+            HarmonicFilterType::LPFBCombFilter => {
                 let (y_out, y_out_lp) = _filt_sample_comb_lp(&sample, &self.g, &lp_coeffs, self.y.read(), self.ylp.read());
                 (y_out, y_out_lp)
             },
-            "lpallpass" => {
+            HarmonicFilterType::LPFBAllpassFilter => {
                 let (y_out, y_out_lp) = _filt_sample_allpass_lp(&sample, &self.g, &lp_coeffs, self.x.read(), self.y.read(), self.ylp.read());
                 (y_out, y_out_lp)
-            },
-            _ => {
-                println!("[ERROR] filt mode not allowed!");
-                std::process::exit(1);
             }
         };
         
@@ -177,18 +189,16 @@ impl Harmonic {
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     ///         input sample
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
+    ///     Vec<T>
     ///         filtered frame
     ///
     ///
-
-    #[pyo3(text_signature = "(frame: list[float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>) -> Vec<f64> {
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
         
         let y = frame
             .iter()
@@ -202,11 +212,86 @@ impl Harmonic {
     /// CLEAR DELAYED SAMPLES CACHE
     /// set buffer and delayed low pass sample to zero
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+}
+
+impl<T: Sample> Filter<T> for Harmonic<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
         self.x.clear();
         self.y.clear();
         self.ylp.clear();
-        println!("[DONE] cache cleared!")
     }
-}
\ No newline at end of file
+}
+
+impl<T: Sample> FrequencyResponse for Harmonic<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    // polynomials in z^-D (D = buffer_delay), the low pass in the feedback
+    // path of lpcombi/lpallpass is ylp[n] = b0 y[n - D] + a1 ylp[n - 1]
+    fn sections(&self) -> Vec<Section> {
+        let g = self.g.to_f64();
+        let (b0, a1) = (self.low_pass_coeffs.b0.to_f64(), self.low_pass_coeffs.a1.to_f64());
+        let delay = |head: f64, tail: f64| {
+            let mut p = vec![0.0; self.buffer_delay + 1];
+            p[0] += head;
+            p[self.buffer_delay] += tail;
+            p
+        };
+        let lp_feedback = || {
+            let mut a = delay(1.0, g * b0);
+            a[1] -= a1;
+            a
+        };
+
+        let section = match self.mode {
+            HarmonicFilterType::CombFIR => Section::new(delay(1.0, g), vec![1.0]),
+            HarmonicFilterType::CombFreeverbFIR => Section::new(delay(-1.0, 1.0 + g), vec![1.0]),
+            HarmonicFilterType::CombIIR => Section::new(vec![1.0], delay(1.0, g)),
+            HarmonicFilterType::Allpass => Section::new(delay(g, 1.0), delay(1.0, g)),
+            HarmonicFilterType::AllpassFreeverb => Section::new(delay(-1.0, 1.0 + g), delay(1.0, g)),
+            HarmonicFilterType::LPFBCombFilter => Section::new(vec![1.0, -a1], lp_feedback()),
+            HarmonicFilterType::LPFBAllpassFilter => Section::new(poly_mul(&delay(g, 1.0), &[1.0, -a1]), lp_feedback())
+        };
+        vec![section]
+    }
+}
+
+// comb buffers and low pass state
+impl<T: Sample> Snapshot for Harmonic<T> {
+    const FAMILY: &'static str = "harmonic";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.x.save_state(state);
+        self.y.save_state(state);
+        self.ylp.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.x.load_state(reader)?;
+        self.y.load_state(reader)?;
+        self.ylp.load_state(reader)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn design_rejects_non_finite_t60() {
+        let mut comb: Harmonic = Harmonic::new("combi", 441, 44100.0).unwrap();
+        for t60 in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(comb.design_filter(t60, None), Err(FilterError::NonPositiveT60(_))), "t60 {t60}");
+        }
+        assert!(comb.design_filter(1.5, None).is_ok());
+    }
+}
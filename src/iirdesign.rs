@@ -0,0 +1,685 @@
+use alloc::{vec, vec::Vec};
+use num_complex::Complex64;
+use super::{sample::Sample, biquadeq::Biquad, filtertype::{BiquadTopology, BesselNorm}, coeffstruct::BiquadCoeffs, polezero::{PoleZero, roots}, freqz::Section, snapshot::{FilterState, StateReader}, error::{Result, FilterError, check_fc, check_bw, require}};
+
+///
+/// DIGITAL BAND
+/// edges in Hz of a band design, mapped to the analog prototype before the
+/// bilinear transform
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Band {
+    Lowpass(f64),
+    Highpass(f64),
+    Bandpass(f64, f64),
+    Bandstop(f64, f64)
+}
+
+impl Band {
+    ///
+    /// BAND FROM MODE
+    /// lp/hp at fc, bp/br from fc - bw / 2 to fc + bw / 2 (as in Butter)
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp, hp, bp or br
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///     fs: f64
+    ///         sampling rate
+    ///
+    /// Return
+    /// ------
+    ///     Result<Option<Band>>
+    ///         None on unknown mode (the caller reports its own family)
+    ///         FilterError on invalid fc or bw, missing bw
+    ///
+    pub fn from_mode(mode: &str, fc: f64, bw: Option<f64>, fs: f64) -> Result<Option<Self>> {
+        let edges = |bw: Option<f64>| -> Result<(f64, f64)> {
+            let bw = require(bw, mode, "bw")?;
+            check_bw(bw)?;
+            check_fc(fc - bw / 2.0, fs)?;
+            check_fc(fc + bw / 2.0, fs)?;
+            Ok((fc - bw / 2.0, fc + bw / 2.0))
+        };
+        Ok(Some(match mode {
+            "lp" => { check_fc(fc, fs)?; Band::Lowpass(fc) },
+            "hp" => { check_fc(fc, fs)?; Band::Highpass(fc) },
+            "bp" => { let (low, high) = edges(bw)?; Band::Bandpass(low, high) },
+            "br" => { let (low, high) = edges(bw)?; Band::Bandstop(low, high) },
+            _ => return Ok(None)
+        }))
+    }
+}
+
+pub(crate) fn check_design_order(order: usize) -> Result<()> {
+    if order > 0 { Ok(()) } else { Err(FilterError::InvalidDesignOrder(order)) }
+}
+
+///
+/// BUTTERWORTH ANALOG PROTOTYPE
+/// low pass with maximally flat magnitude, -3 dB at 1 rad/s. The poles lie
+/// evenly on the left half of the unit circle, no zeros, unit gain at DC
+///
+/// Args
+/// ----
+///     order: usize
+///         filter order
+///
+pub fn butterworth_prototype(order: usize) -> PoleZero {
+    let poles: Vec<Complex64> = (0..order)
+        .map(|k| {
+            let theta = core::f64::consts::PI * (2.0 * k as f64 + 1.0 + order as f64) / (2.0 * order as f64);
+            Complex64::from_polar(1.0, theta)
+        })
+        .collect();
+    PoleZero { zeros: Vec::new(), poles, gain: 1.0 }
+}
+
+///
+/// CHEBYSHEV TYPE I ANALOG PROTOTYPE
+/// low pass with cut off at 1 rad/s and equiripple pass band, no zeros.
+/// Even orders start at -ripple dB at DC
+///
+/// Args
+/// ----
+///     order: usize
+///         filter order
+///     ripple: f64
+///         pass band ripple in dB
+///
+pub fn chebyshev1_prototype(order: usize, ripple: f64) -> PoleZero {
+    let epsilon = libm::sqrt(libm::pow(10.0, 0.1 * ripple) - 1.0);
+    let mu = libm::asinh(1.0 / epsilon) / order as f64;
+
+    let poles: Vec<Complex64> = (0..order)
+        .map(|k| {
+            let theta = core::f64::consts::PI * (2.0 * k as f64 + 1.0 - order as f64) / (2.0 * order as f64);
+            -Complex64::new(mu, theta).sinh()
+        })
+        .collect();
+
+    let mut gain = poles.iter().map(|&pole| -pole).product::<Complex64>().re;
+    if order.is_multiple_of(2) {
+        gain /= libm::sqrt(1.0 + epsilon * epsilon);
+    }
+    PoleZero { zeros: Vec::new(), poles, gain }
+}
+
+///
+/// CHEBYSHEV TYPE II ANALOG PROTOTYPE
+/// (inverse Chebyshev) low pass with monotonic pass band and equiripple
+/// stop band starting at 1 rad/s, where the attenuation first reaches
+/// attenuation dB. Unit gain at DC
+///
+/// Args
+/// ----
+///     order: usize
+///         filter order
+///     attenuation: f64
+///         minimum stop band attenuation in dB
+///
+pub fn chebyshev2_prototype(order: usize, attenuation: f64) -> PoleZero {
+    let n = order as f64;
+    let delta = 1.0 / libm::sqrt(libm::pow(10.0, 0.1 * attenuation) - 1.0);
+    let mu = libm::asinh(1.0 / delta) / n;
+
+    // odd orders have one zero at infinity (m = 0 is skipped)
+    let zeros: Vec<Complex64> = (0..order)
+        .map(|k| 2.0 * k as f64 + 1.0 - n)
+        .filter(|&m| m != 0.0)
+        .map(|m| Complex64::new(0.0, 1.0 / libm::sin(m * core::f64::consts::PI / (2.0 * n))))
+        .collect();
+
+    let poles: Vec<Complex64> = (0..order)
+        .map(|k| {
+            let theta = core::f64::consts::PI * (2.0 * k as f64 + 1.0 - n) / (2.0 * n);
+            let p = -Complex64::new(0.0, theta).exp();
+            Complex64::new(libm::sinh(mu) * p.re, libm::cosh(mu) * p.im).inv()
+        })
+        .collect();
+
+    let gain = product_ratio(&poles, &zeros, |r| -r).re;
+    PoleZero { zeros, poles, gain }
+}
+
+///
+/// ELLIPTIC (CAUER) ANALOG PROTOTYPE
+/// low pass with equiripple pass band (ripple dB up to 1 rad/s) and
+/// equiripple stop band (at least attenuation dB), the steepest transition
+/// for a given order. Designed with Landen transformations (S. J. Orfanidis,
+/// Lecture Notes on Elliptic Filter Design, 2006)
+///
+/// Args
+/// ----
+///     order: usize
+///         filter order
+///     ripple: f64
+///         pass band ripple in dB
+///     attenuation: f64
+///         minimum stop band attenuation in dB (above ripple)
+///
+pub fn elliptic_prototype(order: usize, ripple: f64, attenuation: f64) -> PoleZero {
+    let n = order as f64;
+    let j = Complex64::new(0.0, 1.0);
+    let epsilon_pass = libm::sqrt(libm::pow(10.0, 0.1 * ripple) - 1.0);
+    let epsilon_stop = libm::sqrt(libm::pow(10.0, 0.1 * attenuation) - 1.0);
+    let k1 = epsilon_pass / epsilon_stop;
+    let k = elliptic_degree(order, k1);
+
+    let u: Vec<f64> = (1..=order / 2).map(|i| (2.0 * i as f64 - 1.0) / n).collect();
+    let v0 = -j * asne(j / epsilon_pass, k1) / n;
+
+    let mut zeros = Vec::with_capacity(2 * u.len());
+    let mut poles = Vec::with_capacity(order);
+    for &ui in &u {
+        let zero = j / (k * cde(Complex64::new(ui, 0.0), k));
+        let pole = j * cde(ui - j * v0, k);
+        zeros.extend([zero, zero.conj()]);
+        poles.extend([pole, pole.conj()]);
+    }
+    if order % 2 == 1 {
+        poles.push(Complex64::new((j * sne(j * v0, k)).re, 0.0));
+    }
+
+    let dc_gain = if order % 2 == 1 { 1.0 } else { 1.0 / libm::sqrt(1.0 + epsilon_pass * epsilon_pass) };
+    let gain = dc_gain * product_ratio(&poles, &zeros, |r| -r).re;
+    PoleZero { zeros, poles, gain }
+}
+
+// arithmetic-geometric mean
+fn agm(mut a: f64, mut b: f64) -> f64 {
+    while (a - b).abs() > 1e-15 * a {
+        (a, b) = (0.5 * (a + b), libm::sqrt(a * b));
+    }
+    a
+}
+
+// complete elliptic integrals K(k) and K'(k) = K(sqrt(1 - k^2)), from the
+// modulus and its complement (no cancellation for k close to 0 or 1)
+fn elliptic_k(k: f64) -> (f64, f64) {
+    let complement = libm::sqrt((1.0 - k) * (1.0 + k));
+    let half_pi = core::f64::consts::FRAC_PI_2;
+    (half_pi / agm(1.0, complement), half_pi / agm(1.0, k))
+}
+
+// descending Landen sequence of moduli
+fn landen(k: f64) -> Vec<f64> {
+    let mut moduli = Vec::new();
+    let mut k = k;
+    while k > 1e-15 && moduli.len() < 16 {
+        k = libm::pow(k / (1.0 + libm::sqrt((1.0 - k) * (1.0 + k))), 2.0);
+        moduli.push(k);
+    }
+    moduli
+}
+
+// cd(u K, k) and sn(u K, k) by ascending Landen transformations
+fn cde(u: Complex64, k: f64) -> Complex64 {
+    landen(k).iter().rev().fold((u * core::f64::consts::FRAC_PI_2).cos(), |w, &v| (1.0 + v) * w / (1.0 + v * w * w))
+}
+
+fn sne(u: Complex64, k: f64) -> Complex64 {
+    landen(k).iter().rev().fold((u * core::f64::consts::FRAC_PI_2).sin(), |w, &v| (1.0 + v) * w / (1.0 + v * w * w))
+}
+
+// symmetric remainder, in [-y / 2, y / 2]
+fn symmetric_rem(x: f64, y: f64) -> f64 {
+    x - y * libm::round(x / y)
+}
+
+// inverse of cde, u reduced to the fundamental rectangle
+fn acde(w: Complex64, k: f64) -> Complex64 {
+    let moduli = landen(k);
+    let mut w = w;
+    let mut previous = k;
+    for &v in &moduli {
+        w = w / (1.0 + (1.0 - w * w * previous * previous).sqrt()) * 2.0 / (1.0 + v);
+        previous = v;
+    }
+    let u = w.acos() * 2.0 / core::f64::consts::PI;
+    let (kk, kk_prime) = elliptic_k(k);
+    Complex64::new(symmetric_rem(u.re, 4.0), symmetric_rem(u.im, 2.0 * kk_prime / kk))
+}
+
+fn asne(w: Complex64, k: f64) -> Complex64 {
+    1.0 - acde(w, k)
+}
+
+// selectivity k = wp / ws of an order N elliptic filter with discrimination
+// k1, from the degree equation in nome form
+fn elliptic_degree(order: usize, k1: f64) -> f64 {
+    let (kk1, kk1_prime) = elliptic_k(k1);
+    let q = libm::exp(-core::f64::consts::PI * kk1_prime / (kk1 * order as f64));
+    let numerator: f64 = (0..8).map(|m| libm::pow(q, (m * (m + 1)) as f64)).sum();
+    let denominator: f64 = 1.0 + 2.0 * (1..9).map(|m| libm::pow(q, (m * m) as f64)).sum::<f64>();
+    4.0 * libm::sqrt(q) * libm::pow(numerator / denominator, 2.0)
+}
+
+/// highest Bessel order with accurate poles
+pub const MAX_BESSEL_ORDER: usize = 20;
+
+///
+/// BESSEL (THOMSON) ANALOG PROTOTYPE
+/// low pass with maximally flat group delay, no zeros, unit gain at DC.
+/// The poles are the roots of the reverse Bessel polynomial, scaled to the
+/// normalization of the cut off at 1 rad/s. These roots are ill-conditioned,
+/// in double precision they stay accurate up to MAX_BESSEL_ORDER
+///
+/// Args
+/// ----
+///     order: usize
+///         filter order
+///     norm: BesselNorm
+///         Phase = same asymptotes as a Butterworth cut off at 1 rad/s
+///         Delay = group delay of 1 s at DC
+///         Magnitude = -3 dB at 1 rad/s
+///
+/// Return
+/// ------
+///     Result<PoleZero>
+///         FilterError if the root search does not converge
+///
+pub fn bessel_prototype(order: usize, norm: BesselNorm) -> Result<PoleZero> {
+    // log of the coefficients a_k = (2n - k)! / (2^(n - k) k! (n - k)!) of
+    // s^k, from a_n = 1 down to a_0 = (2n)! / (2^n n!)
+    let n = order as f64;
+    let mut log_a = vec![0.0; order + 1];
+    for k in (0..order).rev() {
+        let ratio = (2.0 * n - k as f64) * (k as f64 + 1.0) / (2.0 * (n - k as f64));
+        log_a[k] = log_a[k + 1] + libm::log(ratio);
+    }
+
+    // s = a_0^(1/n) u keeps the coefficients of high orders finite, and the
+    // roots in u are already phase normalized
+    let log_scale = log_a[0] / n;
+    let polynomial: Vec<f64> = (0..=order).rev()
+        .map(|k| libm::exp(log_a[k] + k as f64 * log_scale - log_a[0]))
+        .collect();
+    let mut phase_poles = roots(&polynomial)?;
+
+    // exact conjugate pairs (and a real pole for odd orders), the roots
+    // found independently differ in the last digits
+    phase_poles.sort_by(|a, b| a.im.total_cmp(&b.im));
+    for i in 0..order.div_ceil(2) {
+        let pole = if i == order - 1 - i {
+            Complex64::new(phase_poles[i].re, 0.0)
+        } else {
+            (phase_poles[i] + phase_poles[order - 1 - i].conj()) / 2.0
+        };
+        phase_poles[i] = pole;
+        phase_poles[order - 1 - i] = pole.conj();
+    }
+
+    let scale = match norm {
+        BesselNorm::Phase => 1.0,
+        BesselNorm::Delay => libm::exp(log_scale),
+        BesselNorm::Magnitude => 1.0 / half_power_frequency(&phase_poles)
+    };
+    let poles: Vec<Complex64> = phase_poles.iter().map(|&p| p * scale).collect();
+    let gain = product_ratio(&[], &poles, |r| -r).inv().re;
+    Ok(PoleZero { zeros: Vec::new(), poles, gain })
+}
+
+// frequency (rad/s) where an all-pole low pass with unit DC gain is -3 dB,
+// found by bisection on its monotonic log magnitude
+fn half_power_frequency(poles: &[Complex64]) -> f64 {
+    let log_power = |w: f64| -> f64 {
+        poles.iter().map(|&p| libm::log(p.norm_sqr() / (Complex64::new(0.0, w) - p).norm_sqr())).sum()
+    };
+    let half = -core::f64::consts::LN_2;
+    let mut high = 1.0;
+    while log_power(high) > half {
+        high *= 2.0;
+    }
+    let mut low = 0.0;
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        if log_power(mid) > half { low = mid } else { high = mid }
+    }
+    0.5 * (low + high)
+}
+
+// prod(map(zeros)) / prod(map(poles)), one factor of each at a time so that
+// high orders don't overflow
+fn product_ratio(zeros: &[Complex64], poles: &[Complex64], map: impl Fn(Complex64) -> Complex64) -> Complex64 {
+    let mut ratio = Complex64::new(1.0, 0.0);
+    for i in 0..zeros.len().max(poles.len()) {
+        if let Some(&z) = zeros.get(i) {
+            ratio *= map(z);
+        }
+        if let Some(&p) = poles.get(i) {
+            ratio /= map(p);
+        }
+    }
+    ratio
+}
+
+// analog frequency (rad/s) of f after the bilinear transform at fs
+fn prewarped(f: f64, fs: f64) -> f64 {
+    2.0 * fs * libm::tan(core::f64::consts::PI * f / fs)
+}
+
+///
+/// ANALOG BAND TRANSFORM
+/// low pass prototype (cut off 1 rad/s) to a low pass, high pass, band pass
+/// or band stop filter with prewarped edges, so that the digital edges land
+/// exactly on the band frequencies
+///
+pub fn transform(prototype: &PoleZero, band: Band, fs: f64) -> PoleZero {
+    let PoleZero { zeros, poles, gain } = prototype;
+    let degree = poles.len() - zeros.len();
+    let origin = Complex64::new(0.0, 0.0);
+    match band {
+        Band::Lowpass(fc) => {
+            let wo = prewarped(fc, fs);
+            PoleZero {
+                zeros: zeros.iter().map(|&z| z * wo).collect(),
+                poles: poles.iter().map(|&p| p * wo).collect(),
+                gain: gain * libm::pow(wo, degree as f64)
+            }
+        },
+        Band::Highpass(fc) => {
+            let wo = prewarped(fc, fs);
+            let mut hp_zeros: Vec<Complex64> = zeros.iter().map(|&z| wo / z).collect();
+            hp_zeros.extend(vec![origin; degree]);
+            PoleZero {
+                zeros: hp_zeros,
+                poles: poles.iter().map(|&p| wo / p).collect(),
+                gain: gain * product_ratio(zeros, poles, |r| -r).re
+            }
+        },
+        Band::Bandpass(low, high) => {
+            let (w1, w2) = (prewarped(low, fs), prewarped(high, fs));
+            let (wo, bw) = (libm::sqrt(w1 * w2), w2 - w1);
+            let split = |roots: &[Complex64]| -> Vec<Complex64> {
+                let half: Vec<Complex64> = roots.iter().map(|&r| r * bw / 2.0).collect();
+                let upper = half.iter().map(|&h| h + (h * h - wo * wo).sqrt());
+                let lower = half.iter().map(|&h| h - (h * h - wo * wo).sqrt());
+                upper.chain(lower).collect()
+            };
+            let mut bp_zeros = split(zeros);
+            bp_zeros.extend(vec![origin; degree]);
+            PoleZero {
+                zeros: bp_zeros,
+                poles: split(poles),
+                gain: gain * libm::pow(bw, degree as f64)
+            }
+        },
+        Band::Bandstop(low, high) => {
+            let (w1, w2) = (prewarped(low, fs), prewarped(high, fs));
+            let (wo, bw) = (libm::sqrt(w1 * w2), w2 - w1);
+            let split = |roots: &[Complex64]| -> Vec<Complex64> {
+                let half: Vec<Complex64> = roots.iter().map(|&r| (bw / 2.0) / r).collect();
+                let upper = half.iter().map(|&h| h + (h * h - wo * wo).sqrt());
+                let lower = half.iter().map(|&h| h - (h * h - wo * wo).sqrt());
+                upper.chain(lower).collect()
+            };
+            let mut bs_zeros = split(zeros);
+            bs_zeros.extend(vec![Complex64::new(0.0, wo); degree]);
+            bs_zeros.extend(vec![Complex64::new(0.0, -wo); degree]);
+            PoleZero {
+                zeros: bs_zeros,
+                poles: split(poles),
+                gain: gain * product_ratio(zeros, poles, |r| -r).re
+            }
+        }
+    }
+}
+
+///
+/// BILINEAR TRANSFORM
+/// s = 2 fs (z - 1) / (z + 1), zeros at infinity are mapped to Nyquist
+///
+pub fn bilinear(analog: &PoleZero, fs: f64) -> PoleZero {
+    let fs2 = Complex64::new(2.0 * fs, 0.0);
+    let degree = analog.poles.len() - analog.zeros.len();
+    let mut zeros: Vec<Complex64> = analog.zeros.iter().map(|&z| (fs2 + z) / (fs2 - z)).collect();
+    zeros.extend(vec![Complex64::new(-1.0, 0.0); degree]);
+    PoleZero {
+        zeros,
+        poles: analog.poles.iter().map(|&p| (fs2 + p) / (fs2 - p)).collect(),
+        gain: analog.gain * product_ratio(&analog.zeros, &analog.poles, |r| fs2 - r).re
+    }
+}
+
+// real roots and one root (positive imaginary part) of every conjugate pair
+fn split_conjugates(roots: &[Complex64]) -> (Vec<Complex64>, Vec<f64>) {
+    let is_real = |root: &Complex64| root.im.abs() <= 1e-10 * root.norm().max(1.0);
+    let pairs = roots.iter().copied().filter(|root| !is_real(root) && root.im > 0.0).collect();
+    let reals = roots.iter().filter(|root| is_real(root)).map(|root| root.re).collect();
+    (pairs, reals)
+}
+
+// take the remaining real root closest to target
+fn take_nearest_real(reals: &mut Vec<f64>, target: Complex64) -> Option<f64> {
+    let index = (0..reals.len()).min_by(|&i, &j| {
+        (target - reals[i]).norm().total_cmp(&(target - reals[j]).norm())
+    })?;
+    Some(reals.swap_remove(index))
+}
+
+// monic polynomial [1, c1, c2] of up to two roots
+fn quadratic(roots: &[Complex64]) -> [f64; 3] {
+    match roots {
+        [] => [1.0, 0.0, 0.0],
+        [r] => [1.0, -r.re, 0.0],
+        [r1, r2] => [1.0, -(r1 + r2).re, (r1 * r2).re],
+        _ => unreachable!("sections hold at most two roots")
+    }
+}
+
+///
+/// ZEROS, POLES AND GAIN TO SECOND ORDER SECTIONS
+/// conjugate poles are grouped in pairs, real poles two by two. Sections
+/// are matched with their nearest zeros, starting from the poles closest
+/// to the unit circle, and ordered with those poles last. The gain is
+/// spread evenly over the sections (the whole gain of a high order design
+/// would underflow in single precision), its sign goes in the first one
+///
+/// Return
+/// ------
+///     Vec<[f64; 6]>
+///         sections [b0, b1, b2, a0 = 1, a1, a2]
+///
+pub fn zpk_to_sos(zpk: &PoleZero) -> Vec<[f64; 6]> {
+    let (pole_pairs, mut pole_reals) = split_conjugates(&zpk.poles);
+    let (mut zero_pairs, mut zero_reals) = split_conjugates(&zpk.zeros);
+
+    // pole groups, by decreasing radius
+    pole_reals.sort_by(|a, b| b.abs().total_cmp(&a.abs()));
+    let mut groups: Vec<Vec<Complex64>> = pole_pairs.iter().map(|&p| vec![p, p.conj()]).collect();
+    groups.extend(pole_reals.chunks(2).map(|chunk| chunk.iter().map(|&p| Complex64::new(p, 0.0)).collect()));
+    groups.sort_by(|a, b| b[0].norm().total_cmp(&a[0].norm()));
+    // a single real pole takes its (real) zero first
+    groups.sort_by_key(|group| group.len());
+
+    let mut sections: Vec<(f64, [f64; 6])> = groups.iter().map(|group| {
+        let target = group[0];
+        let nearest_pair = (0..zero_pairs.len()).min_by(|&i, &j| {
+            (target - zero_pairs[i]).norm().total_cmp(&(target - zero_pairs[j]).norm())
+        });
+        let nearest_real = zero_reals.iter().map(|&z| (target - z).norm()).fold(f64::INFINITY, f64::min);
+
+        let zeros: Vec<Complex64> = match nearest_pair {
+            Some(index) if group.len() == 2 && (target - zero_pairs[index]).norm() <= nearest_real => {
+                let z = zero_pairs.swap_remove(index);
+                vec![z, z.conj()]
+            },
+            _ => (0..group.len())
+                .filter_map(|_| take_nearest_real(&mut zero_reals, target))
+                .map(|z| Complex64::new(z, 0.0))
+                .collect()
+        };
+
+        let (b, a) = (quadratic(&zeros), quadratic(group));
+        (target.norm(), [b[0], b[1], b[2], a[0], a[1], a[2]])
+    }).collect();
+
+    sections.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut sos: Vec<[f64; 6]> = sections.into_iter().map(|(_, section)| section).collect();
+    let spread = libm::pow(zpk.gain.abs(), 1.0 / sos.len().max(1) as f64);
+    for (i, section) in sos.iter_mut().enumerate() {
+        let gain = if i == 0 { libm::copysign(spread, zpk.gain) } else { spread };
+        for b in section[..3].iter_mut() {
+            *b *= gain;
+        }
+    }
+    sos
+}
+
+///
+/// DIGITAL DESIGN
+/// analog prototype -> band transform -> bilinear transform -> sections.
+/// The gain is set so that the digital response matches the prototype DC
+/// gain where the prototype DC lands (DC for lp and br, Nyquist for hp,
+/// the geometric center for bp), which stays finite at high orders
+///
+pub fn design_sos(prototype: &PoleZero, band: Band, fs: f64) -> Vec<[f64; 6]> {
+    let mut digital = bilinear(&transform(prototype, band, fs), fs);
+
+    let reference = match band {
+        Band::Lowpass(_) | Band::Bandstop(..) => Complex64::new(1.0, 0.0),
+        Band::Highpass(_) => Complex64::new(-1.0, 0.0),
+        Band::Bandpass(low, high) => {
+            let wo = libm::sqrt(prewarped(low, fs) * prewarped(high, fs));
+            Complex64::from_polar(1.0, 2.0 * libm::atan(wo / (2.0 * fs)))
+        }
+    };
+    let dc_gain = prototype.gain * product_ratio(&prototype.zeros, &prototype.poles, |r| -r).re;
+    let unit_response = product_ratio(&digital.zeros, &digital.poles, |r| reference - r);
+    digital.gain = (dc_gain / unit_response).re;
+
+    zpk_to_sos(&digital)
+}
+
+///
+/// SECOND ORDER SECTIONS CASCADE
+/// sections normalized by a0, run in transposed direct form II with two
+/// state variables each
+///
+#[derive(Clone)]
+pub(crate) struct Cascade<T: Sample> {
+    sections: Vec<BiquadCoeffs<T>>,
+    state: Vec<[T; 2]>
+}
+
+impl<T: Sample> Cascade<T> {
+    pub(crate) fn new(n_sections: usize) -> Self {
+        let mut cascade = Self { sections: Vec::new(), state: Vec::new() };
+        cascade.set_sections(&vec![[1.0, 0.0, 0.0, 1.0, 0.0, 0.0]; n_sections]);
+        cascade
+    }
+
+    pub(crate) fn n_sections(&self) -> usize {
+        self.sections.len()
+    }
+
+    ///
+    /// SET SECTIONS
+    /// (the state is kept when the number of sections doesn't change)
+    ///
+    pub(crate) fn set_sections(&mut self, sections: &[[f64; 6]]) {
+        self.sections = sections.iter().map(|s| {
+            BiquadCoeffs::with_coeffs((s[0] / s[3], s[1] / s[3], s[2] / s[3], 1.0, s[4] / s[3], s[5] / s[3]))
+        }).collect();
+        self.state.resize(sections.len(), [T::zero(); 2]);
+    }
+
+    ///
+    /// FLAT COEFFICIENTS
+    /// [b0, b1, b2, a0, a1, a2] of every section
+    ///
+    pub(crate) fn coeffs(&self) -> Vec<f64> {
+        self.sections.iter().flat_map(|s| {
+            let (b0, b1, b2, a0, a1, a2) = s.get_coeffs();
+            [b0, b1, b2, a0, a1, a2]
+        }).collect()
+    }
+
+    ///
+    /// SECTIONS
+    /// [b0, b1, b2, a0 = 1, a1, a2] rows
+    ///
+    pub(crate) fn to_sos(&self) -> Vec<[f64; 6]> {
+        self.sections.iter().map(|s| {
+            let (b0, b1, b2, a0, a1, a2) = s.get_coeffs();
+            [b0, b1, b2, a0, a1, a2]
+        }).collect()
+    }
+
+    ///
+    /// SET FLAT COEFFICIENTS
+    /// the number of sections must not change
+    ///
+    pub(crate) fn set_coeffs(&mut self, coeffs: &[f64]) -> Result<()> {
+        let expected = 6 * self.sections.len();
+        if coeffs.len() != expected {
+            return Err(FilterError::InvalidCoefficients { expected, got: coeffs.len() })
+        }
+        let sections: Vec<[f64; 6]> = coeffs.chunks(6).map(|c| [c[0], c[1], c[2], c[3], c[4], c[5]]).collect();
+        self.set_sections(&sections);
+        Ok(())
+    }
+
+    ///
+    /// SECTIONS AS BIQUADS
+    /// one Biquad per section, in cascade order, with the chosen realization
+    ///
+    pub(crate) fn biquads(&self, fs: f64, topology: BiquadTopology) -> Vec<Biquad<T>> {
+        self.sections.iter().map(|s| {
+            let mut biquad = Biquad::with_topology(fs, topology);
+            biquad.set_coeffs(s.get_coeffs());
+            biquad
+        }).collect()
+    }
+
+    pub(crate) fn to_sections(&self) -> Vec<Section> {
+        self.sections.iter().map(|s| {
+            let (b0, b1, b2, a0, a1, a2) = s.get_coeffs();
+            Section::new(vec![b0, b1, b2], vec![a0, a1, a2])
+        }).collect()
+    }
+
+    #[inline]
+    pub(crate) fn process(&mut self, sample: T) -> T {
+        let mut x = sample;
+        for (c, s) in self.sections.iter().zip(self.state.iter_mut()) {
+            let y = c.b0 * x + s[0];
+            s[0] = c.b1 * x - c.a1 * y + s[1];
+            s[1] = c.b2 * x - c.a2 * y;
+            x = y;
+        }
+        x
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.state.fill([T::zero(); 2]);
+    }
+
+    ///
+    /// SET STATE
+    /// the two state variables of every section (the number of sections
+    /// must match)
+    ///
+    pub(crate) fn set_state(&mut self, state: &[[T; 2]]) {
+        self.state.iter_mut().zip(state).for_each(|(stored, s)| *stored = *s);
+    }
+
+    // number of sections and the two state variables of every section
+    pub(crate) fn save_state(&self, state: &mut FilterState) {
+        state.push(self.state.len() as f64);
+        self.state.iter().flatten().for_each(|&value| state.push(value));
+    }
+
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        reader.expect(self.state.len())?;
+        for value in self.state.iter_mut().flatten() {
+            *value = reader.read()?;
+        }
+        Ok(())
+    }
+}
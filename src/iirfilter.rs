@@ -0,0 +1,257 @@
+#![allow(clippy::wrong_self_convention)]
+#![allow(clippy::new_without_default)]
+
+use core::marker::PhantomData;
+use super::{sample::Sample, biquadeq::Biquad, filtertype::{FilterType, BiquadTopology}, iirdesign::Cascade, filtfilt::sos_zi, filter::Filter, error::{Result, check_length}};
+use alloc::vec::Vec;
+use super::freqz::{FrequencyResponse, Section};
+use super::sos::SecondOrderSections;
+use super::snapshot::{Snapshot, FilterState, StateReader};
+
+///
+/// IIR FAMILY
+/// analog prototype of an IirFilter (Butterworth, Chebyshev, elliptic,
+/// Bessel), each family adds its own design_filter
+///
+pub trait IirFamily: Clone {
+    /// family name (error messages and snapshots)
+    const NAME: &'static str;
+    /// mode reported before the first design
+    const INITIAL_MODE: FilterType;
+}
+
+///
+/// IIR FILTER
+/// cascaded second order sections designed from an analog prototype
+/// (see Butter, Chebyshev1, Chebyshev2, Elliptic and Bessel)
+///
+#[derive(Clone)]
+pub struct IirFilter<F: IirFamily, T: Sample = f64> {
+    pub(crate) fs: f64,
+    pub(crate) order: usize,
+    mode: FilterType,
+    cascade: Cascade<T>,
+    family: PhantomData<F>
+}
+
+impl<F: IirFamily, T: Sample> IirFilter<F, T> {
+    ///
+    /// INIT IIR FILTER CLASS
+    /// (pass through until design_filter)
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     order: usize
+    ///         order of the analog prototype (doubled by bp and br)
+    ///
+    pub fn new(fs: f64, order: usize) -> Self {
+        Self {
+            fs,
+            order,
+            mode: F::INITIAL_MODE,
+            cascade: Cascade::new(order.div_ceil(2)),
+            family: PhantomData
+        }
+    }
+
+    ///
+    /// STORE A DESIGN
+    /// (the state is kept when the number of sections doesn't change)
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         [b0, b1, b2, a0, a1, a2] of every section
+    ///
+    pub(crate) fn set_design(&mut self, sections: &[[f64; 6]], mode: FilterType) -> Vec<f64> {
+        self.mode = mode;
+        if sections.len() != self.cascade.n_sections() {
+            self.cascade = Cascade::new(sections.len());
+        }
+        self.cascade.set_sections(sections);
+        self.cascade.coeffs()
+    }
+
+    ///
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design, the number of sections must match the
+    /// current mode (order / 2 rounded up for lp and hp, order for bp and br)
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: &[f64]
+    ///         [b0, b1, b2, a0, a1, a2] of every section
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the number of coefficients does not match the mode
+    ///
+    pub fn set_coeffs(&mut self, coeffs: &[f64]) -> Result<()> {
+        self.cascade.set_coeffs(coeffs)
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         [b0, b1, b2, a0, a1, a2] of every section
+    ///
+    pub fn coeffs(&self) -> Vec<f64> {
+        self.cascade.coeffs()
+    }
+
+    pub fn mode(&self) -> FilterType {
+        self.mode
+    }
+
+    ///
+    /// SECTIONS AS BIQUADS
+    /// the stored design as a chain of Biquad filters (one per section, to be
+    /// run in order), e.g. to use another realization
+    ///
+    /// Args
+    /// ----
+    ///     topology: BiquadTopology
+    ///         realization of every biquad
+    ///
+    pub fn biquads(&self, topology: BiquadTopology) -> Vec<Biquad<T>> {
+        self.cascade.biquads(self.fs, topology)
+    }
+
+    ///
+    /// SET FILTER STATE
+    /// arbitrary initial state of every section
+    ///
+    /// Args
+    /// ----
+    ///     state: &[[T; 2]]
+    ///         transposed direct form II state [s1, s2] of every section
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the number of rows is not the number of sections
+    ///
+    pub fn set_state(&mut self, state: &[[T; 2]]) -> Result<()> {
+        check_length(self.cascade.n_sections(), state.len())?;
+        self.cascade.set_state(state);
+        Ok(())
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the stored design once a unit step has settled
+    /// (as scipy.signal.sosfilt_zi), in the layout of set_state.
+    /// Zero state from the first section with a pole at DC
+    ///
+    /// Return
+    /// ------
+    ///     Vec<[T; 2]>
+    ///         state for a unit input, scale it by the input level
+    ///
+    pub fn step_state(&self) -> Vec<[T; 2]> {
+        sos_zi(&self.sos()).iter().map(|z| [T::from_f64(z[0]), T::from_f64(z[1])]).collect()
+    }
+
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input x0, so that a signal
+    /// starting at x0 (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: T
+    ///         input level, usually the first sample of the signal
+    ///
+    pub fn set_steady_state(&mut self, x0: T) {
+        let x0 = x0.to_f64();
+        let state: Vec<[T; 2]> = sos_zi(&self.sos()).iter().map(|z| [T::from_f64(z[0] * x0), T::from_f64(z[1] * x0)]).collect();
+        self.cascade.set_state(&state);
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample
+    ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        self.cascade.process(sample)
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: &[T]
+    ///         input frame
+    ///
+    /// Return
+    /// ------
+    ///     Vec<T>
+    ///         filtered frame
+    ///
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
+        frame.iter().map(|&x| self.filt_sample(x)).collect()
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set the two state variables of every section to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+}
+
+impl<F: IirFamily, T: Sample> Filter<T> for IirFilter<F, T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
+        self.cascade.reset();
+    }
+}
+
+impl<F: IirFamily, T: Sample> FrequencyResponse for IirFilter<F, T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        self.cascade.to_sections()
+    }
+}
+
+impl<F: IirFamily, T: Sample> SecondOrderSections for IirFilter<F, T> {
+    fn sos(&self) -> Vec<[f64; 6]> {
+        self.cascade.to_sos()
+    }
+}
+
+// section states
+impl<F: IirFamily, T: Sample> Snapshot for IirFilter<F, T> {
+    const FAMILY: &'static str = F::NAME;
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.cascade.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.cascade.load_state(reader)
+    }
+}
@@ -1,3 +1,8 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod delayline;
 pub mod biquadeq;
 pub mod twozerotwopole;
@@ -5,32 +10,89 @@ pub mod filtertype;
 pub mod coeffstruct;
 pub mod onepole;
 pub mod dc;
+#[cfg(feature = "alloc")]
 pub mod harmonic;
 pub mod narrow;
 pub mod zavalishin;
+#[cfg(feature = "alloc")]
 pub mod butter;
+#[cfg(feature = "alloc")]
+pub mod iirdesign;
+#[cfg(feature = "alloc")]
+pub mod iirfilter;
+#[cfg(feature = "alloc")]
+pub mod bessel;
+#[cfg(feature = "alloc")]
+pub mod chebyshev;
+#[cfg(feature = "alloc")]
+pub mod elliptic;
+#[cfg(feature = "alloc")]
+pub mod fir;
+#[cfg(feature = "alloc")]
+pub mod remez;
+#[cfg(feature = "alloc")]
+mod fft;
+#[cfg(feature = "alloc")]
+pub mod convolver;
+pub mod filter;
+pub mod error;
+pub mod sample;
+pub mod realization;
+pub mod smoothing;
+pub mod fastmath;
+#[cfg(feature = "alloc")]
+pub mod freqz;
+#[cfg(feature = "alloc")]
+pub mod polezero;
+#[cfg(feature = "alloc")]
+pub mod sos;
+#[cfg(feature = "alloc")]
+pub mod filtfilt;
+#[cfg(feature = "alloc")]
+pub mod snapshot;
+#[cfg(feature = "alloc")]
+pub mod multichannel;
 
-use pyo3::prelude::*;
-use biquadeq::Biquad;
-use twozerotwopole::TwoZeroTwoPole;
-use onepole::OnePole;
-use dc::DcFilter;
-use harmonic::Harmonic;
-use narrow::Narrow;
-use zavalishin::Zavalishin;
-use butter::Butter;
-
+#[cfg(feature = "python")]
+mod python;
 
-/// A Python module implemented in Rust.
-#[pymodule]
-fn rustlibfilt(_py: Python, m: &PyModule) -> PyResult<()> {
-    m.add_class::<Biquad>()?;
-    m.add_class::<TwoZeroTwoPole>()?;
-    m.add_class::<OnePole>()?;
-    m.add_class::<DcFilter>()?;
-    m.add_class::<Harmonic>()?;
-    m.add_class::<Narrow>()?;
-    m.add_class::<Zavalishin>()?;
-    m.add_class::<Butter>()?;
-    Ok(())
-}
\ No newline at end of file
+pub use filter::Filter;
+pub use error::FilterError;
+pub use sample::Sample;
+#[cfg(feature = "alloc")]
+pub use multichannel::Multichannel;
+#[cfg(feature = "alloc")]
+pub use freqz::FrequencyResponse;
+#[cfg(feature = "alloc")]
+pub use polezero::PoleZeroAnalysis;
+#[cfg(feature = "alloc")]
+pub use sos::{Sos, SecondOrderSections};
+#[cfg(feature = "alloc")]
+pub use filtfilt::{filtfilt, sosfiltfilt};
+#[cfg(feature = "alloc")]
+pub use snapshot::{Snapshot, FilterState};
+pub use biquadeq::Biquad;
+pub use filtertype::BiquadTopology;
+pub use twozerotwopole::TwoZeroTwoPole;
+pub use onepole::OnePole;
+pub use dc::DcFilter;
+#[cfg(feature = "alloc")]
+pub use harmonic::Harmonic;
+pub use narrow::Narrow;
+pub use zavalishin::Zavalishin;
+#[cfg(feature = "alloc")]
+pub use iirfilter::IirFilter;
+#[cfg(feature = "alloc")]
+pub use butter::Butter;
+#[cfg(feature = "alloc")]
+pub use bessel::Bessel;
+#[cfg(feature = "alloc")]
+pub use chebyshev::{Chebyshev1, Chebyshev2};
+#[cfg(feature = "alloc")]
+pub use elliptic::Elliptic;
+#[cfg(feature = "alloc")]
+pub use fir::Fir;
+#[cfg(feature = "alloc")]
+pub use remez::remez;
+#[cfg(feature = "alloc")]
+pub use convolver::Convolver;
@@ -0,0 +1,333 @@
+use alloc::{vec, vec::Vec};
+use super::{sample::Sample, filter::Filter, snapshot::{Snapshot, FilterState, StateReader}, error::{self, FilterError, check_length}};
+
+///
+/// MULTICHANNEL FILTER
+/// one filter per channel sharing the same design, each with its own state.
+/// The design is applied through design/for_each so that every channel
+/// (and the prototype used to add channels) stays in sync
+///
+#[derive(Clone)]
+pub struct Multichannel<F: Clone> {
+    prototype: F,
+    channels: Vec<F>
+}
+
+impl<F: Clone> Multichannel<F> {
+    ///
+    /// INIT MULTICHANNEL FILTER
+    ///
+    /// Args
+    /// ----
+    ///     filter: F
+    ///         filter (design and state) copied on every channel
+    ///     n_channels: usize
+    ///         number of channels (at least 1)
+    ///
+    pub fn new(filter: F, n_channels: usize) -> Self {
+        Self { channels: vec![filter.clone(); n_channels.max(1)], prototype: filter }
+    }
+
+    pub fn n_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    ///
+    /// SET NUMBER OF CHANNELS
+    /// existing channels keep their state, new channels start from the
+    /// current design with a cleared state
+    ///
+    /// Args
+    /// ----
+    ///     n_channels: usize
+    ///         number of channels (at least 1)
+    ///
+    pub fn set_channels(&mut self, n_channels: usize) {
+        self.channels.resize(n_channels.max(1), self.prototype.clone());
+    }
+
+    pub fn channel(&self, index: usize) -> &F {
+        &self.channels[index]
+    }
+
+    pub fn channel_mut(&mut self, index: usize) -> &mut F {
+        &mut self.channels[index]
+    }
+
+    ///
+    /// APPLY A DESIGN TO EVERY CHANNEL
+    /// the operation runs on a copy of the prototype and of every channel,
+    /// which replace them only if it succeeds everywhere (on error nothing
+    /// changes). The channel states are kept
+    ///
+    /// Args
+    /// ----
+    ///     op: FnMut(&mut F) -> Result<R, E>
+    ///         design operation (e.g. |f| f.design_filter("lp", 1000.0, 0.7, None))
+    ///
+    /// Return
+    /// ------
+    ///     Result<R, E>
+    ///         result of the operation on the prototype
+    ///
+    pub fn design<R, E>(&mut self, mut op: impl FnMut(&mut F) -> Result<R, E>) -> Result<R, E> {
+        let mut prototype = self.prototype.clone();
+        let result = op(&mut prototype)?;
+        let mut channels = self.channels.clone();
+        for channel in channels.iter_mut() {
+            op(channel)?;
+        }
+        self.prototype = prototype;
+        self.channels = channels;
+        Ok(result)
+    }
+
+    ///
+    /// APPLY AN OPERATION TO EVERY CHANNEL
+    /// (and to the prototype, e.g. set_coeffs)
+    ///
+    pub fn for_each(&mut self, mut op: impl FnMut(&mut F)) {
+        op(&mut self.prototype);
+        for channel in self.channels.iter_mut() {
+            op(channel);
+        }
+    }
+
+    pub fn prototype(&self) -> &F {
+        &self.prototype
+    }
+
+    ///
+    /// PROTOTYPE
+    /// (design copied on the channels added by set_channels, it never
+    /// processes samples)
+    ///
+    pub fn prototype_mut(&mut self) -> &mut F {
+        &mut self.prototype
+    }
+
+    // samples per channel of a block, FilterError if the block length is not
+    // a multiple of the number of channels
+    fn samples_per_channel(&self, length: usize) -> error::Result<usize> {
+        let n_channels = self.channels.len();
+        if length.is_multiple_of(n_channels) {
+            Ok(length / n_channels)
+        } else {
+            Err(FilterError::InvalidChannelLength { length, n_channels })
+        }
+    }
+
+    ///
+    /// APPLY FILTER ON PLANAR BLOCK
+    /// channels x samples, channel c at input[c * n_samples..(c + 1) * n_samples]
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         planar input block, length n_channels * n_samples
+    ///     output: &mut [T]
+    ///         planar output block, same length as input
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the input length is not a multiple of
+    ///         n_channels or the output length differs
+    ///
+    pub fn process_planar<T: Sample>(&mut self, input: &[T], output: &mut [T]) -> error::Result<()> where F: Filter<T> {
+        let n_samples = self.samples_per_channel(input.len())?;
+        check_length(input.len(), output.len())?;
+        if n_samples == 0 {
+            return Ok(())
+        }
+        for ((channel, x), y) in self.channels.iter_mut().zip(input.chunks(n_samples)).zip(output.chunks_mut(n_samples)) {
+            channel.process_block(x, y);
+        }
+        Ok(())
+    }
+
+    ///
+    /// APPLY FILTER ON INTERLEAVED BLOCK
+    /// samples x channels, sample n of channel c at input[n * n_channels + c]
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         interleaved input block, length n_samples * n_channels
+    ///     output: &mut [T]
+    ///         interleaved output block, same length as input
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the input length is not a multiple of
+    ///         n_channels or the output length differs
+    ///
+    pub fn process_interleaved<T: Sample>(&mut self, input: &[T], output: &mut [T]) -> error::Result<()> where F: Filter<T> {
+        self.samples_per_channel(input.len())?;
+        check_length(input.len(), output.len())?;
+        let n_channels = self.channels.len();
+        for (x, y) in input.chunks_exact(n_channels).zip(output.chunks_exact_mut(n_channels)) {
+            for (c, channel) in self.channels.iter_mut().enumerate() {
+                y[c] = channel.process_sample(x[c]);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE ON PLANAR BLOCK
+    /// (FilterError if the buffer length is not a multiple of n_channels)
+    ///
+    pub fn process_planar_inplace<T: Sample>(&mut self, buffer: &mut [T]) -> error::Result<()> where F: Filter<T> {
+        let n_samples = self.samples_per_channel(buffer.len())?;
+        if n_samples == 0 {
+            return Ok(())
+        }
+        for (channel, x) in self.channels.iter_mut().zip(buffer.chunks_mut(n_samples)) {
+            channel.process_inplace(x);
+        }
+        Ok(())
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE ON INTERLEAVED BLOCK
+    /// (FilterError if the buffer length is not a multiple of n_channels)
+    ///
+    pub fn process_interleaved_inplace<T: Sample>(&mut self, buffer: &mut [T]) -> error::Result<()> where F: Filter<T> {
+        self.samples_per_channel(buffer.len())?;
+        let n_channels = self.channels.len();
+        for x in buffer.chunks_exact_mut(n_channels) {
+            for (c, channel) in self.channels.iter_mut().enumerate() {
+                x[c] = channel.process_sample(x[c]);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// RESET EVERY CHANNEL STATE
+    ///
+    pub fn reset<T: Sample>(&mut self) where F: Filter<T> {
+        for channel in self.channels.iter_mut() {
+            channel.reset();
+        }
+    }
+}
+
+// number of channels and the state of every channel (the prototype has no state)
+impl<F: Snapshot> Snapshot for Multichannel<F> {
+    const FAMILY: &'static str = F::FAMILY;
+
+    fn save_state(&self, state: &mut FilterState) {
+        state.push(self.channels.len() as f64);
+        self.channels.iter().for_each(|channel| channel.save_state(state));
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> error::Result<()> {
+        // every channel stores at least one value
+        let n_channels = reader.read_index(reader.remaining() + 1)?;
+        if n_channels == 0 {
+            return Err(FilterError::InvalidState("no channels"))
+        }
+        self.set_channels(n_channels);
+        for channel in self.channels.iter_mut() {
+            channel.load_state(reader)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biquadeq::Biquad;
+
+    const N_CHANNELS: usize = 3;
+    const N_SAMPLES: usize = 256;
+
+    fn channel_signal(channel: usize, n: usize) -> f64 {
+        libm::sin(0.01 * (channel + 1) as f64 * n as f64 * 7.0) + if n == channel { 1.0 } else { 0.0 }
+    }
+
+    fn designed() -> Biquad {
+        let mut bq = Biquad::new(48000.0);
+        bq.design_filter("lp", 2000.0, 1.5, None).unwrap();
+        bq
+    }
+
+    // every channel run on its own mono filter
+    fn mono_outputs() -> Vec<Vec<f64>> {
+        (0..N_CHANNELS).map(|c| {
+            let mut bq = designed();
+            (0..N_SAMPLES).map(|n| bq.filt_sample(channel_signal(c, n))).collect()
+        }).collect()
+    }
+
+    #[test]
+    fn planar_channels_match_mono_filters() {
+        let mut filter = Multichannel::new(designed(), N_CHANNELS);
+        let input: Vec<f64> = (0..N_CHANNELS).flat_map(|c| (0..N_SAMPLES).map(move |n| channel_signal(c, n))).collect();
+        let mut output = vec![0.0; input.len()];
+        // two blocks, so that the states carry over
+        let half = N_SAMPLES / 2;
+        for block in 0..2 {
+            let x: Vec<f64> = (0..N_CHANNELS).flat_map(|c| input[c * N_SAMPLES + block * half..c * N_SAMPLES + (block + 1) * half].to_vec()).collect();
+            let mut y = vec![0.0; x.len()];
+            filter.process_planar(&x, &mut y).unwrap();
+            for c in 0..N_CHANNELS {
+                output[c * N_SAMPLES + block * half..c * N_SAMPLES + (block + 1) * half].copy_from_slice(&y[c * half..(c + 1) * half]);
+            }
+        }
+        for (c, expected) in mono_outputs().iter().enumerate() {
+            assert_eq!(&output[c * N_SAMPLES..(c + 1) * N_SAMPLES], &expected[..], "channel {c}");
+        }
+    }
+
+    #[test]
+    fn interleaved_channels_match_mono_filters() {
+        let mut filter = Multichannel::new(designed(), N_CHANNELS);
+        let mut buffer: Vec<f64> = (0..N_SAMPLES).flat_map(|n| (0..N_CHANNELS).map(move |c| channel_signal(c, n))).collect();
+        let mut output = vec![0.0; buffer.len()];
+        filter.process_interleaved(&buffer, &mut output).unwrap();
+
+        let mut inplace = Multichannel::new(designed(), N_CHANNELS);
+        inplace.process_interleaved_inplace(&mut buffer).unwrap();
+        assert_eq!(buffer, output);
+
+        for (c, expected) in mono_outputs().iter().enumerate() {
+            let channel: Vec<f64> = output.iter().skip(c).step_by(N_CHANNELS).copied().collect();
+            assert_eq!(&channel, expected, "channel {c}");
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_lengths() {
+        let mut filter = Multichannel::new(designed(), N_CHANNELS);
+        let mut output = vec![0.0; 10];
+        assert_eq!(filter.process_planar(&[0.0; 10], &mut output), Err(FilterError::InvalidChannelLength { length: 10, n_channels: N_CHANNELS }));
+        assert_eq!(filter.process_interleaved(&[0.0; 10], &mut output), Err(FilterError::InvalidChannelLength { length: 10, n_channels: N_CHANNELS }));
+        assert_eq!(filter.process_planar_inplace(&mut output), Err(FilterError::InvalidChannelLength { length: 10, n_channels: N_CHANNELS }));
+        assert_eq!(filter.process_interleaved_inplace(&mut output), Err(FilterError::InvalidChannelLength { length: 10, n_channels: N_CHANNELS }));
+        let mut output = vec![0.0; 6];
+        assert_eq!(filter.process_planar(&[0.0; 9], &mut output), Err(FilterError::LengthMismatch { expected: 9, got: 6 }));
+        assert_eq!(filter.process_interleaved(&[0.0; 9], &mut output), Err(FilterError::LengthMismatch { expected: 9, got: 6 }));
+    }
+
+    #[test]
+    fn failed_design_changes_no_channel() {
+        let mut filter = Multichannel::new(designed(), N_CHANNELS);
+        let coeffs = filter.channel(0).coeffs();
+        // fails on the second channel, after the prototype and the first channel succeeded
+        let mut calls = 0;
+        let result = filter.design(|bq| {
+            calls += 1;
+            if calls == 3 { Err(FilterError::MissingDesign) } else { bq.design_filter("hp", 100.0, 0.707, None) }
+        });
+        assert_eq!(result, Err(FilterError::MissingDesign));
+        assert_eq!(filter.prototype().coeffs(), coeffs);
+        for c in 0..N_CHANNELS {
+            assert_eq!(filter.channel(c).coeffs(), coeffs, "channel {c}");
+        }
+    }
+}
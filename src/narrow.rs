@@ -1,8 +1,15 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use pyo3::prelude::*;
-use super::{filtertype::{FilterType, NarrowFilterType}, coeffstruct::BiquadCoeffs, delayline::DelayLine};
+use super::{sample::Sample, filtertype::{FilterType, NarrowFilterType}, coeffstruct::BiquadCoeffs, delayline::{DelayLine, MAX_ORDER}, filter::Filter, error::{Result, FilterError, ModeName, check_fc, check_bw, check_order, check_length}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::sos::SecondOrderSections;
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
 struct DesignNarrowFilter {
     mode: FilterType,
@@ -14,12 +21,12 @@ struct DesignNarrowFilter {
 
 impl DesignNarrowFilter {
     fn new(mode: FilterType, fc: f64, fs: f64, bw: f64) -> Self {
-        const TWOPI: f64 = 2.0 * std::f64::consts::PI;
+        const TWOPI: f64 = 2.0 * core::f64::consts::PI;
         let filt_coeffs = BiquadCoeffs::new();
         let w = TWOPI * fc / fs;
-        let theta_cosine = w.cos();
+        let theta_cosine = libm::cos(w);
         let r = 1.0 - 3.0 * bw / fs;
-        let k = (1.0 - 2.0 * r * theta_cosine + r.powf(2.0)) / (2.0 - 2.0 * theta_cosine);
+        let k = (1.0 - 2.0 * r * theta_cosine + libm::pow(r, 2.0)) / (2.0 - 2.0 * theta_cosine);
 
         Self {
             mode,
@@ -35,10 +42,10 @@ impl DesignNarrowFilter {
             FilterType::NarrowType(NarrowFilterType::Bp) => {
                 let b0: f64 = 1.0 - self.k;
                 let b1: f64 = 2.0 * (self.k - self.r) * self.theta_cosine;
-                let b2: f64 = self.r.powf(2.0) - self.k;
+                let b2: f64 = libm::pow(self.r, 2.0) - self.k;
                 
                 let a1: f64 = 2.0 * self.r * self.theta_cosine;
-                let a2: f64 = -self.r.powf(2.0);
+                let a2: f64 = -libm::pow(self.r, 2.0);
 
                 self.filt_coeffs.set_coeffs((b0, b1, b2, 1.0, a1, a2))
             },
@@ -48,7 +55,7 @@ impl DesignNarrowFilter {
                 let b2: f64 = self.k;
                 
                 let a1: f64 = 2.0 * self.r * self.theta_cosine;
-                let a2: f64 = -self.r.powf(2.0);
+                let a2: f64 = -libm::pow(self.r, 2.0);
 
                 self.filt_coeffs.set_coeffs((b0, b1, b2, 1.0, a1, a2))
             },
@@ -58,21 +65,20 @@ impl DesignNarrowFilter {
 
 }
 
-#[pyclass]
-pub struct Narrow {
+#[derive(Clone)]
+pub struct Narrow<T: Sample = f64> {
     fs: f64,
-    x1: DelayLine,
-    x2: DelayLine,
-    y1: DelayLine,
-    y2: DelayLine,
+    coeffs: BiquadCoeffs<T>,
+    x1: DelayLine<T>,
+    // two samples per section: x[n - 2], y[n - 2] of every cascaded section
+    x2: DelayLine<T, [T; 2 * MAX_ORDER]>,
+    y1: DelayLine<T>,
+    y2: DelayLine<T, [T; 2 * MAX_ORDER]>,
     order: usize,
 }
 
-#[pymethods]
-impl Narrow {
+impl<T: Sample> Narrow<T> {
     
-    #[new]
-
     ///
     /// INIT NARROW CLASS
     /// 
@@ -81,29 +87,29 @@ impl Narrow {
     ///     fs: f64
     ///         sampling rate
     ///     order: usize
-    ///         filter order
-    /// 
-    
-    #[pyo3(text_signature = "(fs: float, order: int = 1) -> None")]
-    pub fn new(fs: f64, order: Option<usize>) -> Self {
-        
-        let filt_order = match order {
-            Some(order_value) => { order_value },
-            None => { 1 }
-        };
-
-        Self { 
+    ///         filter order (1..=MAX_ORDER)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Narrow>
+    ///         FilterError if order is not in 1..=MAX_ORDER
+    ///
+    pub fn new(fs: f64, order: usize) -> Result<Self> {
+        check_order(order)?;
+        Ok(Self {
             fs, 
-            x1: DelayLine::new(filt_order), 
-            x2: DelayLine::new(filt_order), 
-            y1: DelayLine::new(filt_order), 
-            y2: DelayLine::new(filt_order),
-            order: filt_order,
-        }
+            coeffs: BiquadCoeffs::with_coeffs((1.0, 0.0, 0.0, 1.0, 0.0, 0.0)),
+            x1: DelayLine::new_unchecked(order), 
+            x2: DelayLine::new_unchecked(2 * order), 
+            y1: DelayLine::new_unchecked(order), 
+            y2: DelayLine::new_unchecked(2 * order),
+            order,
+        })
     }
     
     ///
     /// GENERATE BIQUAD FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame)
     ///
     /// Args
     /// ----
@@ -120,61 +126,157 @@ impl Narrow {
     /// 
     /// Return
     /// ------
-    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///     Result<tuple> -> (f64, f64, f64, f64, f64, f64):
     ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///         FilterError on unknown mode, invalid fc or bw, or unstable
+    ///         design (pole radius |1 - 3 * bw / fs| at or above 1.0)
     ///         
-
-    #[pyo3(text_signature = "(mode: str, fc: float, bw: float) -> tuple[float, float, float, float, float, float]")]
-    pub fn design_filter(&self, mode: &str, fc: f64, bw: f64) -> (f64, f64, f64, f64, f64, f64) {
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: f64) -> Result<(f64, f64, f64, f64, f64, f64)> {
 
         let filt_type: FilterType = match mode {
             "bp" => FilterType::NarrowType(NarrowFilterType::Bp),
             "notch" => FilterType::NarrowType(NarrowFilterType::Notch),
-            _ => {
-                println!("[ERROR] Filt mode not allowed!");
-                std::process::exit(1)
-            }
+            _ => return Err(FilterError::UnknownMode { family: "narrow", mode: ModeName::new(mode) })
         };
+
+        check_fc(fc, self.fs)?;
+        check_bw(bw)?;
     
         let mut design_filter: DesignNarrowFilter = DesignNarrowFilter::new(filt_type, fc, self.fs, bw);
+        if design_filter.r.abs() >= 1.0 {
+            return Err(FilterError::UnstableDesign { family: "narrow", radius: design_filter.r.abs() })
+        }
         design_filter.coeffs();
-        let coeffs = design_filter.filt_coeffs;
-        
-        let b0 = coeffs.b0;
-        let b1 = coeffs.b1;
-        let b2 = coeffs.b2;
-        let a0 = coeffs.a0;
-        let a1 = coeffs.a1;
-        let a2 = coeffs.a2;
-    
-        (b0, b1, b2, a0, a1, a2)
+        self.coeffs = design_filter.filt_coeffs.cast();
+
+        Ok(design_filter.filt_coeffs.get_coeffs())
     
     }
 
     ///
-    /// APPLY FILTER SAMPLE BY SAMPLE
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design
     ///
     /// Args
     /// ----
-    ///     sample: f64
-    ///         input sample
     ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
     ///         filter coefficients (b0, b1, b2, a0, a1, a2)
     ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
+        self.coeffs.set_coeffs(coeffs);
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///
+    pub fn coeffs(&self) -> (f64, f64, f64, f64, f64, f64) {
+        self.coeffs.get_coeffs()
+    }
+
+    ///
+    /// SET FILTER STATE
+    /// arbitrary initial state of every cascaded section
+    ///
+    /// Args
+    /// ----
+    ///     state: &[[T; 4]]
+    ///         [x[n - 1], x[n - 2], y[n - 1], y[n - 2]] of every section (order rows)
+    ///
     /// Return
     /// ------
-    ///     f64
+    ///     Result<()>
+    ///         FilterError if the number of rows is not the order
+    ///
+    pub fn set_state(&mut self, state: &[[T; 4]]) -> Result<()> {
+        check_length(self.order, state.len())?;
+        Filter::reset(self);
+        // x2/y2 hold two samples per section: n - 2 of every section first, then n - 1
+        for &[x1, x2, y1, y2] in state {
+            self.x1.write_and_advance(&x1);
+            self.x2.write_and_advance(&x2);
+            self.y1.write_and_advance(&y1);
+            self.y2.write_and_advance(&y2);
+        }
+        for &[x1, _, y1, _] in state {
+            self.x2.write_and_advance(&x1);
+            self.y2.write_and_advance(&y1);
+        }
+        Ok(())
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the current coefficients once a unit step has settled
+    /// (as scipy.signal.lfilter_zi), in the layout of set_state.
+    /// Zero state when a pole sits at DC
+    ///
+    /// Return
+    /// ------
+    ///     Vec<[T; 4]>
+    ///         state for a unit input, scale it by the input level
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn step_state(&self) -> Vec<[T; 4]> {
+        let mut state = [[T::zero(); 4]; MAX_ORDER];
+        self.fill_steady_state(&mut state, 1.0);
+        state[..self.order].to_vec()
+    }
+
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input x0, so that a signal
+    /// starting at x0 (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: T
+    ///         input level, usually the first sample of the signal
+    ///
+    pub fn set_steady_state(&mut self, x0: T) {
+        let mut state = [[T::zero(); 4]; MAX_ORDER];
+        self.fill_steady_state(&mut state, x0.to_f64());
+        // the number of rows is the order, set_state cannot fail
+        let result = self.set_state(&state[..self.order]);
+        debug_assert!(result.is_ok());
+    }
+
+    // [x, x, y, y] levels of every section for a constant input, x times the DC gain per section
+    fn fill_steady_state(&self, state: &mut [[T; 4]; MAX_ORDER], x0: f64) {
+        let (b0, b1, b2, _, a1, a2) = self.coeffs();
+        let gain = (b0 + b1 + b2) / (1.0 - a1 - a2);
+        let mut level = if gain.is_finite() { x0 } else { 0.0 };
+        for section in state[..self.order].iter_mut() {
+            let (x, y) = (T::from_f64(level), T::from_f64(level * gain));
+            *section = [x, x, y, y];
+            level *= gain;
+        }
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
     ///         filtered sample
     ///
     ///
-    
-    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float, float, float, float, float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: (f64, f64, f64, f64, f64, f64)) -> f64 {
+    pub fn filt_sample(&mut self, sample: T) -> T {
 
         let mut x = sample;
-        let mut y: f64 = 0.0;
+        let mut y = T::zero();
         for _ in 0..self.order {
-            y = coeffs.0 * x + coeffs.1 * self.x1.read() + coeffs.2 * self.x2.read() + coeffs.4 * self.y1.read() + coeffs.5 * self.y2.read();
+            y = self.coeffs.b0 * x + self.coeffs.b1 * self.x1.read() + self.coeffs.b2 * self.x2.read() + self.coeffs.a1 * self.y1.read() + self.coeffs.a2 * self.y2.read();
 
             self.x1.write_and_advance(&x);
             self.x2.write_and_advance(&x);
@@ -193,24 +295,21 @@ impl Narrow {
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     ///         input sample
-    ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
-    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
+    ///     Vec<T>
     ///         filtered frame
     ///
     ///
-
-    #[pyo3(text_signature = "(frame: list[float], coeffs: tuple[float, float, float, float, float, float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: (f64, f64, f64, f64, f64, f64)) -> Vec<f64> {
+    #[cfg(feature = "alloc")]
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
         
-        let y: Vec<f64> = frame
+        let y: Vec<T> = frame
             .iter()
-            .map(|&x| self.filt_sample(x, coeffs))
+            .map(|&x| self.filt_sample(x))
             .collect();
         y
     }
@@ -223,14 +322,87 @@ impl Narrow {
     ///     y[n - 1] = 0.0
     ///     y[n - 2] = 0.0
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+
+
+}
+
+impl<T: Sample> Filter<T> for Narrow<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
         self.x1.clear();
         self.x2.clear();
         self.y1.clear();
         self.y2.clear();
-        println!("[DONE] cache cleared!")
     }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for Narrow<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    // y[n] = b0 x[n] + b1 x[n - 1] + b2 x[n - 2] + a1 y[n - 1] + a2 y[n - 2], order times
+    fn sections(&self) -> Vec<Section> {
+        let (b0, b1, b2, _, a1, a2) = self.coeffs();
+        vec![Section::new(vec![b0, b1, b2], vec![1.0, -a1, -a2]); self.order]
+    }
+}
+
+// the same section, order times
+#[cfg(feature = "alloc")]
+impl<T: Sample> SecondOrderSections for Narrow<T> {
+    fn sos(&self) -> Vec<[f64; 6]> {
+        let (b0, b1, b2, _, a1, a2) = self.coeffs();
+        vec![[b0, b1, b2, 1.0, -a1, -a2]; self.order]
+    }
+}
+
+// x and y delay lines of the cascade
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for Narrow<T> {
+    const FAMILY: &'static str = "narrow";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.x1.save_state(state);
+        self.x2.save_state(state);
+        self.y1.save_state(state);
+        self.y2.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.x1.load_state(reader)?;
+        self.x2.load_state(reader)?;
+        self.y1.load_state(reader)?;
+        self.y2.load_state(reader)?;
+        Ok(())
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-}
\ No newline at end of file
+    #[test]
+    fn steady_state_gives_a_flat_output_for_dc_input() {
+        let x0 = 0.5;
+        for order in [1, 3, 8] {
+            let mut notch: Narrow = Narrow::new(48000.0, order).unwrap();
+            notch.design_filter("notch", 1000.0, 100.0).unwrap();
+            // DC gain of one section, order times
+            let (b0, b1, b2, _, a1, a2) = notch.coeffs();
+            let level = x0 * libm::pow((b0 + b1 + b2) / (1.0 - a1 - a2), order as f64);
+            notch.set_steady_state(x0);
+            for n in 0..200 {
+                let y = notch.filt_sample(x0);
+                assert!((y - level).abs() < 1e-12, "order {order} sample {n}: {y} != {level}");
+            }
+        }
+    }
+}
@@ -1,8 +1,15 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use super::{filtertype::{FilterType, OnePoleFilterType}, coeffstruct::OnePoleCoeffs, delayline::DelayLine};
-use pyo3::prelude::*;
+use super::{sample::Sample, filtertype::{FilterType, OnePoleFilterType}, coeffstruct::OnePoleCoeffs, delayline::{DelayLine, MAX_ORDER}, smoothing::{Smoother, SmoothedParam}, fastmath::{exp_approx, fc_ratio}, filter::Filter, error::{Result, FilterError, ModeName, check_fc, check_order, check_length}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::sos::SecondOrderSections;
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
 pub struct DesignOnePoleFilter {
     mode: FilterType,
@@ -12,14 +19,16 @@ pub struct DesignOnePoleFilter {
 
 impl DesignOnePoleFilter {
     pub fn new(mode: FilterType, fc: f64, fs: f64) -> Self {
-        let filt_coeffs = OnePoleCoeffs::new();
-        let twopi = 2.0 * std::f64::consts::PI;
+        let twopi = 2.0 * core::f64::consts::PI;
         let w = twopi * fc / fs;
-        let alpha = (-2.0 * w).exp();
+        Self::with_alpha(mode, libm::exp(-2.0 * w))
+    }
 
+    // alpha = exp(-4 * pi * fc / fs) already computed (modulated path)
+    fn with_alpha(mode: FilterType, alpha: f64) -> Self {
         Self {
             mode,
-            filt_coeffs,
+            filt_coeffs: OnePoleCoeffs::new(),
             alpha
         }
     }
@@ -44,18 +53,36 @@ impl DesignOnePoleFilter {
 }
 
 
-#[pyclass]
-pub struct OnePole {
+fn design_coeffs(mode: FilterType, fc: f64, fs: f64) -> [f64; 3] {
+    let mut design_filter = DesignOnePoleFilter::new(mode, fc, fs);
+    design_filter.coeffs();
+    let (b0, b1, a1) = design_filter.filt_coeffs.get_coeffs();
+    [b0, b1, a1]
+}
+
+// fc clamped by fc_ratio, exp approximation (no transcendental call)
+fn design_coeffs_modulated(mode: FilterType, fc: f64, fs: f64) -> [f64; 3] {
+    let alpha = exp_approx(-4.0 * core::f64::consts::PI * fc_ratio(fc, fs));
+    let mut design_filter = DesignOnePoleFilter::with_alpha(mode, alpha);
+    design_filter.coeffs();
+    let (b0, b1, a1) = design_filter.filt_coeffs.get_coeffs();
+    [b0, b1, a1]
+}
+
+
+#[derive(Clone)]
+pub struct OnePole<T: Sample = f64> {
     fs: f64,
-    x: DelayLine,
-    y: DelayLine,
+    coeffs: OnePoleCoeffs<T>,
+    x: DelayLine<T>,
+    y: DelayLine<T>,
     order: usize,
+    mode: Option<FilterType>,
+    // fc
+    smoother: Smoother<1, 3>
 }
 
-#[pymethods]
-impl OnePole {
-    #[new]
-
+impl<T: Sample> OnePole<T> {
     ///
     /// INIT ONEPOLE CLASS
     ///
@@ -64,27 +91,30 @@ impl OnePole {
     ///     fs: f64
     ///         sampling rate
     ///     order: usize
-    ///         filter order
+    ///         filter order (1..=MAX_ORDER)
     ///
-    
-    #[pyo3(text_signature = "(fs: float, order: int = 1) -> None")]
-    pub fn new(fs: f64, order: Option<usize>) -> Self {
-
-        let filt_order = match order {
-            Some(order_value) => { order_value }
-            None => { 1 }
-        };
-        
-        Self { 
+    /// Return
+    /// ------
+    ///     Result<OnePole>
+    ///         FilterError if order is not in 1..=MAX_ORDER
+    ///
+    pub fn new(fs: f64, order: usize) -> Result<Self> {
+        check_order(order)?;
+        Ok(Self {
             fs, 
-            x: DelayLine::new(filt_order), 
-            y: DelayLine::new(filt_order),
-            order: filt_order,
-        }
+            coeffs: OnePoleCoeffs::with_coeffs((1.0, 0.0, 0.0)),
+            x: DelayLine::new_unchecked(order), 
+            y: DelayLine::new_unchecked(order),
+            order,
+            mode: None,
+            smoother: Smoother::new([SmoothedParam::new(1000.0, true)], [1.0, 0.0, 0.0])
+        })
     }
 
     ///
     /// GENERATE ONE POLE FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame, applied at
+    /// once: use set_target to glide to a new fc)
     ///
     /// Args
     /// ----
@@ -99,27 +129,202 @@ impl OnePole {
     /// 
     /// Return
     /// ------
-    ///     tuple -> (f64, f64):
-    ///         filter coefficients (b0, a1)
+    ///     Result<tuple> -> (f64, f64, f64):
+    ///         filter coefficients (b0, b1, a1)
+    ///         FilterError on unknown mode or invalid fc
     /// 
-
-    #[pyo3(text_signature = "(mode: str, fc: float) -> tuple[float, float, float]")]
-    pub fn design_filter(&mut self, mode: &str, fc: f64) -> (f64, f64, f64) {
+    pub fn design_filter(&mut self, mode: &str, fc: f64) -> Result<(f64, f64, f64)> {
 
         let filt_type: FilterType = match mode {
             "lp" => FilterType::OnePoleType(OnePoleFilterType::LowPass),
             "hp" => FilterType::OnePoleType(OnePoleFilterType::HighPass),
-            _ => {
-                println!("[ERROR] Filt mode not allowed!");
-                std::process::exit(1)
-            }          
+            _ => return Err(FilterError::UnknownMode { family: "onepole", mode: ModeName::new(mode) })
         };
 
-        let mut design_filter = DesignOnePoleFilter::new(filt_type, fc, self.fs);
-        design_filter.coeffs();
+        check_fc(fc, self.fs)?;
+
+        let coeffs = design_coeffs(filt_type, fc, self.fs);
+        let [b0, b1, a1] = coeffs;
+        self.coeffs.set_coeffs((b0, b1, a1));
+        self.mode = Some(filt_type);
+        self.smoother.snap([fc], coeffs);
+
+        Ok((b0, b1, a1))
+
+    }
+
+    ///
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design (stops any glide)
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: tuple(f64, f64, f64)
+    ///         filter coefficients (b0, b1, a1)
+    ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64)) {
+        self.coeffs.set_coeffs(coeffs);
+        let (b0, b1, a1) = coeffs;
+        self.smoother.snap_coeffs([b0, b1, a1]);
+    }
+
+    ///
+    /// SET GLIDE TIME
+    /// used by set_target: fc glides over `time`, the coefficients are
+    /// designed every `control_interval` samples and linearly interpolated
+    /// in between
+    ///
+    /// Args
+    /// ----
+    ///     time: f64
+    ///         glide time in sec. (0.0 = no smoothing, default)
+    ///     control_interval: usize
+    ///         samples between two coefficient designs (DEFAULT_CONTROL_INTERVAL = 32)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on negative time
+    ///
+    pub fn set_glide(&mut self, time: f64, control_interval: usize) -> Result<()> {
+        self.smoother.set_time(time, self.fs, control_interval)
+    }
+
+    ///
+    /// GLIDE TO A NEW CUTOFF
+    /// (same mode as the last design_filter, on a log scale)
+    ///
+    /// Args
+    /// ----
+    ///     fc: f64
+    ///         target corner/cutoff frequency in Hz
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if no design was set or on invalid fc
+    ///
+    pub fn set_target(&mut self, fc: f64) -> Result<()> {
+        let mode = self.mode.ok_or(FilterError::MissingDesign)?;
+        check_fc(fc, self.fs)?;
+
+        if !self.smoother.set_targets([fc]) {
+            let coeffs = design_coeffs(mode, fc, self.fs);
+            let [b0, b1, a1] = coeffs;
+            self.coeffs.set_coeffs((b0, b1, a1));
+            self.smoother.snap([fc], coeffs);
+        }
+        Ok(())
+    }
+
+    ///
+    /// GET CURRENT CUTOFF
+    /// (fc reached by the glide)
+    ///
+    pub fn fc(&self) -> f64 {
+        self.smoother.values()[0]
+    }
+
+    pub fn is_gliding(&self) -> bool {
+        self.smoother.is_smoothing()
+    }
+
+    ///
+    /// SKIP THE GLIDE
+    /// jump to the target cutoff of set_target
+    ///
+    pub fn skip_glide(&mut self) {
+        if let (Some(mode), true) = (self.mode, self.smoother.is_smoothing()) {
+            let [fc] = self.smoother.targets();
+            let coeffs = design_coeffs(mode, fc, self.fs);
+            let [b0, b1, a1] = coeffs;
+            self.coeffs.set_coeffs((b0, b1, a1));
+            self.smoother.snap([fc], coeffs);
+        }
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64, f64):
+    ///         filter coefficients (b0, b1, a1)
+    ///
+    pub fn coeffs(&self) -> (f64, f64, f64) {
+        self.coeffs.get_coeffs()
+    }
+
+    ///
+    /// SET FILTER STATE
+    /// arbitrary initial state of every cascaded section
+    ///
+    /// Args
+    /// ----
+    ///     state: &[[T; 2]]
+    ///         [x[n - 1], y[n - 1]] of every section (order rows)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the number of rows is not the order
+    ///
+    pub fn set_state(&mut self, state: &[[T; 2]]) -> Result<()> {
+        check_length(self.order, state.len())?;
+        self.x.clear();
+        self.y.clear();
+        for &[x, y] in state {
+            self.x.write_and_advance(&x);
+            self.y.write_and_advance(&y);
+        }
+        Ok(())
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the current coefficients once a unit step has settled
+    /// (as scipy.signal.lfilter_zi), in the layout of set_state.
+    /// Zero state when the pole sits at DC
+    ///
+    /// Return
+    /// ------
+    ///     Vec<[T; 2]>
+    ///         state for a unit input, scale it by the input level
+    ///
+    #[cfg(feature = "alloc")]
+    pub fn step_state(&self) -> Vec<[T; 2]> {
+        let mut state = [[T::zero(); 2]; MAX_ORDER];
+        self.fill_steady_state(&mut state, 1.0);
+        state[..self.order].to_vec()
+    }
 
-        (design_filter.filt_coeffs.b0, design_filter.filt_coeffs.b1, design_filter.filt_coeffs.a1)
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input x0, so that a signal
+    /// starting at x0 (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: T
+    ///         input level, usually the first sample of the signal
+    ///
+    pub fn set_steady_state(&mut self, x0: T) {
+        let mut state = [[T::zero(); 2]; MAX_ORDER];
+        self.fill_steady_state(&mut state, x0.to_f64());
+        // the number of rows is the order, set_state cannot fail
+        let result = self.set_state(&state[..self.order]);
+        debug_assert!(result.is_ok());
+    }
 
+    // [x, y] levels of every section for a constant input, x times the DC gain per section
+    fn fill_steady_state(&self, state: &mut [[T; 2]; MAX_ORDER], x0: f64) {
+        let (b0, b1, a1) = self.coeffs();
+        let gain = (b0 + b1) / (1.0 - a1);
+        let mut level = if gain.is_finite() { x0 } else { 0.0 };
+        for section in state[..self.order].iter_mut() {
+            *section = [T::from_f64(level), T::from_f64(level * gain)];
+            level *= gain;
+        }
     }
 
     ///
@@ -127,25 +332,33 @@ impl OnePole {
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     ///         input sample
-    ///     coeffs: tuple(f64, f64)
-    ///         filter coefficients (b0, a1)
     ///
     /// Return
     /// ------
-    ///     f64
+    ///     T
     ///         filtered sample
     ///
     ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
 
-    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float, float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: (f64, f64, f64)) -> f64 {
+        if let Some(mode) = self.mode {
+            let fs = self.fs;
+            if let Some([b0, b1, a1]) = self.smoother.tick(|[fc]| design_coeffs(mode, fc, fs)) {
+                self.coeffs.set_coeffs((b0, b1, a1));
+            }
+        }
+
+        self.filt_order(sample)
+    }
 
+    // cascade of `order` one pole sections with the current coefficients
+    fn filt_order(&mut self, sample: T) -> T {
         let mut x = sample;
-        let mut y: f64 = 0.0;
+        let mut y = T::zero();
         for _ in 0..self.order {
-            y = coeffs.0 * x + coeffs.1 * self.x.read() + coeffs.2 * self.y.read();
+            y = self.coeffs.b0 * x + self.coeffs.b1 * self.x.read() + self.coeffs.a1 * self.y.read();
             self.x.write_and_advance(&x);
             self.y.write_and_advance(&y);
             x = y;
@@ -154,29 +367,69 @@ impl OnePole {
         y
     }
 
+    ///
+    /// APPLY FILTER WITH AUDIO-RATE MODULATION
+    /// the coefficients of the last design_filter mode are recomputed at
+    /// every sample from fc[n] through an exp approximation (no
+    /// transcendental call per sample), fc clamped to
+    /// [MIN_FC_RATIO * fs, MAX_FC_RATIO * fs] (NaN to the lowest). The last
+    /// coefficients stay as the stored design
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         input frame
+    ///     fc: &[f64]
+    ///         cutoff frequency in Hz per sample (same length as input)
+    ///     output: &mut [T]
+    ///         filtered frame (same length as input)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if no design was set or on mismatched lengths
+    ///
+    pub fn filt_modulated(&mut self, input: &[T], fc: &[f64], output: &mut [T]) -> Result<()> {
+        let mode = self.mode.ok_or(FilterError::MissingDesign)?;
+        check_length(input.len(), fc.len())?;
+        check_length(input.len(), output.len())?;
+        if input.is_empty() {
+            return Ok(())
+        }
+
+        let mut coeffs = [0.0; 3];
+        for ((&x, y), &f) in input.iter().zip(output.iter_mut()).zip(fc) {
+            coeffs = design_coeffs_modulated(mode, f, self.fs);
+            let [b0, b1, a1] = coeffs;
+            self.coeffs.set_coeffs((b0, b1, a1));
+            *y = self.filt_order(x);
+        }
+
+        let fc_last = fc_ratio(fc[fc.len() - 1], self.fs) * self.fs;
+        self.smoother.snap([fc_last], coeffs);
+        Ok(())
+    }
+
     ///
     /// APPLY FILTER ON FRAME OR SIGNAL
     ///
     /// Args
     /// ----
-    ///     frame: Vec<f64>
+    ///     frame: &[f64]
     ///         input frame
-    ///     coeffs: tuple(f64, f64)
-    ///         filter coefficients (b0, a1)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
+    ///     Vec<T>
     ///         filtered frame
     ///
     ///
-
-    #[pyo3(text_signature = "(frame: liat[float], coeffs: tuple[float, float, float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: (f64, f64, f64)) -> Vec<f64> {
+    #[cfg(feature = "alloc")]
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
 
         let y = frame
             .iter()
-            .map(|&x| self.filt_sample(x, coeffs))
+            .map(|&x| self.filt_sample(x))
             .collect();
 
         y
@@ -189,13 +442,137 @@ impl OnePole {
     ///     x[n - 1] = 0.0
     ///     y[n - 1] = 0.0
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+
+
+
+}
+
+impl<T: Sample> Filter<T> for OnePole<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
         self.x.clear();
         self.y.clear();
-        println!("[DONE] cache cleared!")
     }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for OnePole<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    // y[n] = b0 x[n] + b1 x[n - 1] + a1 y[n - 1], order times
+    fn sections(&self) -> Vec<Section> {
+        let (b0, b1, a1) = self.coeffs();
+        vec![Section::new(vec![b0, b1], vec![1.0, -a1]); self.order]
+    }
+}
+
+// order first order sections y[n] = b0 x[n] + b1 x[n - 1] + a1 y[n - 1]
+#[cfg(feature = "alloc")]
+impl<T: Sample> SecondOrderSections for OnePole<T> {
+    fn sos(&self) -> Vec<[f64; 6]> {
+        let (b0, b1, a1) = self.coeffs();
+        vec![[b0, b1, 0.0, 1.0, -a1, 0.0]; self.order]
+    }
+}
+
+// x and y delay lines of the cascade and running glide (the restored filter
+// continues with the coefficients reached by the glide)
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for OnePole<T> {
+    const FAMILY: &'static str = "onepole";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.x.save_state(state);
+        self.y.save_state(state);
+        self.smoother.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.x.load_state(reader)?;
+        self.y.load_state(reader)?;
+        self.smoother.load_state(reader)?;
+        let [b0, b1, a1] = self.smoother.coeffs();
+        self.coeffs.set_coeffs((b0, b1, a1));
+        Ok(())
+    }
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn steady_state_gives_a_flat_output_for_dc_input() {
+        let x0 = -0.8;
+        for order in [1, 4, MAX_ORDER] {
+            let mut lp: OnePole = OnePole::new(48000.0, order).unwrap();
+            lp.design_filter("lp", 200.0).unwrap();
+            lp.set_steady_state(x0);
+            for n in 0..200 {
+                let y = lp.filt_sample(x0);
+                assert!((y - x0).abs() < 1e-12, "order {order} sample {n}: {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn set_coeffs_stops_the_glide() {
+        let external = (0.3, 0.0, 0.7);
+        let mut lp: OnePole = OnePole::new(48000.0, 2).unwrap();
+        lp.design_filter("lp", 200.0).unwrap();
+        lp.set_glide(0.05, 32).unwrap();
+        lp.set_target(5000.0).unwrap();
+        lp.filt_sample(1.0);
+        lp.set_coeffs(external);
+        assert!(!lp.is_gliding());
+        for n in 0..100 {
+            lp.filt_sample(0.5);
+            assert_eq!(lp.coeffs(), external, "sample {n}");
+        }
+    }
 
-}
\ No newline at end of file
+    const N_MODULATED: usize = 1000;
+
+    // exponential sweep from 200 Hz to 6 kHz
+    fn sweep() -> [f64; N_MODULATED] {
+        core::array::from_fn(|n| 200.0 * libm::pow(30.0, n as f64 / N_MODULATED as f64))
+    }
+
+    #[test]
+    fn modulated_matches_per_sample_design() {
+        let fc = sweep();
+        let input: [f64; N_MODULATED] = core::array::from_fn(|n| libm::sin(0.07 * n as f64) + if n == 0 { 1.0 } else { 0.0 });
+        for mode in ["lp", "hp"] {
+            let mut filter: OnePole = OnePole::new(48000.0, 2).unwrap();
+            filter.design_filter(mode, 1000.0).unwrap();
+            let mut output = [0.0; N_MODULATED];
+            filter.filt_modulated(&input, &fc, &mut output).unwrap();
+
+            let mut reference: OnePole = OnePole::new(48000.0, 2).unwrap();
+            for n in 0..N_MODULATED {
+                reference.design_filter(mode, fc[n]).unwrap();
+                let y = reference.filt_sample(input[n]);
+                assert!((output[n] - y).abs() < 1e-8, "{mode} sample {n}: {} != {y}", output[n]);
+            }
+        }
+    }
+
+    #[test]
+    fn modulated_keeps_the_pole_inside_the_unit_circle() {
+        let mut filter: OnePole = OnePole::new(48000.0, 1).unwrap();
+        filter.design_filter("lp", 1000.0).unwrap();
+        let mut output = [0.0; 3];
+        filter.filt_modulated(&[1.0; 3], &[0.0, f64::NAN, -5.0], &mut output).unwrap();
+        assert!(output.iter().all(|y| y.is_finite()));
+        let (_, _, a1) = filter.coeffs();
+        assert!(a1 < 1.0, "pole at {a1}");
+    }
+}
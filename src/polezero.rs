@@ -0,0 +1,313 @@
+use alloc::{vec, vec::Vec};
+use num_complex::Complex64;
+use super::{freqz::{FrequencyResponse, Section}, error::{Result, FilterError}};
+
+const MAX_ITERATIONS: usize = 500;
+
+///
+/// POLYNOMIAL ROOTS
+/// roots in z of p[0] z^n + p[1] z^(n - 1) + ... + p[n], i.e. of the
+/// polynomial p(z^-1) multiplied by z^n. Leading zero coefficients are
+/// roots at infinity and are dropped, trailing zero coefficients are roots
+/// at the origin. Quadratics and binomials (z^n + c, comb filters) are
+/// solved in closed form, other polynomials with the Aberth-Ehrlich iteration
+///
+/// Args
+/// ----
+///     p: &[f64]
+///         polynomial coefficients, highest power first
+///
+/// Return
+/// ------
+///     Result<Vec<Complex64>>
+///         n roots (empty for a constant or null polynomial)
+///         FilterError if the iteration does not converge in MAX_ITERATIONS
+///
+pub fn roots(p: &[f64]) -> Result<Vec<Complex64>> {
+    let first = match p.iter().position(|&c| c != 0.0) {
+        Some(first) => first,
+        None => return Ok(Vec::new())
+    };
+    let last = p.iter().rposition(|&c| c != 0.0).unwrap_or(first);
+    let mut found = vec![Complex64::new(0.0, 0.0); p.len() - 1 - last];
+
+    let monic: Vec<f64> = p[first..=last].iter().map(|&c| c / p[first]).collect();
+    let degree = monic.len() - 1;
+    if degree == 0 {
+        return Ok(found)
+    }
+
+    if degree == 2 {
+        found.extend(quadratic(monic[1], monic[2]));
+        return Ok(found)
+    }
+
+    if monic[1..degree].iter().all(|&c| c == 0.0) {
+        // z^n = -c
+        let radius = libm::pow(monic[degree].abs(), 1.0 / degree as f64);
+        let angle = if monic[degree] > 0.0 { core::f64::consts::PI } else { 0.0 };
+        let two_pi = 2.0 * core::f64::consts::PI;
+        found.extend((0..degree).map(|k| Complex64::from_polar(radius, (angle + two_pi * k as f64) / degree as f64)));
+        return Ok(found)
+    }
+
+    found.extend(aberth(&monic)?);
+    Ok(found)
+}
+
+// z^2 + b z + c, without cancellation between -b and the discriminant
+fn quadratic(b: f64, c: f64) -> [Complex64; 2] {
+    let discriminant = b * b - 4.0 * c;
+    if discriminant >= 0.0 {
+        let q = -0.5 * (b + libm::copysign(libm::sqrt(discriminant), b));
+        let other = if q == 0.0 { 0.0 } else { c / q };
+        [Complex64::new(q, 0.0), Complex64::new(other, 0.0)]
+    } else {
+        let imaginary = 0.5 * libm::sqrt(-discriminant);
+        [Complex64::new(-0.5 * b, imaginary), Complex64::new(-0.5 * b, -imaginary)]
+    }
+}
+
+// simultaneous Newton iteration with the Aberth correction, started on a
+// circle of radius |p[n]|^(1/n) (the geometric mean of the root moduli)
+fn aberth(monic: &[f64]) -> Result<Vec<Complex64>> {
+    let degree = monic.len() - 1;
+    let radius = libm::pow(monic[degree].abs(), 1.0 / degree as f64);
+    let step = 2.0 * core::f64::consts::PI / degree as f64;
+    let mut z: Vec<Complex64> = (0..degree).map(|k| Complex64::from_polar(radius, step * k as f64 + 0.4)).collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut converged = true;
+        for i in 0..degree {
+            let (value, derivative) = horner(monic, z[i]);
+            // |p(z)| within the rounding error of its evaluation: z is a
+            // root to working precision (ill-conditioned roots, as the high
+            // order Bessel ones, never settle below the step tolerance)
+            let modulus = z[i].norm();
+            let rounding = f64::EPSILON * degree as f64 * monic.iter().fold(0.0, |acc, &c| acc * modulus + c.abs());
+            if value.norm() <= rounding {
+                continue
+            }
+            let ratio = value / derivative;
+            let repulsion: Complex64 = (0..degree)
+                .filter(|&j| j != i)
+                .map(|j| (z[i] - z[j]).inv())
+                .sum();
+            let correction = ratio / (1.0 - ratio * repulsion);
+            if !correction.is_finite() {
+                // a non-finite p(z) (non-finite coefficients) never converges
+                converged &= value.is_finite();
+                continue
+            }
+            z[i] -= correction;
+            if correction.norm() > 1e-14 * z[i].norm().max(1.0) {
+                converged = false;
+            }
+        }
+        if converged {
+            return Ok(z)
+        }
+    }
+    Err(FilterError::RootsNotConverged(MAX_ITERATIONS))
+}
+
+// p(z) and p'(z)
+fn horner(p: &[f64], z: Complex64) -> (Complex64, Complex64) {
+    let mut value = Complex64::new(0.0, 0.0);
+    let mut derivative = Complex64::new(0.0, 0.0);
+    for &c in p {
+        derivative = derivative * z + value;
+        value = value * z + c;
+    }
+    (value, derivative)
+}
+
+///
+/// POLES AND ZEROS
+/// of a transfer function, H(z) = gain * prod(z - zeros) / prod(z - poles)
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoleZero {
+    pub zeros: Vec<Complex64>,
+    pub poles: Vec<Complex64>,
+    pub gain: f64
+}
+
+impl PoleZero {
+    ///
+    /// POLES AND ZEROS OF A SECTION
+    /// b and a are padded to the same length, so that FIR sections have
+    /// their poles at the origin (FilterError if a root search does not
+    /// converge)
+    ///
+    pub fn from_section(section: &Section) -> Result<Self> {
+        let length = section.b.len().max(section.a.len());
+        let padded = |p: &[f64]| {
+            let mut p = p.to_vec();
+            p.resize(length, 0.0);
+            p
+        };
+        let leading = |p: &[f64]| p.iter().copied().find(|&c| c != 0.0).unwrap_or(0.0);
+        Ok(Self {
+            zeros: roots(&padded(&section.b))?,
+            poles: roots(&padded(&section.a))?,
+            gain: leading(&section.b) / leading(&section.a)
+        })
+    }
+
+    ///
+    /// POLES AND ZEROS OF A CASCADE
+    /// (roots of every section, gains multiplied). Poles and zeros at the
+    /// origin coming from different sections cancel out
+    ///
+    pub fn from_sections(sections: &[Section]) -> Result<Self> {
+        let mut cascade = Self { zeros: Vec::new(), poles: Vec::new(), gain: 1.0 };
+        for section in sections {
+            let section = Self::from_section(section)?;
+            cascade.zeros.extend(section.zeros);
+            cascade.poles.extend(section.poles);
+            cascade.gain *= section.gain;
+        }
+
+        let origin = Complex64::new(0.0, 0.0);
+        let count = |roots: &[Complex64]| roots.iter().filter(|&&root| root == origin).count();
+        let cancel = count(&cascade.zeros).min(count(&cascade.poles));
+        for roots in [&mut cascade.zeros, &mut cascade.poles] {
+            let mut left = cancel;
+            roots.retain(|&root| {
+                let keep = root != origin || left == 0;
+                if !keep {
+                    left -= 1;
+                }
+                keep
+            });
+        }
+        Ok(cascade)
+    }
+
+    ///
+    /// LARGEST POLE RADIUS
+    /// (0.0 without poles)
+    ///
+    pub fn max_pole_radius(&self) -> f64 {
+        self.poles.iter().map(|pole| pole.norm()).fold(0.0, f64::max)
+    }
+
+    ///
+    /// STABILITY MARGIN
+    /// distance of the outermost pole from the unit circle, 1 - max |pole|
+    /// (negative or 0.0 for unstable designs)
+    ///
+    pub fn stability_margin(&self) -> f64 {
+        1.0 - self.max_pole_radius()
+    }
+
+    ///
+    /// STABILITY
+    /// true if every pole lies strictly inside the unit circle
+    ///
+    pub fn is_stable(&self) -> bool {
+        self.max_pole_radius() < 1.0
+    }
+}
+
+///
+/// POLE/ZERO ANALYSIS OF A DESIGN
+/// available on every filter implementing FrequencyResponse, from the same
+/// sections used by freqz (FilterError if a root search does not converge)
+///
+pub trait PoleZeroAnalysis: FrequencyResponse {
+    fn pole_zero(&self) -> Result<PoleZero> {
+        PoleZero::from_sections(&self.sections())
+    }
+
+    fn is_stable(&self) -> Result<bool> {
+        Ok(self.pole_zero()?.is_stable())
+    }
+
+    fn stability_margin(&self) -> Result<f64> {
+        Ok(self.pole_zero()?.stability_margin())
+    }
+}
+
+impl<F: FrequencyResponse + ?Sized> PoleZeroAnalysis for F {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{biquadeq::Biquad, harmonic::Harmonic, butter::Butter, narrow::Narrow, freqz::poly_mul};
+
+    const FS: f64 = 48000.0;
+
+    // every expected root matched by a distinct found root
+    fn assert_roots(mut found: Vec<Complex64>, expected: Vec<Complex64>, tolerance: f64) {
+        assert_eq!(found.len(), expected.len());
+        for e in expected {
+            let nearest = (0..found.len()).min_by(|&i, &j| (found[i] - e).norm().total_cmp(&(found[j] - e).norm())).unwrap();
+            assert!((found[nearest] - e).norm() < tolerance, "{} != {e}", found[nearest]);
+            found.swap_remove(nearest);
+        }
+    }
+
+    #[test]
+    fn biquad_poles() {
+        // a = [1, -2 r cos(theta), r^2]: poles r e^(+-j theta)
+        let (r, theta) = (0.9, 0.3);
+        let mut bq: Biquad = Biquad::new(FS);
+        bq.set_coeffs((1.0, 0.5, 0.25, 1.0, -2.0 * r * libm::cos(theta), r * r));
+        let pz = bq.pole_zero().unwrap();
+        assert_roots(pz.poles.clone(), vec![Complex64::from_polar(r, theta), Complex64::from_polar(r, -theta)], 1e-12);
+        assert!((pz.max_pole_radius() - r).abs() < 1e-12);
+        assert_eq!(pz.gain, 1.0);
+    }
+
+    #[test]
+    fn aberth_finds_known_roots() {
+        let expected = vec![Complex64::new(0.5, 0.0), Complex64::new(-0.25, 0.0), Complex64::new(0.0, 0.8), Complex64::new(0.0, -0.8), Complex64::new(0.3, 0.6), Complex64::new(0.3, -0.6)];
+        let factors: [&[f64]; 4] = [&[1.0, -0.5], &[1.0, 0.25], &[1.0, 0.0, 0.64], &[1.0, -0.6, 0.45]];
+        let p = factors.iter().fold(vec![1.0], |p, q| poly_mul(&p, q));
+        assert_roots(roots(&p).unwrap(), expected, 1e-10);
+    }
+
+    #[test]
+    fn comb_roots_are_evenly_spread() {
+        // 1 + g z^-N: N poles of radius g^(1/N) = 10^(-3 / (fs t60))
+        let (delay, t60) = (8, 0.01);
+        let mut comb: Harmonic = Harmonic::new("combi", delay, FS).unwrap();
+        comb.design_filter(t60, None).unwrap();
+        let g = comb.sections()[0].a[delay];
+        let poles = comb.pole_zero().unwrap().poles;
+        assert_eq!(poles.len(), delay);
+        let radius = libm::pow(10.0, -3.0 / (FS * t60));
+        for pole in poles {
+            assert!((pole.norm() - radius).abs() < 1e-12, "{pole}");
+            assert!((pole.powi(delay as i32) + g).norm() < 1e-12, "{pole}");
+        }
+    }
+
+    #[test]
+    fn butter_poles_are_inside_the_unit_circle() {
+        for (mode, order) in [("lp", 8), ("hp", 5), ("bp", 6)] {
+            let mut butter: Butter = Butter::new(FS, order);
+            butter.design_filter(mode, 2000.0, Some(500.0)).unwrap();
+            let pz = butter.pole_zero().unwrap();
+            let expected = if mode == "bp" { 2 * order } else { order };
+            assert_eq!(pz.poles.len(), expected, "{mode}");
+            assert!(pz.is_stable() && pz.stability_margin() > 0.0, "{mode}: {}", pz.max_pole_radius());
+        }
+    }
+
+    #[test]
+    fn narrow_rejects_poles_on_the_unit_circle() {
+        // r = 1 - 3 * bw / fs, |r| >= 1 from bw = 2 * fs / 3
+        let mut narrow: Narrow = Narrow::new(FS, 1).unwrap();
+        let bw = 2.0 * FS / 3.0;
+        assert_eq!(narrow.design_filter("notch", 1000.0, bw), Err(FilterError::UnstableDesign { family: "narrow", radius: 1.0 }));
+        assert!(matches!(narrow.design_filter("notch", 1000.0, 1.5 * bw), Err(FilterError::UnstableDesign { .. })));
+    }
+
+    #[test]
+    fn non_finite_polynomials_do_not_converge() {
+        assert_eq!(roots(&[1.0, f64::NAN, 0.5, 0.25]), Err(FilterError::RootsNotConverged(MAX_ITERATIONS)));
+    }
+}
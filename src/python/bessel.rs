@@ -0,0 +1,61 @@
+use pyo3::prelude::*;
+use crate::bessel::Bessel;
+use crate::filtertype::BesselNorm;
+use crate::multichannel::Multichannel;
+use super::Precision;
+
+iir_filter_class!(PyBessel, "Bessel", Bessel, {
+    #[new]
+
+    ///
+    /// INIT BESSEL CLASS
+    /// 
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     order: usize
+    ///         filter order, 1..=20
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (coefficients are always designed in double precision)
+    ///
+    #[pyo3(text_signature = "(fs: float, order: int = 1, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, order = None, dtype = "float64"))]
+    pub fn new(fs: f64, order: Option<usize>, dtype: &str) -> PyResult<Self> {
+        let order = order.unwrap_or(1);
+        Ok(Self { inner: Precision::new(dtype, || Multichannel::new(Bessel::new(fs, order), 1), || Multichannel::new(Bessel::new(fs, order), 1))? })
+    }
+
+    ///
+    /// GENERATE BESSEL (THOMSON) FILTER COEFFICIENTS
+    /// maximally flat group delay, cascaded second order sections
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///     fc: f64
+    ///         cut off frequency in Hz
+    ///     norm: &str
+    ///         phase = magnitude asymptotes of a Butterworth at fc (default)
+    ///         delay = group delay of 1 / (2 * pi * fc) sec. at DC
+    ///         mag = -3 dB at fc
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode, norm, order or fc
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float, norm: str = 'phase') -> list[float]")]
+    #[pyo3(signature = (mode, fc, norm = "phase"))]
+    pub fn design_filter(&mut self, mode: &str, fc: f64, norm: &str) -> PyResult<Vec<f64>> {
+        let norm = BesselNorm::from_name(norm)?;
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc, norm)))?)
+    }
+});
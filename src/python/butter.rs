@@ -0,0 +1,60 @@
+use pyo3::prelude::*;
+use crate::butter::Butter;
+use crate::multichannel::Multichannel;
+use super::Precision;
+
+iir_filter_class!(PyButter, "Butter", Butter, {
+    #[new]
+
+    ///
+    /// INIT BUTTER CLASS
+    /// 
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     order: usize
+    ///         filter order (doubled by bp and br)
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (coefficients are always designed in double precision)
+    ///
+    #[pyo3(text_signature = "(fs: float, order: int = 1, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, order = None, dtype = "float64"))]
+    pub fn new(fs: f64, order: Option<usize>, dtype: &str) -> PyResult<Self> {
+        let order = order.unwrap_or(1);
+        Ok(Self { inner: Precision::new(dtype, || Multichannel::new(Butter::new(fs, order), 1), || Multichannel::new(Butter::new(fs, order), 1))? })
+    }
+
+    ///
+    /// GENERATE BUTTERWORTH FILTER COEFFICIENTS
+    /// cascaded second order sections (odd orders end with a first order
+    /// section, b2 = a2 = 0)
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = butterworth low pass filter IIR, -3 dB at fc
+    ///         hp = butterworth high pass filter IIR, -3 dB at fc
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2 (-3 dB edges)
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2 (-3 dB edges)
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode, order 0 or invalid parameters
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float, bw: float|None = None) -> list[float]")]
+    #[pyo3(signature = (mode, fc, bw = None))]
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: Option<f64>) -> PyResult<Vec<f64>> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc, bw)))?)
+    }
+});
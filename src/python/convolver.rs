@@ -0,0 +1,247 @@
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use numpy::PyArray1;
+use crate::convolver::Convolver;
+use crate::sample::Sample;
+use crate::multichannel::Multichannel;
+use crate::snapshot::Snapshot;
+use super::{Precision, Dtype, Frame, FrameMut, filt_frame_array, filt_frame_inplace_array, FreqzArrays, freqz_arrays, PyFilterState};
+
+#[pyclass(name = "Convolver")]
+pub struct PyConvolver {
+    inner: Precision<Multichannel<Convolver<f64>>, Multichannel<Convolver<f32>>>
+}
+
+// 1-D impulse response as f64 values
+fn impulse_response(frame: &Frame<'_>) -> PyResult<Vec<f64>> {
+    if frame.shape().len() != 1 {
+        return Err(PyValueError::new_err("impulse response must be a 1-D array"))
+    }
+    Ok(frame.to_samples())
+}
+
+#[pymethods]
+impl PyConvolver {
+    #[new]
+
+    ///
+    /// INIT CONVOLVER CLASS
+    /// uniformly partitioned overlap-save FFT convolution, for long FIR
+    /// filters and measured impulse responses. The output is delayed by
+    /// block_size samples
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate (used by freqz)
+    ///     impulse_response: numpy.ndarray
+    ///         1-D FIR taps or impulse response
+    ///     block_size: usize
+    ///         partition size and latency in samples, power of two (default 256)
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (the convolution is always computed in double precision)
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the impulse response is not 1-D
+    ///     FilterError (ValueError) on empty impulse response, block size
+    ///     not a power of two
+    ///
+    #[pyo3(text_signature = "(fs: float, impulse_response: numpy.ndarray, block_size: int = 256, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, impulse_response, block_size = 256, dtype = "float64"))]
+    pub fn new(fs: f64, impulse_response: Frame<'_>, block_size: usize, dtype: &str) -> PyResult<Self> {
+        let ir = self::impulse_response(&impulse_response)?;
+        let inner = match Dtype::parse(dtype)? {
+            Dtype::Float64 => Precision::Double(Multichannel::new(Convolver::new(fs, &ir, block_size)?, 1)),
+            Dtype::Float32 => Precision::Single(Multichannel::new(Convolver::new(fs, &ir, block_size)?, 1))
+        };
+        Ok(Self { inner })
+    }
+
+    ///
+    /// SET IMPULSE RESPONSE
+    /// (the state is kept when the number of partitions doesn't change)
+    ///
+    /// Args
+    /// ----
+    ///     impulse_response: numpy.ndarray
+    ///         1-D FIR taps or impulse response
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the impulse response is not 1-D
+    ///     FilterError (ValueError) on empty impulse response
+    ///
+    #[pyo3(text_signature = "(impulse_response: numpy.ndarray) -> None")]
+    pub fn set_impulse_response(&mut self, impulse_response: Frame<'_>) -> PyResult<()> {
+        let ir = self::impulse_response(&impulse_response)?;
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.set_impulse_response(&ir)))?)
+    }
+
+    ///
+    /// GET IMPULSE RESPONSE
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         float64 impulse response
+    ///
+    #[pyo3(text_signature = "() -> numpy.ndarray")]
+    pub fn get_impulse_response<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        dispatch!(&self.inner, f => PyArray1::from_slice(py, f.prototype().impulse_response()))
+    }
+
+    ///
+    /// LATENCY
+    ///
+    /// Return
+    /// ------
+    ///     int
+    ///         block_size, samples between input and output on top of the
+    ///         delay of the impulse response
+    ///
+    #[pyo3(text_signature = "() -> int")]
+    pub fn latency(&self) -> usize {
+        dispatch!(&self.inner, f => f.prototype().latency())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         filtered sample, block_size samples late
+    ///
+    ///
+    #[pyo3(text_signature = "(sample: float) -> float")]
+    pub fn filt_sample(&mut self, sample: f64) -> f64 {
+        dispatch!(&mut self.inner, f => f.channel_mut(0).filt_sample(Sample::from_f64(sample)).to_f64())
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, float64 or float32 (read without copy when it matches dtype),
+    ///         1-D (mono) or 2-D (one filter state per channel)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, layout = "planar"))]
+    pub fn filt_frame<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        dispatch!(&mut self.inner, f => filt_frame_array(py, f, &frame, layout))
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+    ///
+    /// Args
+    /// ----
+    ///     buffer: numpy.ndarray
+    ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the buffer dtype does not match the filter dtype,
+    ///     on unknown layout or more than 2 dimensions
+    ///
+    ///
+    #[pyo3(text_signature = "(buffer: numpy.ndarray, layout: str = 'planar') -> None")]
+    #[pyo3(signature = (buffer, layout = "planar"))]
+    pub fn filt_frame_inplace(&mut self, py: Python<'_>, buffer: FrameMut<'_>, layout: &str) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => filt_frame_inplace_array(py, f, buffer, layout))
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the impulse response (block latency not included)
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back). The design is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family, block size and number of partitions
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set the input history, the delay line spectra and the pending output
+    /// of every channel to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.reset());
+    }
+}
@@ -0,0 +1,292 @@
+use pyo3::prelude::*;
+use crate::dc::DcFilter;
+use crate::sample::Sample;
+use crate::multichannel::Multichannel;
+use crate::polezero::PoleZeroAnalysis;
+use crate::snapshot::Snapshot;
+use super::{Precision, Frame, FrameMut, filt_frame_array, filt_frame_inplace_array, FreqzArrays, freqz_arrays, PoleZeroArrays, pole_zero_arrays, PyFilterState};
+
+#[pyclass(name = "DcFilter")]
+pub struct PyDcFilter {
+    inner: Precision<Multichannel<DcFilter<f64>>, Multichannel<DcFilter<f32>>>
+}
+
+#[pymethods]
+impl PyDcFilter {
+    #[new]
+
+    ///
+    /// INIT DCFILTER CLASS
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate 
+    ///
+    pub fn new(fs: f64, dtype: &str) -> PyResult<Self> {
+        Ok(Self { inner: Precision::new(dtype, || Multichannel::new(DcFilter::new(fs), 1), || Multichannel::new(DcFilter::new(fs), 1))? })
+    }
+
+    ///
+    /// GENERATE DC FILTER COEFFICIENTS
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         filter type:
+    ///             dcj = dc block from J. O. Smith 
+    ///     fc: f64
+    ///         corner/cutoff frequency in Hz
+    /// 
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64):
+    ///         filter coefficients (b0, a1)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode or parameters
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float) -> tuple[float, float]")]
+    pub fn design_filter(&mut self, mode: &str, fc: f64) -> PyResult<(f64, f64)> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc)))?)
+    }
+
+    ///
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1)
+    ///
+    #[pyo3(text_signature = "(coeffs: tuple[float, float]) -> None")]
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64)) {
+        dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(coeffs)))
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple(f64, f64)
+    ///         stored filter coefficients
+    ///
+    #[pyo3(text_signature = "() -> tuple[float, float]")]
+    pub fn get_coeffs(&self) -> (f64, f64) {
+        dispatch!(&self.inner, f => f.prototype().coeffs())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         input sample
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         filtered sample
+    ///
+    ///
+    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float]|None = None) -> float")]
+    #[pyo3(signature = (sample, coeffs = None))]
+    pub fn filt_sample(&mut self, sample: f64, coeffs: Option<(f64, f64)>) -> f64 {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => f.channel_mut(0).filt_sample(Sample::from_f64(sample)).to_f64())
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, float64 or float32 (read without copy when it matches dtype),
+    ///         1-D (mono) or 2-D (one filter state per channel)
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, coeffs: tuple[float, float]|None = None, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, coeffs = None, layout = "planar"))]
+    pub fn filt_frame<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, coeffs: Option<(f64, f64)>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => filt_frame_array(py, f, &frame, layout))
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+    ///
+    /// Args
+    /// ----
+    ///     buffer: numpy.ndarray
+    ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the buffer dtype does not match the filter dtype,
+    ///     on unknown layout or more than 2 dimensions
+    ///
+    ///
+    #[pyo3(text_signature = "(buffer: numpy.ndarray, coeffs: tuple[float, float]|None = None, layout: str = 'planar') -> None")]
+    #[pyo3(signature = (buffer, coeffs = None, layout = "planar"))]
+    pub fn filt_frame_inplace(&mut self, py: Python<'_>, buffer: FrameMut<'_>, coeffs: Option<(f64, f64)>, layout: &str) -> PyResult<()> {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => filt_frame_inplace_array(py, f, buffer, layout))
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// POLES AND ZEROS
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, numpy.ndarray, float)
+    ///         zeros, poles (complex, z plane) and gain
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+    pub fn poles_zeros<'py>(&self, py: Python<'py>) -> PyResult<PoleZeroArrays<'py>> {
+        dispatch!(&self.inner, f => pole_zero_arrays(py, f.channel(0)))
+    }
+
+    ///
+    /// STABILITY
+    ///
+    /// Return
+    /// ------
+    ///     bool
+    ///         True if every pole lies strictly inside the unit circle
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> bool")]
+    pub fn is_stable(&self) -> PyResult<bool> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).is_stable())?)
+    }
+
+    ///
+    /// STABILITY MARGIN
+    ///
+    /// Return
+    /// ------
+    ///     float
+    ///         1 - max |pole|, distance of the outermost pole from the unit
+    ///         circle (negative or 0.0 if unstable)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn stability_margin(&self) -> PyResult<f64> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).stability_margin())?)
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back). The design is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family, order and topology
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set:
+    ///     y[n - 1] = 0.0
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.reset());
+    }
+
+
+
+}
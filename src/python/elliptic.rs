@@ -0,0 +1,64 @@
+use pyo3::prelude::*;
+use crate::elliptic::Elliptic;
+use crate::multichannel::Multichannel;
+use super::Precision;
+
+iir_filter_class!(PyElliptic, "Elliptic", Elliptic, {
+    #[new]
+
+    ///
+    /// INIT ELLIPTIC CLASS
+    /// 
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     order: usize
+    ///         order of the analog prototype (doubled by bp and br)
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (coefficients are always designed in double precision)
+    ///
+    #[pyo3(text_signature = "(fs: float, order: int = 1, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, order = None, dtype = "float64"))]
+    pub fn new(fs: f64, order: Option<usize>, dtype: &str) -> PyResult<Self> {
+        let order = order.unwrap_or(1);
+        Ok(Self { inner: Precision::new(dtype, || Multichannel::new(Elliptic::new(fs, order), 1), || Multichannel::new(Elliptic::new(fs, order), 1))? })
+    }
+
+    ///
+    /// GENERATE ELLIPTIC (CAUER) FILTER COEFFICIENTS
+    /// equiripple pass and stop bands, cascaded second order sections
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2
+    ///     fc: f64
+    ///         pass band edge (lp, hp) or center (bp, br) frequency in Hz
+    ///     ripple: f64
+    ///         pass band ripple in dB
+    ///     attenuation: f64
+    ///         minimum stop band attenuation in dB
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         [b0, b1, b2, a0, a1, a2] of every section, a0 = 1.0
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode, order 0 or invalid parameters,
+    ///     ripple at or above attenuation
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float, ripple: float, attenuation: float, bw: float|None = None) -> list[float]")]
+    #[pyo3(signature = (mode, fc, ripple, attenuation, bw = None))]
+    pub fn design_filter(&mut self, mode: &str, fc: f64, ripple: f64, attenuation: f64, bw: Option<f64>) -> PyResult<Vec<f64>> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc, ripple, attenuation, bw)))?)
+    }
+});
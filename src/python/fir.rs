@@ -0,0 +1,324 @@
+use pyo3::prelude::*;
+use crate::fir::Fir;
+use crate::filtertype::FirWindow;
+use crate::sample::Sample;
+use crate::multichannel::Multichannel;
+use crate::snapshot::Snapshot;
+use super::{Precision, Frame, FrameMut, filt_frame_array, filt_frame_inplace_array, FreqzArrays, freqz_arrays, PoleZeroArrays, pole_zero_arrays, PyFilterState};
+
+#[pyclass(name = "Fir")]
+pub struct PyFir {
+    inner: Precision<Multichannel<Fir<f64>>, Multichannel<Fir<f32>>>
+}
+
+#[pymethods]
+impl PyFir {
+    #[new]
+
+    ///
+    /// INIT FIR CLASS
+    /// (pass through until design_filter)
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     n_taps: usize
+    ///         number of taps, order + 1 (odd for hp and br)
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (taps are always designed in double precision)
+    ///
+    #[pyo3(text_signature = "(fs: float, n_taps: int = 101, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, n_taps = 101, dtype = "float64"))]
+    pub fn new(fs: f64, n_taps: usize, dtype: &str) -> PyResult<Self> {
+        Ok(Self { inner: Precision::new(dtype, || Multichannel::new(Fir::new(fs, n_taps), 1), || Multichannel::new(Fir::new(fs, n_taps), 1))? })
+    }
+
+    ///
+    /// GENERATE WINDOWED SINC FIR TAPS
+    /// linear phase, delay of (n_taps - 1) / 2 samples (as scipy.signal.firwin)
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         lp = low pass, -6 dB at fc
+    ///         hp = high pass, -6 dB at fc (odd number of taps)
+    ///         bp = band pass from fc - bw / 2 to fc + bw / 2
+    ///         br = band stop from fc - bw / 2 to fc + bw / 2 (odd number of taps)
+    ///     fc: f64
+    ///         cut off (lp, hp) or center (bp, br) frequency in Hz
+    ///     bw: Option<f64>
+    ///         band width in Hz (required only for bp and br)
+    ///     window: &str
+    ///         hann, hamming (default), blackman or kaiser
+    ///     attenuation: Option<f64>
+    ///         stop band attenuation in dB, sets the kaiser beta (required only for kaiser)
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         taps h[0], ..., h[n_taps - 1]
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode or window, even number of
+    ///     taps for hp and br or invalid parameters
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float, bw: float|None = None, window: str = 'hamming', attenuation: float|None = None) -> list[float]")]
+    #[pyo3(signature = (mode, fc, bw = None, window = "hamming", attenuation = None))]
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: Option<f64>, window: &str, attenuation: Option<f64>) -> PyResult<Vec<f64>> {
+        let window = FirWindow::from_name(window, attenuation)?;
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc, bw, window)))?)
+    }
+
+    ///
+    /// GENERATE EQUIRIPPLE FIR TAPS
+    /// Parks-McClellan (Remez exchange) design with the number of taps of the
+    /// filter, as scipy.signal.remez
+    ///
+    /// Args
+    /// ----
+    ///     bands: Vec<f64>
+    ///         band edges in Hz, [low, high] pairs increasing from 0 to fs / 2
+    ///     desired: Vec<f64>
+    ///         gain of every band (slope for the differentiator: gain desired * f / fs)
+    ///     weights: Option<Vec<f64>>
+    ///         relative weight of the error in every band (default 1.0)
+    ///     mode: &str
+    ///         bandpass = piecewise constant gains, multiband (default)
+    ///         differentiator = 90 degrees phase lead, gain rising with frequency
+    ///         hilbert = 90 degrees phase lag (-j) over the bands
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         taps h[0], ..., h[n_taps - 1]
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode, bands, gains or weights,
+    ///     too few taps or when the exchange does not converge
+    ///
+    #[pyo3(text_signature = "(bands: list[float], desired: list[float], weights: list[float]|None = None, mode: str = 'bandpass') -> list[float]")]
+    #[pyo3(signature = (bands, desired, weights = None, mode = "bandpass"))]
+    pub fn design_remez(&mut self, bands: Vec<f64>, desired: Vec<f64>, weights: Option<Vec<f64>>, mode: &str) -> PyResult<Vec<f64>> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_remez(mode, &bands, &desired, weights.as_deref())))?)
+    }
+
+    ///
+    /// SET EXTERNAL FILTER TAPS
+    /// override the stored design
+    ///
+    /// Args
+    /// ----
+    ///     taps: Vec<f64>
+    ///         h[0], ..., h[n_taps - 1]
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the number of taps does not match
+    ///
+    #[pyo3(text_signature = "(taps: list[float]) -> None")]
+    pub fn set_taps(&mut self, taps: Vec<f64>) -> PyResult<()> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.set_taps(&taps)))?)
+    }
+
+    ///
+    /// GET FILTER TAPS
+    ///
+    /// Return
+    /// ------
+    ///     Vec<f64>
+    ///         stored taps h[0], ..., h[n_taps - 1]
+    ///
+    #[pyo3(text_signature = "() -> list[float]")]
+    pub fn get_taps(&self) -> Vec<f64> {
+        dispatch!(&self.inner, f => f.prototype().taps())
+    }
+
+    ///
+    /// GROUP DELAY
+    ///
+    /// Return
+    /// ------
+    ///     float
+    ///         (n_taps - 1) / 2 samples, delay of the linear phase designs
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn delay(&self) -> f64 {
+        dispatch!(&self.inner, f => f.prototype().delay())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         input sample
+    ///     taps: Vec<f64>
+    ///         filter taps from fn design_filter (optional, override the stored design)
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         filtered sample
+    ///
+    ///
+    #[pyo3(text_signature = "(sample: float, taps: list[float]|None = None) -> float")]
+    #[pyo3(signature = (sample, taps = None))]
+    pub fn filt_sample(&mut self, sample: f64, taps: Option<Vec<f64>>) -> PyResult<f64> {
+        if let Some(c) = taps {
+            dispatch!(&mut self.inner, f => f.design(|filter| filter.set_taps(&c)))?;
+        }
+        Ok(dispatch!(&mut self.inner, f => f.channel_mut(0).filt_sample(Sample::from_f64(sample)).to_f64()))
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, float64 or float32 (read without copy when it matches dtype),
+    ///         1-D (mono) or 2-D (one filter state per channel)
+    ///     taps: Vec<f64>
+    ///         filter taps from design_filter (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, taps: list[float]|None = None, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, taps = None, layout = "planar"))]
+    pub fn filt_frame<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, taps: Option<Vec<f64>>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(c) = taps {
+            dispatch!(&mut self.inner, f => f.design(|filter| filter.set_taps(&c)))?;
+        }
+        dispatch!(&mut self.inner, f => filt_frame_array(py, f, &frame, layout))
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+    ///
+    /// Args
+    /// ----
+    ///     buffer: numpy.ndarray
+    ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+    ///     taps: Vec<f64>
+    ///         filter taps from design_filter (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the buffer dtype does not match the filter dtype,
+    ///     on unknown layout or more than 2 dimensions
+    ///
+    ///
+    #[pyo3(text_signature = "(buffer: numpy.ndarray, taps: list[float]|None = None, layout: str = 'planar') -> None")]
+    #[pyo3(signature = (buffer, taps = None, layout = "planar"))]
+    pub fn filt_frame_inplace(&mut self, py: Python<'_>, buffer: FrameMut<'_>, taps: Option<Vec<f64>>, layout: &str) -> PyResult<()> {
+        if let Some(c) = taps {
+            dispatch!(&mut self.inner, f => f.design(|filter| filter.set_taps(&c)))?;
+        }
+        dispatch!(&mut self.inner, f => filt_frame_inplace_array(py, f, buffer, layout))
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the stored design
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// POLES AND ZEROS
+    /// of the stored design (all poles at the origin)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, numpy.ndarray, float)
+    ///         zeros, poles (complex, z plane) and gain
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+    pub fn poles_zeros<'py>(&self, py: Python<'py>) -> PyResult<PoleZeroArrays<'py>> {
+        dispatch!(&self.inner, f => pole_zero_arrays(py, f.channel(0)))
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back). The design is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family and number of taps
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set x[n - 1], ..., x[n - n_taps + 1] to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.reset());
+    }
+}
@@ -0,0 +1,362 @@
+///
+/// IIR FILTER CLASS
+/// python class of an IirFilter family (Butter, Chebyshev1, Chebyshev2,
+/// Elliptic, Bessel): the family gives the constructor and design_filter,
+/// every other method is shared
+///
+macro_rules! iir_filter_class {
+    ($py:ident, $name:tt, $filter:ident, { $($family_methods:tt)* }) => {
+        #[::pyo3::pyclass(name = $name)]
+        pub struct $py {
+            inner: $crate::python::Precision<$crate::multichannel::Multichannel<$filter<f64>>, $crate::multichannel::Multichannel<$filter<f32>>>
+        }
+
+        #[::pyo3::pymethods]
+        impl $py {
+            $($family_methods)*
+
+            ///
+            /// SET EXTERNAL FILTER COEFFICIENTS
+            /// override the stored design
+            ///
+            /// Args
+            /// ----
+            ///     coeffs: Vec<f64>
+            ///         filter coefficients from design_filter
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) if the number of coefficients does not match the mode
+            ///
+            #[pyo3(text_signature = "(coeffs: list[float]) -> None")]
+            pub fn set_coeffs(&mut self, coeffs: Vec<f64>) -> ::pyo3::PyResult<()> {
+                Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.set_coeffs(&coeffs)))?)
+            }
+
+            ///
+            /// GET FILTER COEFFICIENTS
+            ///
+            /// Return
+            /// ------
+            ///     Vec<f64>
+            ///         stored filter coefficients
+            ///
+            #[pyo3(text_signature = "() -> list[float]")]
+            pub fn get_coeffs(&self) -> Vec<f64> {
+                dispatch!(&self.inner, f => f.prototype().coeffs())
+            }
+
+            ///
+            /// EXPORT SECOND ORDER SECTIONS
+            ///
+            /// Return
+            /// ------
+            ///     numpy.ndarray
+            ///         (K, 6) float64 array of rows [b0, b1, b2, a0 = 1, a1, a2],
+            ///         the layout of scipy.signal sos (e.g. for sosfilt)
+            ///
+            #[pyo3(text_signature = "() -> numpy.ndarray")]
+            pub fn sos<'py>(&self, py: ::pyo3::Python<'py>) -> ::pyo3::PyResult<::pyo3::Bound<'py, ::numpy::PyArray2<f64>>> {
+                $crate::python::sos_array(py, dispatch!(&self.inner, f => $crate::sos::SecondOrderSections::sos(f.prototype())))
+            }
+
+            ///
+            /// APPLY FILTER SAMPLE BY SAMPLE
+            ///
+            /// Args
+            /// ----
+            ///     sample: f64
+            ///         input sample
+            ///     coeffs: Vec<f64>
+            ///         filter coefficients from fn design_filter (optional, override the stored design)
+            ///
+            /// Return
+            /// ------
+            ///     f64
+            ///         filtered sample
+            ///
+            ///
+            #[pyo3(text_signature = "(sample: float, coeffs: list[float]|None = None) -> float")]
+            #[pyo3(signature = (sample, coeffs = None))]
+            pub fn filt_sample(&mut self, sample: f64, coeffs: Option<Vec<f64>>) -> ::pyo3::PyResult<f64> {
+                if let Some(c) = coeffs {
+                    dispatch!(&mut self.inner, f => f.design(|filter| filter.set_coeffs(&c)))?;
+                }
+                Ok(dispatch!(&mut self.inner, f => $crate::sample::Sample::to_f64(f.channel_mut(0).filt_sample($crate::sample::Sample::from_f64(sample)))))
+            }
+
+            ///
+            /// APPLY FILTER ON FRAME OR SIGNAL
+            ///
+            /// Args
+            /// ----
+            ///     frame: numpy.ndarray
+            ///         input frame, float64 or float32 (read without copy when it matches dtype),
+            ///         1-D (mono) or 2-D (one filter state per channel)
+            ///     coeffs: Vec<f64>
+            ///         filter coefficients from design_filter (optional, override the stored design)
+            ///     layout: &str
+            ///         channel layout of 2-D frames:
+            ///             planar = channels x samples (default)
+            ///             interleaved = samples x channels
+            ///
+            /// Return
+            /// ------
+            ///     numpy.ndarray
+            ///         filtered frame (filter dtype, computed with the GIL released)
+            ///
+            ///
+            #[pyo3(text_signature = "(frame: numpy.ndarray, coeffs: list[float]|None = None, layout: str = 'planar') -> numpy.ndarray")]
+            #[pyo3(signature = (frame, coeffs = None, layout = "planar"))]
+            pub fn filt_frame<'py>(&mut self, py: ::pyo3::Python<'py>, frame: $crate::python::Frame<'py>, coeffs: Option<Vec<f64>>, layout: &str) -> ::pyo3::PyResult<::pyo3::Bound<'py, ::pyo3::PyAny>> {
+                if let Some(c) = coeffs {
+                    dispatch!(&mut self.inner, f => f.design(|filter| filter.set_coeffs(&c)))?;
+                }
+                dispatch!(&mut self.inner, f => $crate::python::filt_frame_array(py, f, &frame, layout))
+            }
+
+            ///
+            /// APPLY FILTER IN PLACE
+            /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+            ///
+            /// Args
+            /// ----
+            ///     buffer: numpy.ndarray
+            ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+            ///     coeffs: Vec<f64>
+            ///         filter coefficients from design_filter (optional, override the stored design)
+            ///     layout: &str
+            ///         channel layout of 2-D frames:
+            ///             planar = channels x samples (default)
+            ///             interleaved = samples x channels
+            ///
+            /// Raise
+            /// -----
+            ///     ValueError if the buffer dtype does not match the filter dtype,
+            ///     on unknown layout or more than 2 dimensions
+            ///
+            ///
+            #[pyo3(text_signature = "(buffer: numpy.ndarray, coeffs: list[float]|None = None, layout: str = 'planar') -> None")]
+            #[pyo3(signature = (buffer, coeffs = None, layout = "planar"))]
+            pub fn filt_frame_inplace(&mut self, py: ::pyo3::Python<'_>, buffer: $crate::python::FrameMut<'_>, coeffs: Option<Vec<f64>>, layout: &str) -> ::pyo3::PyResult<()> {
+                if let Some(c) = coeffs {
+                    dispatch!(&mut self.inner, f => f.design(|filter| filter.set_coeffs(&c)))?;
+                }
+                dispatch!(&mut self.inner, f => $crate::python::filt_frame_inplace_array(py, f, buffer, layout))
+            }
+
+            ///
+            /// ZERO PHASE FILTERING
+            /// forward-backward filtering of the stored design with padded ends and
+            /// steady state initial conditions (as scipy.signal.sosfiltfilt), the
+            /// filter state is neither used nor changed
+            ///
+            /// Args
+            /// ----
+            ///     frame: numpy.ndarray
+            ///         input frame, 1-D or 2-D (every channel filtered on its own)
+            ///     padtype: &str
+            ///         odd (default), even, constant or none
+            ///     padlen: Option<usize>
+            ///         samples added at each end (default 3 times the number of taps)
+            ///     layout: &str
+            ///         channel layout of 2-D frames:
+            ///             planar = channels x samples (default)
+            ///             interleaved = samples x channels
+            ///
+            /// Return
+            /// ------
+            ///     numpy.ndarray
+            ///         filtered frame (filter dtype, computed with the GIL released)
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) on unknown padtype, frame not longer than padlen
+            ///
+            #[pyo3(text_signature = "(frame: numpy.ndarray, padtype: str = 'odd', padlen: int|None = None, layout: str = 'planar') -> numpy.ndarray")]
+            #[pyo3(signature = (frame, padtype = "odd", padlen = None, layout = "planar"))]
+            pub fn filtfilt<'py>(&self, py: ::pyo3::Python<'py>, frame: $crate::python::Frame<'py>, padtype: &str, padlen: Option<usize>, layout: &str) -> ::pyo3::PyResult<::pyo3::Bound<'py, ::pyo3::PyAny>> {
+                dispatch!(&self.inner, f => $crate::python::filtfilt_array(py, f.prototype(), &frame, padtype, padlen, layout))
+            }
+
+            ///
+            /// FREQUENCY RESPONSE
+            /// of the stored design (cascaded sections included)
+            ///
+            /// Args
+            /// ----
+            ///     frequencies: numpy.ndarray
+            ///         frequencies in Hz (optional, default grid of n_points)
+            ///     n_points: usize
+            ///         size of the default grid (default 512)
+            ///     log: bool
+            ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+            ///     f_min: f64
+            ///         lowest frequency of the log grid in Hz (default 20.0)
+            ///
+            /// Return
+            /// ------
+            ///     tuple(numpy.ndarray, ...)
+            ///         frequencies, complex response, magnitude in dB, unwrapped phase
+            ///         in radians, group delay in samples
+            ///
+            #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+            #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+            pub fn freqz<'py>(&self, py: ::pyo3::Python<'py>, frequencies: Option<$crate::python::Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> $crate::python::FreqzArrays<'py> {
+                dispatch!(&self.inner, f => $crate::python::freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+            }
+
+            ///
+            /// POLES AND ZEROS
+            /// of the stored design (cascaded sections included)
+            ///
+            /// Return
+            /// ------
+            ///     tuple(numpy.ndarray, numpy.ndarray, float)
+            ///         zeros, poles (complex, z plane) and gain
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) if the root search does not converge
+            ///
+            #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+            pub fn poles_zeros<'py>(&self, py: ::pyo3::Python<'py>) -> ::pyo3::PyResult<$crate::python::PoleZeroArrays<'py>> {
+                dispatch!(&self.inner, f => $crate::python::pole_zero_arrays(py, f.channel(0)))
+            }
+
+            ///
+            /// STABILITY
+            ///
+            /// Return
+            /// ------
+            ///     bool
+            ///         True if every pole lies strictly inside the unit circle
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) if the root search does not converge
+            ///
+            #[pyo3(text_signature = "() -> bool")]
+            pub fn is_stable(&self) -> ::pyo3::PyResult<bool> {
+                Ok(dispatch!(&self.inner, f => $crate::polezero::PoleZeroAnalysis::is_stable(f.channel(0)))?)
+            }
+
+            ///
+            /// STABILITY MARGIN
+            ///
+            /// Return
+            /// ------
+            ///     float
+            ///         1 - max |pole|, distance of the outermost pole from the unit
+            ///         circle (negative or 0.0 if unstable)
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) if the root search does not converge
+            ///
+            #[pyo3(text_signature = "() -> float")]
+            pub fn stability_margin(&self) -> ::pyo3::PyResult<f64> {
+                Ok(dispatch!(&self.inner, f => $crate::polezero::PoleZeroAnalysis::stability_margin(f.channel(0)))?)
+            }
+
+            ///
+            /// SET FILTER STATE
+            /// arbitrary initial state, the same on every channel
+            ///
+            /// Args
+            /// ----
+            ///     state: numpy.ndarray
+            ///         (sections, 2) state, transposed direct form II [s1, s2] of every section
+            ///
+            /// Raise
+            /// -----
+            ///     ValueError on a wrong shape
+            ///     FilterError (ValueError) if the number of rows is not the number of sections
+            ///
+            #[pyo3(text_signature = "(state: numpy.ndarray) -> None")]
+            pub fn set_state(&mut self, state: $crate::python::StateInput<'_, 2>) -> ::pyo3::PyResult<()> {
+                dispatch!(&mut self.inner, f => {
+                    let state = state.rows()?;
+                    Ok($crate::python::set_channel_states(f, |filter| filter.set_state(&state))?)
+                })
+            }
+
+            ///
+            /// STEP RESPONSE STEADY STATE
+            /// state of the current design once a unit step has settled
+            /// (as scipy.signal.sosfilt_zi), in the layout of set_state
+            ///
+            /// Return
+            /// ------
+            ///     numpy.ndarray
+            ///         (sections, 2) state for a unit input, scale it by the input level
+            ///
+            #[pyo3(text_signature = "() -> numpy.ndarray")]
+            pub fn step_state<'py>(&self, py: ::pyo3::Python<'py>) -> ::pyo3::PyResult<::pyo3::Bound<'py, ::numpy::PyArray2<f64>>> {
+                dispatch!(&self.inner, f => $crate::python::state_array(py, &f.channel(0).step_state()))
+            }
+
+            ///
+            /// START IN STEADY STATE
+            /// set the state reached by a constant input, so that a signal starting
+            /// at that level (DC offset, mid-file chunk) has no startup transient
+            ///
+            /// Args
+            /// ----
+            ///     x0: float | list[float]
+            ///         input level of every channel, or one level per channel (sets
+            ///         the number of channels), usually the first samples of the signal
+            ///
+            /// Raise
+            /// -----
+            ///     ValueError on an empty sequence
+            ///
+            #[pyo3(text_signature = "(x0: float|list[float]) -> None")]
+            pub fn set_steady_state(&mut self, x0: $crate::python::Levels) -> ::pyo3::PyResult<()> {
+                dispatch!(&mut self.inner, f => $crate::python::set_channel_levels(f, x0, |filter, level| filter.set_steady_state(level)))
+            }
+
+            ///
+            /// STATE SNAPSHOT
+            /// delayed samples of every channel, to be restored later (seek, render
+            /// ahead, roll back). The design is not part of the state
+            ///
+            /// Return
+            /// ------
+            ///     FilterState
+            ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+            ///
+            #[pyo3(text_signature = "() -> FilterState")]
+            pub fn snapshot(&self) -> $crate::python::PyFilterState {
+                dispatch!(&self.inner, f => $crate::python::PyFilterState::from($crate::snapshot::Snapshot::snapshot(f)))
+            }
+
+            ///
+            /// RESTORE STATE SNAPSHOT
+            /// (sets the number of channels of the snapshot)
+            ///
+            /// Args
+            /// ----
+            ///     state: FilterState
+            ///         snapshot of a filter of the same family, order and topology
+            ///
+            /// Raise
+            /// -----
+            ///     FilterError (ValueError) if the state does not match the filter,
+            ///     the filter is unchanged
+            ///
+            #[pyo3(text_signature = "(state: FilterState) -> None")]
+            pub fn restore(&mut self, state: ::pyo3::PyRef<'_, $crate::python::PyFilterState>) -> ::pyo3::PyResult<()> {
+                dispatch!(&mut self.inner, f => Ok($crate::snapshot::Snapshot::restore(f, &state.inner)?))
+            }
+
+            ///
+            /// CLEAR DELAYED SAMPLES CACHE
+            /// set the two state variables of every section to zero
+            ///
+            pub fn clear_delayed_samples_cache(&mut self) {
+                dispatch!(&mut self.inner, f => f.reset());
+            }
+        }
+    };
+}
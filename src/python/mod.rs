@@ -0,0 +1,564 @@
+///
+/// DISPATCH ON FILTER PRECISION
+/// run the same body on the f64 or f32 filter held by a Precision
+///
+macro_rules! dispatch {
+    ($inner:expr, $f:ident => $body:expr) => {
+        match $inner {
+            $crate::python::Precision::Double($f) => $body,
+            $crate::python::Precision::Single($f) => $body
+        }
+    };
+}
+
+mod biquadeq;
+mod twozerotwopole;
+mod onepole;
+mod dc;
+mod harmonic;
+mod narrow;
+mod zavalishin;
+#[macro_use]
+mod iir;
+mod butter;
+mod bessel;
+mod chebyshev;
+mod elliptic;
+mod sos;
+mod fir;
+mod convolver;
+mod state;
+
+use std::borrow::Cow;
+use pyo3::prelude::*;
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use numpy::{Element, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArrayDyn, PyReadwriteArrayDyn, PyUntypedArrayMethods};
+use crate::sample::Sample;
+use crate::filter::Filter;
+use crate::multichannel::Multichannel;
+use crate::freqz::{FrequencyResponse, linear_grid, log_grid};
+use crate::polezero::PoleZeroAnalysis;
+use crate::sos::SecondOrderSections;
+use crate::filtfilt::sosfiltfilt;
+use crate::filtertype::PadType;
+use num_complex::Complex64;
+use biquadeq::PyBiquad;
+use twozerotwopole::PyTwoZeroTwoPole;
+use onepole::PyOnePole;
+use dc::PyDcFilter;
+use harmonic::PyHarmonic;
+use narrow::PyNarrow;
+use zavalishin::PyZavalishin;
+use butter::PyButter;
+use bessel::PyBessel;
+use chebyshev::{PyChebyshev1, PyChebyshev2};
+use elliptic::PyElliptic;
+use sos::PySos;
+use fir::PyFir;
+use convolver::PyConvolver;
+use state::PyFilterState;
+
+
+create_exception!(rustlibfilt, FilterError, PyValueError, "Invalid filter design or parameters.");
+
+impl From<crate::error::FilterError> for PyErr {
+    fn from(err: crate::error::FilterError) -> PyErr {
+        FilterError::new_err(err.to_string())
+    }
+}
+
+///
+/// PROCESSING PRECISION
+/// parsed from the `dtype` argument of the python constructors
+///
+pub(crate) enum Dtype {
+    Float64,
+    Float32
+}
+
+impl Dtype {
+    pub(crate) fn parse(dtype: &str) -> PyResult<Self> {
+        match dtype {
+            "float64" | "f64" | "double" => Ok(Dtype::Float64),
+            "float32" | "f32" | "single" => Ok(Dtype::Float32),
+            _ => Err(PyValueError::new_err(format!("dtype must be `float64` or `float32`, got `{}`", dtype)))
+        }
+    }
+}
+
+///
+/// FILTER IN DOUBLE OR SINGLE PRECISION
+/// wrapped by every python class, see dispatch!
+///
+pub(crate) enum Precision<D, S> {
+    Double(D),
+    Single(S)
+}
+
+impl<D, S> Precision<D, S> {
+    pub(crate) fn new(dtype: &str, double: impl FnOnce() -> D, single: impl FnOnce() -> S) -> PyResult<Self> {
+        Ok(match Dtype::parse(dtype)? {
+            Dtype::Float64 => Precision::Double(double()),
+            Dtype::Float32 => Precision::Single(single())
+        })
+    }
+}
+
+///
+/// INPUT FRAME
+/// 1-D or 2-D float64 or float32 numpy array (read without copy), or any sequence of floats
+///
+#[derive(FromPyObject)]
+pub enum Frame<'py> {
+    Double(PyReadonlyArrayDyn<'py, f64>),
+    Single(PyReadonlyArrayDyn<'py, f32>),
+    List(Vec<f64>)
+}
+
+///
+/// IN-PLACE BUFFER
+/// writable 1-D or 2-D float64 or float32 numpy array
+///
+#[derive(FromPyObject)]
+pub enum FrameMut<'py> {
+    Double(PyReadwriteArrayDyn<'py, f64>),
+    Single(PyReadwriteArrayDyn<'py, f32>)
+}
+
+///
+/// CHANNEL LAYOUT
+/// of 2-D frames: planar = channels x samples, interleaved = samples x channels
+///
+#[derive(Clone, Copy)]
+enum Layout {
+    Mono,
+    Planar(usize),
+    Interleaved(usize)
+}
+
+impl Layout {
+    fn parse(layout: &str, shape: &[usize]) -> PyResult<Self> {
+        match (shape, layout) {
+            ([_], _) => Ok(Layout::Mono),
+            ([channels, _], "planar") => Ok(Layout::Planar(*channels)),
+            ([_, channels], "interleaved") => Ok(Layout::Interleaved(*channels)),
+            ([_, _], _) => Err(PyValueError::new_err(format!("layout must be `planar` or `interleaved`, got `{}`", layout))),
+            _ => Err(PyValueError::new_err(format!("frame must be 1-D or 2-D, got {}-D", shape.len())))
+        }
+    }
+
+    fn process<T: Sample, F: Filter<T> + Clone>(self, filter: &mut Multichannel<F>, input: &[T], output: &mut [T]) -> crate::error::Result<()> {
+        match self {
+            Layout::Mono => {
+                filter.channel_mut(0).process_block(input, output);
+                Ok(())
+            },
+            Layout::Planar(_) => filter.process_planar(input, output),
+            Layout::Interleaved(_) => filter.process_interleaved(input, output)
+        }
+    }
+
+    fn process_inplace<T: Sample, F: Filter<T> + Clone>(self, filter: &mut Multichannel<F>, buffer: &mut [T]) -> crate::error::Result<()> {
+        match self {
+            Layout::Mono => {
+                filter.channel_mut(0).process_inplace(buffer);
+                Ok(())
+            },
+            Layout::Planar(_) => filter.process_planar_inplace(buffer),
+            Layout::Interleaved(_) => filter.process_interleaved_inplace(buffer)
+        }
+    }
+
+    // 2-D frames set the number of channels, 1-D frames run on channel 0
+    fn set_channels<F: Clone>(self, filter: &mut Multichannel<F>) {
+        if let Layout::Planar(channels) | Layout::Interleaved(channels) = self {
+            filter.set_channels(channels)
+        }
+    }
+}
+
+///
+/// NUMPY SAMPLE
+/// processing precision exchanged with numpy without conversion
+///
+pub(crate) trait PySample: Sample + Element {
+    fn borrow<'a>(frame: &'a Frame<'_>) -> Option<&'a [Self]>;
+    fn borrow_mut<'a, 'py>(buffer: &'a mut FrameMut<'py>) -> Option<&'a mut PyReadwriteArrayDyn<'py, Self>>;
+}
+
+impl PySample for f64 {
+    fn borrow<'a>(frame: &'a Frame<'_>) -> Option<&'a [Self]> {
+        match frame {
+            Frame::Double(array) => array.as_slice().ok(),
+            Frame::List(values) => Some(values),
+            _ => None
+        }
+    }
+
+    fn borrow_mut<'a, 'py>(buffer: &'a mut FrameMut<'py>) -> Option<&'a mut PyReadwriteArrayDyn<'py, Self>> {
+        match buffer {
+            FrameMut::Double(array) => Some(array),
+            _ => None
+        }
+    }
+}
+
+impl PySample for f32 {
+    fn borrow<'a>(frame: &'a Frame<'_>) -> Option<&'a [Self]> {
+        match frame {
+            Frame::Single(array) => array.as_slice().ok(),
+            _ => None
+        }
+    }
+
+    fn borrow_mut<'a, 'py>(buffer: &'a mut FrameMut<'py>) -> Option<&'a mut PyReadwriteArrayDyn<'py, Self>> {
+        match buffer {
+            FrameMut::Single(array) => Some(array),
+            _ => None
+        }
+    }
+}
+
+impl Frame<'_> {
+    fn shape(&self) -> Vec<usize> {
+        match self {
+            Frame::Double(array) => array.shape().to_vec(),
+            Frame::Single(array) => array.shape().to_vec(),
+            Frame::List(values) => vec![values.len()]
+        }
+    }
+
+    // values converted to T in row-major order, used when the frame can't be borrowed as &[T]
+    fn to_samples<T: Sample>(&self) -> Vec<T> {
+        match self {
+            Frame::Double(array) => array.as_array().iter().map(|&x| T::from_f64(x)).collect(),
+            Frame::Single(array) => array.as_array().iter().map(|&x| T::from_f64(x as f64)).collect(),
+            Frame::List(values) => values.iter().map(|&x| T::from_f64(x)).collect()
+        }
+    }
+}
+
+///
+/// FILTER FRAME INTO A NEW NUMPY ARRAY
+/// the input is read in place when its dtype matches the filter precision
+/// and it is contiguous, the output array is created from the filtered Vec
+/// without copy and has the filter dtype and the input shape. 2-D frames are
+/// filtered with one state per channel. The GIL is released while filtering
+///
+/// Raise
+/// -----
+///     ValueError on unknown layout or frames with more than 2 dimensions
+///
+pub(crate) fn filt_frame_array<'py, T: PySample, F: Filter<T> + Clone + Send>(py: Python<'py>, filter: &mut Multichannel<F>, frame: &Frame<'py>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+    let shape = frame.shape();
+    let layout = Layout::parse(layout, &shape)?;
+    layout.set_channels(filter);
+
+    let mut output = vec![T::zero(); shape.iter().product()];
+    match T::borrow(frame) {
+        Some(input) => py.detach(|| layout.process(filter, input, &mut output))?,
+        None => {
+            let input = frame.to_samples::<T>();
+            py.detach(|| layout.process(filter, &input, &mut output))?
+        }
+    }
+    Ok(PyArray1::from_vec(py, output).reshape(shape)?.into_any())
+}
+
+///
+/// FILTER NUMPY BUFFER IN PLACE
+/// 1-D or 2-D, the GIL is released while filtering
+///
+/// Raise
+/// -----
+///     ValueError if the buffer dtype does not match the filter precision,
+///     on unknown layout or buffers with more than 2 dimensions
+///
+pub(crate) fn filt_frame_inplace_array<T: PySample, F: Filter<T> + Clone + Send>(py: Python<'_>, filter: &mut Multichannel<F>, mut buffer: FrameMut<'_>, layout: &str) -> PyResult<()> {
+    let array = T::borrow_mut(&mut buffer)
+        .ok_or_else(|| PyValueError::new_err("buffer dtype must match the filter dtype"))?;
+    let layout = Layout::parse(layout, array.shape())?;
+    layout.set_channels(filter);
+
+    let mut view = array.as_array_mut();
+    match view.as_slice_mut() {
+        Some(samples) => py.detach(|| layout.process_inplace(filter, samples))?,
+        None => {
+            // strided view: filter a row-major copy and write it back
+            let mut samples: Vec<T> = view.iter().copied().collect();
+            py.detach(|| layout.process_inplace(filter, &mut samples))?;
+            view.iter_mut().zip(samples).for_each(|(x, y)| *x = y);
+        }
+    }
+    Ok(())
+}
+
+///
+/// FILTER FRAME WITH PER-SAMPLE PARAMETERS INTO A NEW NUMPY ARRAY
+/// 1-D frame and parameter arrays (read without copy when float64 and
+/// contiguous), the GIL is released while filtering
+///
+/// Raise
+/// -----
+///     ValueError if the frame is not 1-D
+///     FilterError (ValueError) if the process fails (no design, mismatched lengths)
+///
+pub(crate) fn filt_frame_modulated_array<'py, T: PySample, F: Send>(
+    py: Python<'py>,
+    filter: &mut F,
+    frame: &Frame<'py>,
+    fc: &Frame<'py>,
+    q: Option<&Frame<'py>>,
+    process: impl FnOnce(&mut F, &[T], &[f64], Option<&[f64]>, &mut [T]) -> crate::error::Result<()> + Send
+) -> PyResult<Bound<'py, PyAny>> {
+    let shape = frame.shape();
+    if shape.len() != 1 {
+        return Err(PyValueError::new_err(format!("modulated frame must be 1-D, got {}-D", shape.len())))
+    }
+
+    let input: Cow<[T]> = T::borrow(frame).map_or_else(|| Cow::Owned(frame.to_samples()), Cow::Borrowed);
+    let fc: Cow<[f64]> = f64::borrow(fc).map_or_else(|| Cow::Owned(fc.to_samples()), Cow::Borrowed);
+    let q: Option<Cow<[f64]>> = q.map(|q| f64::borrow(q).map_or_else(|| Cow::Owned(q.to_samples()), Cow::Borrowed));
+
+    let mut output = vec![T::zero(); input.len()];
+    py.detach(|| process(filter, &input, &fc, q.as_deref(), &mut output))?;
+    Ok(PyArray1::from_vec(py, output).into_any())
+}
+
+
+///
+/// ZERO PHASE FILTERING INTO A NEW NUMPY ARRAY
+/// forward-backward filtering of the stored design (the filter state is not
+/// used), every channel of a 2-D frame on its own. The GIL is released
+/// while filtering
+///
+/// Raise
+/// -----
+///     ValueError on unknown layout or frames with more than 2 dimensions
+///     FilterError (ValueError) on unknown padtype, signal not longer than padlen
+///
+pub(crate) fn filtfilt_array<'py, T: PySample, F: Filter<T> + SecondOrderSections>(py: Python<'py>, filter: &F, frame: &Frame<'py>, padtype: &str, padlen: Option<usize>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+    let padtype = PadType::from_name(padtype)?;
+    let shape = frame.shape();
+    let layout = Layout::parse(layout, &shape)?;
+    let sos = filter.sos();
+    let input: Cow<[T]> = T::borrow(frame).map_or_else(|| Cow::Owned(frame.to_samples()), Cow::Borrowed);
+
+    let output = py.detach(|| -> crate::error::Result<Vec<T>> {
+        match layout {
+            Layout::Mono => sosfiltfilt(&sos, &input, padtype, padlen),
+            Layout::Planar(_) => {
+                let mut output = Vec::with_capacity(input.len());
+                for channel in input.chunks(shape[1].max(1)) {
+                    output.extend(sosfiltfilt(&sos, channel, padtype, padlen)?);
+                }
+                Ok(output)
+            },
+            Layout::Interleaved(channels) => {
+                let mut output = vec![T::zero(); input.len()];
+                for c in 0..channels {
+                    let channel: Vec<T> = input.iter().skip(c).step_by(channels).copied().collect();
+                    let filtered = sosfiltfilt(&sos, &channel, padtype, padlen)?;
+                    output.iter_mut().skip(c).step_by(channels).zip(filtered).for_each(|(y, value)| *y = value);
+                }
+                Ok(output)
+            }
+        }
+    })?;
+    Ok(PyArray1::from_vec(py, output).reshape(shape)?.into_any())
+}
+
+///
+/// FREQUENCY RESPONSE ARRAYS
+/// (frequencies, complex response, magnitude in dB, unwrapped phase, group delay in samples)
+/// on the given frequencies, or on n_points frequencies from 0.0 (linear) or
+/// f_min (log) up to fs / 2
+///
+pub(crate) type FreqzArrays<'py> = (Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<Complex64>>, Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<f64>>, Bound<'py, PyArray1<f64>>);
+
+pub(crate) fn freqz_arrays<'py>(py: Python<'py>, filter: &impl FrequencyResponse, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+    let fs = filter.sampling_rate();
+    let frequencies = match frequencies {
+        Some(frequencies) => frequencies.to_samples(),
+        None if log => log_grid(f_min, fs / 2.0, n_points),
+        None => linear_grid(fs, n_points)
+    };
+    let response = filter.freqz(&frequencies);
+    (
+        PyArray1::from_vec(py, response.frequencies.clone()),
+        PyArray1::from_slice(py, &response.response),
+        PyArray1::from_vec(py, response.magnitude_db()),
+        PyArray1::from_vec(py, response.phase()),
+        PyArray1::from_vec(py, response.group_delay)
+    )
+}
+
+///
+/// POLE/ZERO ARRAYS
+/// (zeros, poles, gain) of the stored design
+///
+pub(crate) type PoleZeroArrays<'py> = (Bound<'py, PyArray1<Complex64>>, Bound<'py, PyArray1<Complex64>>, f64);
+
+pub(crate) fn pole_zero_arrays<'py>(py: Python<'py>, filter: &impl FrequencyResponse) -> PyResult<PoleZeroArrays<'py>> {
+    let pz = filter.pole_zero()?;
+    Ok((PyArray1::from_vec(py, pz.zeros), PyArray1::from_vec(py, pz.poles), pz.gain))
+}
+
+///
+/// SOS INPUT
+/// (K, 6) numpy array (or a single row of 6) or sequence of rows
+/// [b0, b1, b2, a0, a1, a2], as scipy.signal sos
+///
+#[derive(FromPyObject)]
+pub enum SosInput<'py> {
+    Array(PyReadonlyArrayDyn<'py, f64>),
+    Rows(Vec<[f64; 6]>)
+}
+
+impl SosInput<'_> {
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the array shape is not (K, 6) or (6,)
+    ///
+    pub(crate) fn rows(&self) -> PyResult<Vec<[f64; 6]>> {
+        match self {
+            SosInput::Array(array) => match array.shape() {
+                [_, 6] | [6] => {
+                    let values: Vec<f64> = array.as_array().iter().copied().collect();
+                    Ok(values.chunks(6).map(|c| [c[0], c[1], c[2], c[3], c[4], c[5]]).collect())
+                },
+                shape => Err(PyValueError::new_err(format!("sos must have shape (K, 6), got {:?}", shape)))
+            },
+            SosInput::Rows(rows) => Ok(rows.clone())
+        }
+    }
+}
+
+///
+/// SOS ARRAY
+/// (K, 6) float64 numpy array from the section rows
+///
+pub(crate) fn sos_array<'py>(py: Python<'py>, sos: Vec<[f64; 6]>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let n_sections = sos.len();
+    PyArray1::from_vec(py, sos.concat()).reshape([n_sections, 6])
+}
+
+///
+/// STATE INPUT
+/// (K, N) numpy array (or a single row of N) or sequence of rows, one row
+/// per section in the layout of the filter set_state
+///
+#[derive(FromPyObject)]
+pub enum StateInput<'py, const N: usize> {
+    Array(PyReadonlyArrayDyn<'py, f64>),
+    Rows(Vec<[f64; N]>)
+}
+
+impl<const N: usize> StateInput<'_, N> {
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the array shape is not (K, N) or (N,)
+    ///
+    pub(crate) fn rows<T: Sample>(&self) -> PyResult<Vec<[T; N]>> {
+        let rows = match self {
+            StateInput::Array(array) => match array.shape() {
+                [_, n] | [n] if *n == N => {
+                    let values: Vec<f64> = array.as_array().iter().copied().collect();
+                    values.chunks(N).map(|c| core::array::from_fn(|i| c[i])).collect()
+                },
+                shape => return Err(PyValueError::new_err(format!("state must have shape (K, {}), got {:?}", N, shape)))
+            },
+            StateInput::Rows(rows) => rows.clone()
+        };
+        Ok(rows.iter().map(|row| row.map(T::from_f64)).collect())
+    }
+
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the state is not a single row
+    ///
+    pub(crate) fn row<T: Sample>(&self) -> PyResult<[T; N]> {
+        match self.rows()?[..] {
+            [row] => Ok(row),
+            ref rows => Err(PyValueError::new_err(format!("state must have shape (1, {}), got {} rows", N, rows.len())))
+        }
+    }
+}
+
+///
+/// STATE ARRAY
+/// (K, N) float64 numpy array from the state rows
+///
+pub(crate) fn state_array<'py, T: Sample, const N: usize>(py: Python<'py>, state: &[[T; N]]) -> PyResult<Bound<'py, PyArray2<f64>>> {
+    let values: Vec<f64> = state.iter().flat_map(|row| row.map(T::to_f64)).collect();
+    PyArray1::from_vec(py, values).reshape([state.len(), N])
+}
+
+///
+/// SET STATE OF EVERY CHANNEL
+/// (the prototype keeps a cleared state for new channels)
+///
+pub(crate) fn set_channel_states<F: Clone, E>(filter: &mut Multichannel<F>, mut op: impl FnMut(&mut F) -> Result<(), E>) -> Result<(), E> {
+    (0..filter.n_channels()).try_for_each(|index| op(filter.channel_mut(index)))
+}
+
+///
+/// INPUT LEVELS
+/// a single level for every channel or a sequence of one level per channel
+///
+#[derive(FromPyObject)]
+pub enum Levels {
+    Scalar(f64),
+    Channels(Vec<f64>)
+}
+
+///
+/// SET STEADY STATE OF EVERY CHANNEL
+/// a scalar level is used on every channel, a sequence sets the number of
+/// channels and the level of each one (e.g. the first samples of a block)
+///
+/// Raise
+/// -----
+///     ValueError on an empty sequence
+///
+pub(crate) fn set_channel_levels<T: Sample, F: Clone>(filter: &mut Multichannel<F>, levels: Levels, mut op: impl FnMut(&mut F, T)) -> PyResult<()> {
+    let levels = match levels {
+        Levels::Scalar(level) => vec![level; filter.n_channels()],
+        Levels::Channels(levels) if levels.is_empty() => return Err(PyValueError::new_err("levels must not be empty")),
+        Levels::Channels(levels) => {
+            filter.set_channels(levels.len());
+            levels
+        }
+    };
+    for (index, level) in levels.into_iter().enumerate() {
+        op(filter.channel_mut(index), T::from_f64(level));
+    }
+    Ok(())
+}
+
+/// A Python module implemented in Rust.
+#[pymodule]
+fn rustlibfilt(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("FilterError", m.py().get_type::<FilterError>())?;
+    m.add_class::<PyBiquad>()?;
+    m.add_class::<PyTwoZeroTwoPole>()?;
+    m.add_class::<PyOnePole>()?;
+    m.add_class::<PyDcFilter>()?;
+    m.add_class::<PyHarmonic>()?;
+    m.add_class::<PyNarrow>()?;
+    m.add_class::<PyZavalishin>()?;
+    m.add_class::<PyButter>()?;
+    m.add_class::<PyBessel>()?;
+    m.add_class::<PyChebyshev1>()?;
+    m.add_class::<PyChebyshev2>()?;
+    m.add_class::<PyElliptic>()?;
+    m.add_class::<PySos>()?;
+    m.add_class::<PyFir>()?;
+    m.add_class::<PyConvolver>()?;
+    m.add_class::<PyFilterState>()?;
+    Ok(())
+}
@@ -0,0 +1,490 @@
+use pyo3::prelude::*;
+use crate::onepole::OnePole;
+use crate::sample::Sample;
+use crate::smoothing::DEFAULT_CONTROL_INTERVAL;
+use crate::multichannel::Multichannel;
+use crate::polezero::PoleZeroAnalysis;
+use numpy::PyArray2;
+use crate::snapshot::Snapshot;
+use super::{Precision, Dtype, Frame, FrameMut, filt_frame_array, filt_frame_inplace_array, filt_frame_modulated_array, FreqzArrays, freqz_arrays, PoleZeroArrays, pole_zero_arrays, filtfilt_array, StateInput, state_array, set_channel_states, Levels, set_channel_levels, PyFilterState};
+
+#[pyclass(name = "OnePole")]
+pub struct PyOnePole {
+    inner: Precision<Multichannel<OnePole<f64>>, Multichannel<OnePole<f32>>>
+}
+
+#[pymethods]
+impl PyOnePole {
+    #[new]
+
+    ///
+    /// INIT ONEPOLE CLASS
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     order: usize
+    ///         filter order
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (coefficients are always designed in double precision)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if order is not in 1..=16
+    ///
+    #[pyo3(text_signature = "(fs: float, order: int = 1, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, order = None, dtype = "float64"))]
+    pub fn new(fs: f64, order: Option<usize>, dtype: &str) -> PyResult<Self> {
+        let order = order.unwrap_or(1);
+        let inner = match Dtype::parse(dtype)? {
+            Dtype::Float64 => Precision::Double(Multichannel::new(OnePole::new(fs, order)?, 1)),
+            Dtype::Float32 => Precision::Single(Multichannel::new(OnePole::new(fs, order)?, 1))
+        };
+        Ok(Self { inner })
+    }
+
+    ///
+    /// GENERATE ONE POLE FILTER COEFFICIENTS
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         filter type:
+    ///             lp = low pass
+    ///             hp = high pass
+    ///     fc: f64
+    ///         corner/cutoff frequency in Hz
+    ///     fs: f64
+    ///         sampling rate in Hz
+    /// 
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64):
+    ///         filter coefficients (b0, a1)
+    /// 
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode or parameters
+    ///
+    #[pyo3(text_signature = "(mode: str, fc: float) -> tuple[float, float, float]")]
+    pub fn design_filter(&mut self, mode: &str, fc: f64) -> PyResult<(f64, f64, f64)> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.design_filter(mode, fc)))?)
+    }
+
+    ///
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: tuple(f64, f64, f64)
+    ///         filter coefficients (b0, b1, a1)
+    ///
+    #[pyo3(text_signature = "(coeffs: tuple[float, float, float]) -> None")]
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64)) {
+        dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(coeffs)))
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple(f64, f64, f64)
+    ///         filter coefficients of channel 0 (current glide position)
+    ///
+    #[pyo3(text_signature = "() -> tuple[float, float, float]")]
+    pub fn get_coeffs(&self) -> (f64, f64, f64) {
+        dispatch!(&self.inner, f => f.channel(0).coeffs())
+    }
+
+    ///
+    /// SET GLIDE TIME
+    /// used by set_target: fc glides over `time`, the coefficients are
+    /// designed every `control_interval` samples and interpolated per sample
+    ///
+    /// Args
+    /// ----
+    ///     time: f64
+    ///         glide time in sec. (0.0 = no smoothing, default)
+    ///     control_interval: usize
+    ///         samples between two coefficient designs (default 32)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on negative time
+    ///
+    #[pyo3(text_signature = "(time: float, control_interval: int = 32) -> None")]
+    #[pyo3(signature = (time, control_interval = DEFAULT_CONTROL_INTERVAL))]
+    pub fn set_glide(&mut self, time: f64, control_interval: usize) -> PyResult<()> {
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.set_glide(time, control_interval)))?)
+    }
+
+    ///
+    /// GLIDE TO A NEW CUTOFF
+    /// (same mode as the last design_filter)
+    ///
+    /// Args
+    /// ----
+    ///     fc: f64
+    ///         target corner/cutoff frequency in Hz
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if no design was set or on invalid fc
+    ///
+    #[pyo3(text_signature = "(fc: float) -> None")]
+    pub fn set_target(&mut self, fc: f64) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => {
+            f.design(|filter| filter.set_target(fc))?;
+            // the prototype never processes samples: channels added later start at the targets
+            f.prototype_mut().skip_glide();
+        });
+        Ok(())
+    }
+
+    ///
+    /// GET CURRENT CUTOFF
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         fc reached by the glide
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn get_fc(&self) -> f64 {
+        dispatch!(&self.inner, f => f.channel(0).fc())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         input sample
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         filtered sample
+    ///
+    ///
+    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float, float]|None = None) -> float")]
+    #[pyo3(signature = (sample, coeffs = None))]
+    pub fn filt_sample(&mut self, sample: f64, coeffs: Option<(f64, f64, f64)>) -> f64 {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => f.channel_mut(0).filt_sample(Sample::from_f64(sample)).to_f64())
+    }
+
+    ///
+    /// APPLY FILTER WITH AUDIO-RATE MODULATION
+    /// coefficients recomputed at every sample from the parameter arrays
+    /// (mono: first channel)
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         1-D input frame, float64 or float32
+    ///     fc: numpy.ndarray
+    ///         cutoff frequency in Hz per sample (same length as frame)
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if no design was set or on mismatched lengths
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, fc: numpy.ndarray) -> numpy.ndarray")]
+    pub fn filt_frame_modulated<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, fc: Frame<'py>) -> PyResult<Bound<'py, PyAny>> {
+        dispatch!(&mut self.inner, f => filt_frame_modulated_array(py, f.channel_mut(0), &frame, &fc, None, |filter, x, fc, _, y| filter.filt_modulated(x, fc, y)))
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, float64 or float32 (read without copy when it matches dtype),
+    ///         1-D (mono) or 2-D (one filter state per channel)
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, coeffs: tuple[float, float, float]|None = None, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, coeffs = None, layout = "planar"))]
+    pub fn filt_frame<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, coeffs: Option<(f64, f64, f64)>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => filt_frame_array(py, f, &frame, layout))
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+    ///
+    /// Args
+    /// ----
+    ///     buffer: numpy.ndarray
+    ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+    ///     coeffs: tuple(f64, f64)
+    ///         filter coefficients (b0, a1) (optional, override the stored design)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the buffer dtype does not match the filter dtype,
+    ///     on unknown layout or more than 2 dimensions
+    ///
+    ///
+    #[pyo3(text_signature = "(buffer: numpy.ndarray, coeffs: tuple[float, float, float]|None = None, layout: str = 'planar') -> None")]
+    #[pyo3(signature = (buffer, coeffs = None, layout = "planar"))]
+    pub fn filt_frame_inplace(&mut self, py: Python<'_>, buffer: FrameMut<'_>, coeffs: Option<(f64, f64, f64)>, layout: &str) -> PyResult<()> {
+        if let Some(c) = coeffs {
+            dispatch!(&mut self.inner, f => f.for_each(|filter| filter.set_coeffs(c)));
+        }
+        dispatch!(&mut self.inner, f => filt_frame_inplace_array(py, f, buffer, layout))
+    }
+
+    ///
+    /// ZERO PHASE FILTERING
+    /// forward-backward filtering of the stored design with padded ends and
+    /// steady state initial conditions (as scipy.signal.sosfiltfilt), the
+    /// filter state is neither used nor changed
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, 1-D or 2-D (every channel filtered on its own)
+    ///     padtype: &str
+    ///         odd (default), even, constant or none
+    ///     padlen: Option<usize>
+    ///         samples added at each end (default 3 times the number of taps)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on unknown padtype, frame not longer than padlen
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, padtype: str = 'odd', padlen: int|None = None, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, padtype = "odd", padlen = None, layout = "planar"))]
+    pub fn filtfilt<'py>(&self, py: Python<'py>, frame: Frame<'py>, padtype: &str, padlen: Option<usize>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        dispatch!(&self.inner, f => filtfilt_array(py, f.prototype(), &frame, padtype, padlen, layout))
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// POLES AND ZEROS
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, numpy.ndarray, float)
+    ///         zeros, poles (complex, z plane) and gain
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+    pub fn poles_zeros<'py>(&self, py: Python<'py>) -> PyResult<PoleZeroArrays<'py>> {
+        dispatch!(&self.inner, f => pole_zero_arrays(py, f.channel(0)))
+    }
+
+    ///
+    /// STABILITY
+    ///
+    /// Return
+    /// ------
+    ///     bool
+    ///         True if every pole lies strictly inside the unit circle
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> bool")]
+    pub fn is_stable(&self) -> PyResult<bool> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).is_stable())?)
+    }
+
+    ///
+    /// STABILITY MARGIN
+    ///
+    /// Return
+    /// ------
+    ///     float
+    ///         1 - max |pole|, distance of the outermost pole from the unit
+    ///         circle (negative or 0.0 if unstable)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn stability_margin(&self) -> PyResult<f64> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).stability_margin())?)
+    }
+
+    ///
+    /// SET FILTER STATE
+    /// arbitrary initial state, the same on every channel
+    ///
+    /// Args
+    /// ----
+    ///     state: numpy.ndarray
+    ///         (order, 2) state, [x[n - 1], y[n - 1]] of every cascaded section
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError on a wrong shape
+    ///     FilterError (ValueError) if the number of rows is not the order
+    ///
+    #[pyo3(text_signature = "(state: numpy.ndarray) -> None")]
+    pub fn set_state(&mut self, state: StateInput<'_, 2>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => {
+            let state = state.rows()?;
+            Ok(set_channel_states(f, |filter| filter.set_state(&state))?)
+        })
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the current design once a unit step has settled
+    /// (as scipy.signal.lfilter_zi), in the layout of set_state
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         (order, 2) state for a unit input, scale it by the input level
+    ///
+    #[pyo3(text_signature = "() -> numpy.ndarray")]
+    pub fn step_state<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        dispatch!(&self.inner, f => state_array(py, &f.channel(0).step_state()))
+    }
+
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input, so that a signal starting
+    /// at that level (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: float | list[float]
+    ///         input level of every channel, or one level per channel (sets
+    ///         the number of channels), usually the first samples of the signal
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError on an empty sequence
+    ///
+    #[pyo3(text_signature = "(x0: float|list[float]) -> None")]
+    pub fn set_steady_state(&mut self, x0: Levels) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => set_channel_levels(f, x0, |filter, level| filter.set_steady_state(level)))
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back), with the running glide and the coefficients it
+    /// reached. The design mode is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family, order and topology
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set:
+    ///     x[n - 1] = 0.0
+    ///     y[n - 1] = 0.0
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.reset());
+    }
+
+
+
+}
@@ -0,0 +1,311 @@
+use pyo3::prelude::*;
+use numpy::PyArray2;
+use crate::sos::{Sos, SecondOrderSections};
+use crate::sample::Sample;
+use crate::multichannel::Multichannel;
+use crate::polezero::PoleZeroAnalysis;
+use crate::snapshot::Snapshot;
+use super::{Precision, Dtype, Frame, FrameMut, filt_frame_array, filt_frame_inplace_array, FreqzArrays, freqz_arrays, PoleZeroArrays, pole_zero_arrays, SosInput, sos_array, filtfilt_array, PyFilterState};
+
+#[pyclass(name = "Sos")]
+pub struct PySos {
+    inner: Precision<Multichannel<Sos<f64>>, Multichannel<Sos<f32>>>
+}
+
+#[pymethods]
+impl PySos {
+    #[new]
+
+    ///
+    /// INIT SOS CLASS
+    /// cascade of second order sections, e.g. designed with scipy.signal
+    /// (output="sos") or exported by sos() of another filter
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate (used by freqz)
+    ///     sos: numpy.ndarray
+    ///         (K, 6) array of rows [b0, b1, b2, a0, a1, a2]
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if sos is not (K, 6)
+    ///     FilterError (ValueError) on invalid fs, a0 = 0 or non finite coefficients
+    ///
+    #[pyo3(text_signature = "(fs: float, sos: numpy.ndarray, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, sos, dtype = "float64"))]
+    pub fn new(fs: f64, sos: SosInput<'_>, dtype: &str) -> PyResult<Self> {
+        let rows = sos.rows()?;
+        let inner = match Dtype::parse(dtype)? {
+            Dtype::Float64 => Precision::Double(Multichannel::new(Sos::new(fs, &rows)?, 1)),
+            Dtype::Float32 => Precision::Single(Multichannel::new(Sos::new(fs, &rows)?, 1))
+        };
+        Ok(Self { inner })
+    }
+
+    ///
+    /// SET SECTIONS
+    /// (the state is kept when the number of sections doesn't change)
+    ///
+    /// Args
+    /// ----
+    ///     sos: numpy.ndarray
+    ///         (K, 6) array of rows [b0, b1, b2, a0, a1, a2]
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if sos is not (K, 6)
+    ///     FilterError (ValueError) on a0 = 0 or non finite coefficients
+    ///
+    #[pyo3(text_signature = "(sos: numpy.ndarray) -> None")]
+    pub fn set_sos(&mut self, sos: SosInput<'_>) -> PyResult<()> {
+        let rows = sos.rows()?;
+        Ok(dispatch!(&mut self.inner, f => f.design(|filter| filter.set_sos(&rows)))?)
+    }
+
+    ///
+    /// EXPORT SECOND ORDER SECTIONS
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         (K, 6) float64 array of rows [b0, b1, b2, a0 = 1, a1, a2]
+    ///
+    #[pyo3(text_signature = "() -> numpy.ndarray")]
+    pub fn sos<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<f64>>> {
+        sos_array(py, dispatch!(&self.inner, f => f.prototype().sos()))
+    }
+
+    ///
+    /// NUMBER OF SECTIONS
+    ///
+    #[pyo3(text_signature = "() -> int")]
+    pub fn n_sections(&self) -> usize {
+        dispatch!(&self.inner, f => f.prototype().n_sections())
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         filtered sample
+    ///
+    #[pyo3(text_signature = "(sample: float) -> float")]
+    pub fn filt_sample(&mut self, sample: f64) -> f64 {
+        dispatch!(&mut self.inner, f => f.channel_mut(0).filt_sample(Sample::from_f64(sample)).to_f64())
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, float64 or float32 (read without copy when it matches dtype),
+    ///         1-D (mono) or 2-D (one filter state per channel)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, layout = "planar"))]
+    pub fn filt_frame<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        dispatch!(&mut self.inner, f => filt_frame_array(py, f, &frame, layout))
+    }
+
+    ///
+    /// APPLY FILTER IN PLACE
+    /// (writes the filtered frame into the caller buffer, no allocation, GIL released)
+    ///
+    /// Args
+    /// ----
+    ///     buffer: numpy.ndarray
+    ///         writable input frame, with the same dtype as the filter, 1-D or 2-D
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Raise
+    /// -----
+    ///     ValueError if the buffer dtype does not match the filter dtype,
+    ///     on unknown layout or more than 2 dimensions
+    ///
+    #[pyo3(text_signature = "(buffer: numpy.ndarray, layout: str = 'planar') -> None")]
+    #[pyo3(signature = (buffer, layout = "planar"))]
+    pub fn filt_frame_inplace(&mut self, py: Python<'_>, buffer: FrameMut<'_>, layout: &str) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => filt_frame_inplace_array(py, f, buffer, layout))
+    }
+
+    ///
+    /// ZERO PHASE FILTERING
+    /// forward-backward filtering of the stored design with padded ends and
+    /// steady state initial conditions (as scipy.signal.sosfiltfilt), the
+    /// filter state is neither used nor changed
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         input frame, 1-D or 2-D (every channel filtered on its own)
+    ///     padtype: &str
+    ///         odd (default), even, constant or none
+    ///     padlen: Option<usize>
+    ///         samples added at each end (default 3 times the number of taps)
+    ///     layout: &str
+    ///         channel layout of 2-D frames:
+    ///             planar = channels x samples (default)
+    ///             interleaved = samples x channels
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on unknown padtype, frame not longer than padlen
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, padtype: str = 'odd', padlen: int|None = None, layout: str = 'planar') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, padtype = "odd", padlen = None, layout = "planar"))]
+    pub fn filtfilt<'py>(&self, py: Python<'py>, frame: Frame<'py>, padtype: &str, padlen: Option<usize>, layout: &str) -> PyResult<Bound<'py, PyAny>> {
+        dispatch!(&self.inner, f => filtfilt_array(py, f.prototype(), &frame, padtype, padlen, layout))
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f.channel(0), frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// POLES AND ZEROS
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, numpy.ndarray, float)
+    ///         zeros, poles (complex, z plane) and gain
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+    pub fn poles_zeros<'py>(&self, py: Python<'py>) -> PyResult<PoleZeroArrays<'py>> {
+        dispatch!(&self.inner, f => pole_zero_arrays(py, f.channel(0)))
+    }
+
+    ///
+    /// STABILITY
+    ///
+    /// Return
+    /// ------
+    ///     bool
+    ///         True if every pole lies strictly inside the unit circle
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> bool")]
+    pub fn is_stable(&self) -> PyResult<bool> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).is_stable())?)
+    }
+
+    ///
+    /// STABILITY MARGIN
+    ///
+    /// Return
+    /// ------
+    ///     float
+    ///         1 - max |pole|, distance of the outermost pole from the unit
+    ///         circle (negative or 0.0 if unstable)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn stability_margin(&self) -> PyResult<f64> {
+        Ok(dispatch!(&self.inner, f => f.channel(0).stability_margin())?)
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back). The design is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family, order and topology
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set the two state variables of every section to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.reset());
+    }
+}
@@ -0,0 +1,99 @@
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyType};
+use numpy::PyArray1;
+use crate::snapshot::FilterState;
+
+#[pyclass(name = "FilterState", module = "rustlibfilt", eq)]
+#[derive(Clone, PartialEq)]
+pub struct PyFilterState {
+    pub(crate) inner: FilterState
+}
+
+impl From<FilterState> for PyFilterState {
+    fn from(inner: FilterState) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyFilterState {
+
+    #[new]
+
+    ///
+    /// INIT FILTER STATE
+    /// (usually returned by snapshot, picklable)
+    ///
+    /// Args
+    /// ----
+    ///     family: str
+    ///         filter family the state belongs to
+    ///     values: list[float]
+    ///         state values, in the order written by the filter
+    ///
+    #[pyo3(text_signature = "(family: str, values: list[float]) -> None")]
+    pub fn new(family: &str, values: Vec<f64>) -> Self {
+        Self { inner: FilterState::new(family, values) }
+    }
+
+    ///
+    /// FILTER FAMILY
+    ///
+    #[getter]
+    pub fn family(&self) -> &str {
+        self.inner.family()
+    }
+
+    ///
+    /// STATE VALUES
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         float64 copy of the values
+    ///
+    #[pyo3(text_signature = "() -> numpy.ndarray")]
+    pub fn values<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<f64>> {
+        PyArray1::from_slice(py, self.inner.values())
+    }
+
+    ///
+    /// ENCODE STATE
+    /// family length (u32), family (utf-8) and values (f64), little endian
+    ///
+    #[pyo3(text_signature = "() -> bytes")]
+    pub fn to_bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+
+    ///
+    /// DECODE STATE
+    ///
+    /// Args
+    /// ----
+    ///     data: bytes
+    ///         state encoded by to_bytes
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on malformed bytes
+    ///
+    #[staticmethod]
+    #[pyo3(text_signature = "(data: bytes) -> FilterState")]
+    pub fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self { inner: FilterState::from_bytes(data)? })
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("FilterState(family='{}', {} values)", self.inner.family(), self.inner.len())
+    }
+
+    pub fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyType>, (String, Vec<f64>)) {
+        let state = slf.borrow();
+        (slf.get_type(), (state.inner.family().to_string(), state.inner.values().to_vec()))
+    }
+}
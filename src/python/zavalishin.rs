@@ -0,0 +1,239 @@
+use pyo3::prelude::*;
+use crate::zavalishin::{Zavalishin, ZavalishinOutput};
+use crate::sample::Sample;
+use crate::snapshot::Snapshot;
+use super::{Precision, Frame, filt_frame_modulated_array, FreqzArrays, freqz_arrays, PoleZeroArrays, pole_zero_arrays, PyFilterState};
+use crate::polezero::PoleZeroAnalysis;
+
+#[pyclass(name = "Zavalishin")]
+pub struct PyZavalishin {
+    inner: Precision<Zavalishin<f64>, Zavalishin<f32>>
+}
+
+#[pymethods]
+impl PyZavalishin {
+    #[new]
+
+    ///
+    /// INIT ZAVALISHIN CLASS
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate
+    ///     dtype: &str
+    ///         processing precision, "float64" (default) or "float32"
+    ///         (coefficients are always designed in double precision)
+    ///
+    #[pyo3(text_signature = "(fs: float, dtype: str = 'float64') -> None")]
+    #[pyo3(signature = (fs, dtype = "float64"))]
+    pub fn new(fs: f64, dtype: &str) -> PyResult<Self> {
+        Ok(Self { inner: Precision::new(dtype, || Zavalishin::new(fs), || Zavalishin::new(fs))? })
+    }
+    
+    ///
+    /// DESIGN FILTER
+    ///
+    /// Args
+    /// ----
+    ///     mode: &str
+    ///         filt mode:
+    ///             - zdf = zero delay feedback one pole filter (generate low pass, high pass, allpass)
+    ///             - naive = naive one pole filter (generate low pass, high pass)
+    ///             - trap = trapezoidal integration (generate low pass, high pass)
+    ///             - svf = state variable (generate low pass, high pass, band pass, band reject)
+    ///     fc: f64
+    ///         cut-off frequency in Hz
+    ///     fc_spread: Option<f64>
+    ///         frequency spread in Hz (fc + fc_spread = fhigh for SVF)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) on invalid mode or parameters
+    ///
+    #[pyo3(text_signature = "(fc: float, fc_spread: float|None) -> None")]
+    pub fn design_filter(&mut self, mode: &str, fc: f64, fc_spread: Option<f64>) -> PyResult<()> {
+        Ok(dispatch!(&mut self.inner, f => f.design_filter(mode, fc, fc_spread))?)
+    }
+    
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: f64
+    ///         sample in
+    ///
+    /// Return
+    /// ------
+    ///     (f64, f64, f64, f64, f64, f64) -> (low_pass, high_pass, allpass, band pass, band reject)
+    ///     
+    ///
+    #[pyo3(text_signature = "(sample: float) -> tuple[float, float, float, float, float]")]
+    pub fn filt_sample(&mut self, sample: f64) -> (f64, f64, f64, f64, f64) {
+        dispatch!(&mut self.inner, f => {
+            let (lp, hp, ap, bp, br) = f.filt_sample(Sample::from_f64(sample));
+            (lp.to_f64(), hp.to_f64(), ap.to_f64(), bp.to_f64(), br.to_f64())
+        })
+    }
+
+    ///
+    /// APPLY FILTER WITH AUDIO-RATE MODULATION
+    /// coefficients recomputed at every sample from the parameter arrays
+    /// (cheap tan approximation), fc is the center frequency in svf mode
+    ///
+    /// Args
+    /// ----
+    ///     frame: numpy.ndarray
+    ///         1-D input frame, float64 or float32
+    ///     fc: numpy.ndarray
+    ///         cutoff frequency in Hz per sample (same length as frame)
+    ///     q: numpy.ndarray
+    ///         resonance per sample, svf mode only (optional, keep the designed damping)
+    ///     output: &str
+    ///         returned output: lp (default), hp, ap, bp or br
+    ///
+    /// Return
+    /// ------
+    ///     numpy.ndarray
+    ///         filtered frame (filter dtype, computed with the GIL released)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if no design was set or on mismatched lengths or unknown output
+    ///
+    #[pyo3(text_signature = "(frame: numpy.ndarray, fc: numpy.ndarray, q: numpy.ndarray|None = None, output: str = 'lp') -> numpy.ndarray")]
+    #[pyo3(signature = (frame, fc, q = None, output = "lp"))]
+    pub fn filt_frame_modulated<'py>(&mut self, py: Python<'py>, frame: Frame<'py>, fc: Frame<'py>, q: Option<Frame<'py>>, output: &str) -> PyResult<Bound<'py, PyAny>> {
+        let output = ZavalishinOutput::from_name(output)?;
+        dispatch!(&mut self.inner, f => {
+            f.set_output(output);
+            filt_frame_modulated_array(py, f, &frame, &fc, q.as_ref(), |filter, x, fc, q, y| filter.filt_modulated(x, fc, q, y))
+        })
+    }
+
+    ///
+    /// FREQUENCY RESPONSE
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Args
+    /// ----
+    ///     frequencies: numpy.ndarray
+    ///         frequencies in Hz (optional, default grid of n_points)
+    ///     n_points: usize
+    ///         size of the default grid (default 512)
+    ///     log: bool
+    ///         log spaced grid from f_min to fs / 2 (default False: linear from 0.0)
+    ///     f_min: f64
+    ///         lowest frequency of the log grid in Hz (default 20.0)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, ...)
+    ///         frequencies, complex response, magnitude in dB, unwrapped phase
+    ///         in radians, group delay in samples
+    ///
+    #[pyo3(text_signature = "(frequencies: numpy.ndarray|None = None, n_points: int = 512, log: bool = False, f_min: float = 20.0) -> tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]")]
+    #[pyo3(signature = (frequencies = None, n_points = 512, log = false, f_min = 20.0))]
+    pub fn freqz<'py>(&self, py: Python<'py>, frequencies: Option<Frame<'py>>, n_points: usize, log: bool, f_min: f64) -> FreqzArrays<'py> {
+        dispatch!(&self.inner, f => freqz_arrays(py, f, frequencies, n_points, log, f_min))
+    }
+
+    ///
+    /// POLES AND ZEROS
+    /// of the stored design (cascaded sections included)
+    ///
+    /// Return
+    /// ------
+    ///     tuple(numpy.ndarray, numpy.ndarray, float)
+    ///         zeros, poles (complex, z plane) and gain
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> tuple[numpy.ndarray, numpy.ndarray, float]")]
+    pub fn poles_zeros<'py>(&self, py: Python<'py>) -> PyResult<PoleZeroArrays<'py>> {
+        dispatch!(&self.inner, f => pole_zero_arrays(py, f))
+    }
+
+    ///
+    /// STABILITY
+    ///
+    /// Return
+    /// ------
+    ///     bool
+    ///         True if every pole lies strictly inside the unit circle
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> bool")]
+    pub fn is_stable(&self) -> PyResult<bool> {
+        Ok(dispatch!(&self.inner, f => f.is_stable())?)
+    }
+
+    ///
+    /// STABILITY MARGIN
+    ///
+    /// Return
+    /// ------
+    ///     float
+    ///         1 - max |pole|, distance of the outermost pole from the unit
+    ///         circle (negative or 0.0 if unstable)
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the root search does not converge
+    ///
+    #[pyo3(text_signature = "() -> float")]
+    pub fn stability_margin(&self) -> PyResult<f64> {
+        Ok(dispatch!(&self.inner, f => f.stability_margin())?)
+    }
+
+    ///
+    /// STATE SNAPSHOT
+    /// delayed samples of every channel, to be restored later (seek, render
+    /// ahead, roll back). The design is not part of the state
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         picklable state (to_bytes/from_bytes to store it elsewhere)
+    ///
+    #[pyo3(text_signature = "() -> FilterState")]
+    pub fn snapshot(&self) -> PyFilterState {
+        dispatch!(&self.inner, f => PyFilterState::from(f.snapshot()))
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (sets the number of channels of the snapshot)
+    ///
+    /// Args
+    /// ----
+    ///     state: FilterState
+    ///         snapshot of a filter of the same family, order and topology
+    ///
+    /// Raise
+    /// -----
+    ///     FilterError (ValueError) if the state does not match the filter,
+    ///     the filter is unchanged
+    ///
+    #[pyo3(text_signature = "(state: FilterState) -> None")]
+    pub fn restore(&mut self, state: PyRef<'_, PyFilterState>) -> PyResult<()> {
+        dispatch!(&mut self.inner, f => Ok(f.restore(&state.inner)?))
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set:
+    ///     z_sample = 0.0
+    ///     s_sample = 0.0
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        dispatch!(&mut self.inner, f => f.clear_delayed_samples_cache())
+    }
+
+}
@@ -0,0 +1,262 @@
+use super::{sample::Sample, filtertype::BiquadTopology, error::{Result, FilterError, ModeName}};
+
+impl BiquadTopology {
+    ///
+    /// PARSE TOPOLOGY NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         df1 = direct form I
+    ///         df2 = direct form II
+    ///         tdf2 = transposed direct form II
+    ///         lattice = normalized lattice-ladder
+    ///         ss = state-space (normal form for complex poles)
+    ///
+    /// Return
+    /// ------
+    ///     Result<BiquadTopology>
+    ///         FilterError on unknown name
+    ///
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "df1" => Ok(BiquadTopology::DirectForm1),
+            "df2" => Ok(BiquadTopology::DirectForm2),
+            "tdf2" => Ok(BiquadTopology::TransposedDirectForm2),
+            "lattice" => Ok(BiquadTopology::Lattice),
+            "ss" | "statespace" => Ok(BiquadTopology::StateSpace),
+            _ => Err(FilterError::UnknownTopology(ModeName::new(name)))
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kernel<T: Sample> {
+    // b0, b1, b2, a1, a2 normalized by a0 (df1, df2, tdf2)
+    Direct([T; 5]),
+    // reflection k, forward scale p, backward scale q (per stage), ladder taps v
+    Lattice { k: [T; 2], p: [T; 2], q: [T; 2], v: [T; 3] },
+    // x[n + 1] = A x[n] + B u[n], y[n] = C x[n] + D u[n]
+    StateSpace { a: [[T; 2]; 2], b: [T; 2], c: [T; 2], d: T }
+}
+
+///
+/// BIQUAD REALIZATION
+/// second order section run with the selected topology. The coefficients are
+/// normalized by a0 (and converted to lattice or state-space form) once, when
+/// they are set, never per sample. Changing the coefficients keeps the state,
+/// so the topology also sets how the filter reacts to modulation
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadRealization<T: Sample = f64> {
+    topology: BiquadTopology,
+    kernel: Kernel<T>,
+    state: [T; 4]
+}
+
+impl<T: Sample> BiquadRealization<T> {
+    ///
+    /// INIT REALIZATION
+    /// (pass-through until coefficients are set)
+    ///
+    /// Args
+    /// ----
+    ///     topology: BiquadTopology
+    ///         realization structure
+    ///
+    pub fn new(topology: BiquadTopology) -> Self {
+        let mut realization = Self { topology, kernel: Kernel::Direct([T::zero(); 5]), state: [T::zero(); 4] };
+        realization.set_coeffs((1.0, 0.0, 0.0, 1.0, 0.0, 0.0));
+        realization
+    }
+
+    pub fn topology(&self) -> BiquadTopology {
+        self.topology
+    }
+
+    ///
+    /// SET COEFFICIENTS
+    /// computed in f64, then stored in the processing precision
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
+    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
+        let (b0, b1, b2, a0, a1, a2) = coeffs;
+        let (b0, b1, b2, a1, a2) = (b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0);
+
+        self.kernel = match self.topology {
+            BiquadTopology::DirectForm1 | BiquadTopology::DirectForm2 | BiquadTopology::TransposedDirectForm2 => {
+                Kernel::Direct(cast([b0, b1, b2, a1, a2]))
+            },
+            BiquadTopology::Lattice => lattice_kernel(b0, b1, b2, a1, a2),
+            BiquadTopology::StateSpace => state_space_kernel(b0, b1, b2, a1, a2)
+        };
+    }
+
+    ///
+    /// PROCESS ONE SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample
+    ///
+    pub fn process(&mut self, sample: T) -> T {
+        let s = &mut self.state;
+        match self.kernel {
+            Kernel::Direct([b0, b1, b2, a1, a2]) => match self.topology {
+                BiquadTopology::DirectForm1 => {
+                    // s = [x[n - 1], x[n - 2], y[n - 1], y[n - 2]]
+                    let y = b0 * sample + b1 * s[0] + b2 * s[1] - a1 * s[2] - a2 * s[3];
+                    *s = [sample, s[0], y, s[2]];
+                    y
+                },
+                BiquadTopology::DirectForm2 => {
+                    // s = [w[n - 1], w[n - 2]]
+                    let w = sample - a1 * s[0] - a2 * s[1];
+                    let y = b0 * w + b1 * s[0] + b2 * s[1];
+                    s[1] = s[0];
+                    s[0] = w;
+                    y
+                },
+                _ => {
+                    // transposed direct form II (also used by lattice fallback)
+                    let y = b0 * sample + s[0];
+                    s[0] = b1 * sample - a1 * y + s[1];
+                    s[1] = b2 * sample - a2 * y;
+                    y
+                }
+            },
+            Kernel::Lattice { k, p, q, v } => {
+                // s = [g0[n - 1], g1[n - 1]]
+                let f1 = p[1] * sample - k[1] * s[1];
+                let g2 = k[1] * sample + q[1] * s[1];
+                let g0 = p[0] * f1 - k[0] * s[0];
+                let g1 = k[0] * f1 + q[0] * s[0];
+                s[0] = g0;
+                s[1] = g1;
+                v[0] * g0 + v[1] * g1 + v[2] * g2
+            },
+            Kernel::StateSpace { a, b, c, d } => {
+                let y = c[0] * s[0] + c[1] * s[1] + d * sample;
+                let x0 = a[0][0] * s[0] + a[0][1] * s[1] + b[0] * sample;
+                let x1 = a[1][0] * s[0] + a[1][1] * s[1] + b[1] * sample;
+                s[0] = x0;
+                s[1] = x1;
+                y
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = [T::zero(); 4];
+    }
+
+    ///
+    /// SET STATE
+    /// in the layout of the topology:
+    ///     df1 = [x[n - 1], x[n - 2], y[n - 1], y[n - 2]]
+    ///     df2 = [w[n - 1], w[n - 2], 0, 0]
+    ///     tdf2 = [s1, s2, 0, 0]
+    ///     lattice = [g0[n - 1], g1[n - 1], 0, 0]
+    ///     ss = [x1, x2, 0, 0]
+    ///
+    pub fn state(&self) -> [T; 4] {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: [T; 4]) {
+        self.state = state;
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state reached once a unit step has settled, in the layout of the
+    /// topology (zero state when a pole sits at DC)
+    ///
+    pub fn step_state(&self) -> [T; 4] {
+        let state = match self.kernel {
+            Kernel::Direct(coeffs) => {
+                let [b0, b1, b2, a1, a2] = coeffs.map(T::to_f64);
+                let den = 1.0 + a1 + a2;
+                let gain = (b0 + b1 + b2) / den;
+                match self.topology {
+                    BiquadTopology::DirectForm1 => [1.0, 1.0, gain, gain],
+                    BiquadTopology::DirectForm2 => [1.0 / den, 1.0 / den, 0.0, 0.0],
+                    _ => [gain - b0, b2 - a2 * gain, 0.0, 0.0]
+                }
+            },
+            Kernel::Lattice { k, p, q, .. } => {
+                let (k, p, q) = (k.map(T::to_f64), p.map(T::to_f64), q.map(T::to_f64));
+                steady_state([[-k[0], -p[0] * k[1]], [q[0], -k[0] * k[1]]], [p[0] * p[1], k[0] * p[1]])
+            },
+            Kernel::StateSpace { a, b, .. } => {
+                steady_state([a[0].map(T::to_f64), a[1].map(T::to_f64)], b.map(T::to_f64))
+            }
+        };
+
+        if state.iter().all(|value| value.is_finite()) { cast(state) } else { [T::zero(); 4] }
+    }
+}
+
+// fixed point of x[n + 1] = A x[n] + B for a unit input: (I - A) x = B
+fn steady_state(a: [[f64; 2]; 2], b: [f64; 2]) -> [f64; 4] {
+    let det = (1.0 - a[0][0]) * (1.0 - a[1][1]) - a[0][1] * a[1][0];
+    let x0 = ((1.0 - a[1][1]) * b[0] + a[0][1] * b[1]) / det;
+    let x1 = (a[1][0] * b[0] + (1.0 - a[0][0]) * b[1]) / det;
+    [x0, x1, 0.0, 0.0]
+}
+
+fn cast<T: Sample, const N: usize>(values: [f64; N]) -> [T; N] {
+    values.map(T::from_f64)
+}
+
+// normalized (rotation) lattice when |k| < 1, one-multiplier lattice otherwise,
+// tdf2 when the denominator has no lattice form (a2 = -1)
+fn lattice_kernel<T: Sample>(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Kernel<T> {
+    if 1.0 + a2 == 0.0 {
+        return Kernel::Direct(cast([b0, b1, b2, a1, a2]))
+    }
+
+    let k = [a1 / (1.0 + a2), a2];
+    let stable = k.iter().all(|value| value.abs() < 1.0);
+    let (p, q) = if stable {
+        let c = k.map(|value| libm::sqrt(1.0 - value * value));
+        (c, c)
+    } else {
+        ([1.0; 2], k.map(|value| 1.0 - value * value))
+    };
+
+    // taps of the one-multiplier lattice, scaled by the gain of the normalized stages
+    let v2 = b2;
+    let v1 = b1 - v2 * a1;
+    let v0 = b0 - v2 * a2 - v1 * k[0];
+    let v = [v0 / (p[1] * p[0]), v1 / p[1], v2];
+
+    Kernel::Lattice { k: cast(k), p: cast(p), q: cast(q), v: cast(v) }
+}
+
+// normal (coupled) form for complex poles, controllable canonical form otherwise
+fn state_space_kernel<T: Sample>(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Kernel<T> {
+    let c1 = b1 - b0 * a1;
+    let c2 = b2 - b0 * a2;
+    let discriminant = a1 * a1 - 4.0 * a2;
+
+    let (a, b, c) = if discriminant < 0.0 {
+        let sigma = -a1 / 2.0;
+        let omega = libm::sqrt(-discriminant) / 2.0;
+        ([[sigma, -omega], [omega, sigma]], [1.0, 0.0], [c1, (c2 + c1 * sigma) / omega])
+    } else {
+        ([[-a1, -a2], [1.0, 0.0]], [1.0, 0.0], [c1, c2])
+    };
+
+    Kernel::StateSpace { a: [cast(a[0]), cast(a[1])], b: cast(b), c: cast(c), d: T::from_f64(b0) }
+}
@@ -0,0 +1,349 @@
+use alloc::{vec, vec::Vec};
+use super::{filtertype::RemezType, error::{Result, FilterError, ModeName, check_fs}};
+
+const PI: f64 = core::f64::consts::PI;
+// dense grid points per extremal frequency (as scipy.signal.remez)
+const GRID_DENSITY: usize = 16;
+const MAX_ITERATIONS: usize = 40;
+// relative spread of the error at the extremal frequencies at convergence
+const TOLERANCE: f64 = 1e-4;
+
+impl RemezType {
+    ///
+    /// PARSE REMEZ TYPE NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         bandpass = piecewise constant gain of every band (multiband)
+    ///         differentiator = gain rising with frequency
+    ///         hilbert = 90 degrees phase shift
+    ///
+    /// Return
+    /// ------
+    ///     Result<RemezType>
+    ///         FilterError on unknown name
+    ///
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "bandpass" => Ok(RemezType::Bandpass),
+            "differentiator" => Ok(RemezType::Differentiator),
+            "hilbert" => Ok(RemezType::Hilbert),
+            _ => Err(FilterError::UnknownMode { family: "remez", mode: ModeName::new(name) })
+        }
+    }
+}
+
+// dense grid of the bands with the desired amplitude and the weight, both
+// divided by the fixed factor of the linear phase type
+struct Grid {
+    frequencies: Vec<f64>,
+    desired: Vec<f64>,
+    weights: Vec<f64>
+}
+
+///
+/// PARKS-MCCLELLAN EQUIRIPPLE DESIGN
+/// linear phase FIR taps minimizing the maximum weighted error over the
+/// bands (Remez exchange, as scipy.signal.remez). The response between the
+/// bands (transition bands) is not constrained
+///
+/// Args
+/// ----
+///     n_taps: usize
+///         number of taps
+///     bands: &[f64]
+///         band edges in Hz, [low, high] pairs increasing from 0 to fs / 2
+///     desired: &[f64]
+///         gain of every band, for the differentiator the slope: gain
+///         desired * f / fs (2 pi for the derivative of the samples)
+///     weights: Option<&[f64]>
+///         relative weight of the error in every band (default 1.0), the
+///         ripples scale as 1 / weight
+///     kind: RemezType
+///         Bandpass (symmetric taps), Differentiator (H = j D(f)) or
+///         Hilbert (H = -j D(f), x + j y is the analytic signal)
+///     fs: f64
+///         sampling rate
+///
+/// Return
+/// ------
+///     Result<Vec<f64>>
+///         taps h[0], ..., h[n_taps - 1]
+///         FilterError on invalid bands, gains or weights, too few taps, or
+///         when the exchange does not converge
+///
+pub fn remez(n_taps: usize, bands: &[f64], desired: &[f64], weights: Option<&[f64]>, kind: RemezType, fs: f64) -> Result<Vec<f64>> {
+    check_fs(fs)?;
+    let n_bands = bands.len() / 2;
+    if bands.is_empty() || !bands.len().is_multiple_of(2) {
+        return Err(FilterError::InvalidBands("band edges must come in [low, high] pairs"))
+    }
+    if desired.len() != n_bands {
+        return Err(FilterError::InvalidBands("one desired gain per band"))
+    }
+    if weights.is_some_and(|w| w.len() != n_bands) {
+        return Err(FilterError::InvalidBands("one weight per band"))
+    }
+    if weights.is_some_and(|w| w.iter().any(|&w| !(w > 0.0 && w.is_finite()))) || desired.iter().any(|d| !d.is_finite()) {
+        return Err(FilterError::InvalidBands("weights must be positive, gains finite"))
+    }
+    let edges_increase = bands.windows(2).enumerate().all(|(n, e)| if n.is_multiple_of(2) { e[0] < e[1] } else { e[0] <= e[1] });
+    if !edges_increase || bands[0] < 0.0 || bands[bands.len() - 1] > fs / 2.0 {
+        return Err(FilterError::InvalidBands("band edges must increase from 0 to fs / 2"))
+    }
+
+    // antisymmetric taps for the 90 degrees types, number of cosine functions
+    let negative = kind != RemezType::Bandpass;
+    let odd = n_taps % 2 == 1;
+    let n_functions = if odd && !negative { n_taps / 2 + 1 } else { n_taps / 2 };
+    let weights: Vec<f64> = weights.map_or_else(|| vec![1.0; n_bands], |w| w.to_vec());
+    let grid = dense_grid(bands, desired, &weights, kind, n_functions, odd, fs);
+    if n_functions == 0 || grid.frequencies.len() <= n_functions {
+        return Err(FilterError::InvalidBands("too few taps for the bands"))
+    }
+
+    let extremals = exchange(&grid, n_functions)?;
+    let (x, barycentric, _, values) = levelled(&grid, &extremals);
+
+    // amplitude of the taps: interpolated polynomial times the fixed factor
+    let amplitude = |f: f64| {
+        interpolate(&x, &barycentric, &values, libm::cos(2.0 * PI * f)) * fixed_factor(f, negative, odd)
+    };
+
+    // frequency sampling at k / n_taps, exact for a trigonometric
+    // polynomial of this length
+    let n = n_taps as f64;
+    let center = (n - 1.0) / 2.0;
+    let samples: Vec<f64> = (0..=n_taps / 2).map(|k| amplitude(k as f64 / n)).collect();
+    let taps = (0..n_taps).map(|i| {
+        let t = i as f64 - center;
+        let sum: f64 = (1..=(n_taps - 1) / 2).map(|k| {
+            let phase = 2.0 * PI * k as f64 * t / n;
+            if negative { -samples[k] * libm::sin(phase) } else { samples[k] * libm::cos(phase) }
+        }).sum();
+        let tap = if negative {
+            let nyquist = if odd { 0.0 } else { -samples[n_taps / 2] * libm::sin(PI * t) };
+            (2.0 * sum + nyquist) / n
+        } else {
+            (samples[0] + 2.0 * sum) / n
+        };
+        // the Hilbert transformer is -j on positive frequencies
+        if kind == RemezType::Hilbert { -tap } else { tap }
+    }).collect();
+    Ok(taps)
+}
+
+// factor of the response shared by every design of a linear phase type,
+// the exchange approximates the rest with a cosine polynomial
+fn fixed_factor(f: f64, negative: bool, odd: bool) -> f64 {
+    match (negative, odd) {
+        (false, true) => 1.0,
+        (false, false) => libm::cos(PI * f),
+        (true, true) => libm::sin(2.0 * PI * f),
+        (true, false) => libm::sin(PI * f)
+    }
+}
+
+fn dense_grid(bands: &[f64], desired: &[f64], weights: &[f64], kind: RemezType, n_functions: usize, odd: bool, fs: f64) -> Grid {
+    let step = 0.5 / (GRID_DENSITY * n_functions.max(1)) as f64;
+    let negative = kind != RemezType::Bandpass;
+    let mut grid = Grid { frequencies: Vec::new(), desired: Vec::new(), weights: Vec::new() };
+    let mut band_of = Vec::new();
+
+    for (band, edges) in bands.chunks(2).enumerate() {
+        let (low, high) = (edges[0] / fs, edges[1] / fs);
+        // antisymmetric responses are 0 at DC
+        let mut f = if negative && low == 0.0 { step.min(high) } else { low };
+        loop {
+            grid.frequencies.push(f);
+            band_of.push(band);
+            f += step;
+            if f > high {
+                break
+            }
+        }
+        if let Some(last) = grid.frequencies.last_mut() {
+            *last = high;
+        }
+    }
+    // types with a zero at Nyquist cannot fit a point there
+    if negative == odd && grid.frequencies.last().is_some_and(|&f| f > 0.5 - step) {
+        grid.frequencies.pop();
+        band_of.pop();
+    }
+
+    for (&f, &band) in grid.frequencies.iter().zip(&band_of) {
+        let (mut gain, mut weight) = (desired[band], weights[band]);
+        if kind == RemezType::Differentiator {
+            gain *= f;
+            // relative error for a rising gain
+            if desired[band] >= 1e-4 {
+                weight /= f;
+            }
+        }
+        let factor = fixed_factor(f, negative, odd);
+        grid.desired.push(gain / factor);
+        grid.weights.push(weight * factor);
+    }
+    grid
+}
+
+// barycentric weights of the interpolation nodes, scaled by 2 per factor to
+// stay in range
+fn barycentric_weights(x: &[f64]) -> Vec<f64> {
+    (0..x.len()).map(|k| {
+        1.0 / x.iter().enumerate().filter(|&(j, _)| j != k).map(|(_, &xj)| 2.0 * (x[k] - xj)).product::<f64>()
+    }).collect()
+}
+
+fn interpolate(x: &[f64], barycentric: &[f64], values: &[f64], xf: f64) -> f64 {
+    let (mut numerator, mut denominator) = (0.0, 0.0);
+    for ((&xk, &ak), &yk) in x.iter().zip(barycentric).zip(values) {
+        let d = xf - xk;
+        if d == 0.0 {
+            return yk
+        }
+        numerator += ak / d * yk;
+        denominator += ak / d;
+    }
+    numerator / denominator
+}
+
+// nodes, barycentric weights, deviation and values of the polynomial with
+// an error of alternating sign and equal size at the extremal frequencies
+fn levelled(grid: &Grid, extremals: &[usize]) -> (Vec<f64>, Vec<f64>, f64, Vec<f64>) {
+    let x: Vec<f64> = extremals.iter().map(|&i| libm::cos(2.0 * PI * grid.frequencies[i])).collect();
+    let barycentric = barycentric_weights(&x);
+    let sign = |k: usize| if k.is_multiple_of(2) { 1.0 } else { -1.0 };
+
+    let (mut numerator, mut denominator) = (0.0, 0.0);
+    for (k, (&i, &a)) in extremals.iter().zip(&barycentric).enumerate() {
+        numerator += a * grid.desired[i];
+        denominator += sign(k) * a / grid.weights[i];
+    }
+    let deviation = numerator / denominator;
+    let values = extremals.iter().enumerate().map(|(k, &i)| grid.desired[i] - sign(k) * deviation / grid.weights[i]).collect();
+    (x, barycentric, deviation, values)
+}
+
+// Remez exchange: move the extremal frequencies to the peaks of the
+// weighted error until the peaks are level
+fn exchange(grid: &Grid, n_functions: usize) -> Result<Vec<usize>> {
+    let n_grid = grid.frequencies.len();
+    let n_extremals = n_functions + 1;
+    let mut extremals: Vec<usize> = (0..n_extremals).map(|j| j * (n_grid - 1) / n_functions).collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let (x, barycentric, deviation, values) = levelled(grid, &extremals);
+        let error: Vec<f64> = (0..n_grid).map(|i| {
+            let a = interpolate(&x, &barycentric, &values, libm::cos(2.0 * PI * grid.frequencies[i]));
+            grid.weights[i] * (grid.desired[i] - a)
+        }).collect();
+
+        let found = error_peaks(&error, n_extremals);
+        if found.len() < n_extremals {
+            break
+        }
+        let peak = found.iter().map(|&i| libm::fabs(error[i])).fold(0.0, f64::max);
+        let floor = found.iter().map(|&i| libm::fabs(error[i])).fold(f64::INFINITY, f64::min);
+        extremals = found;
+        if peak - floor <= TOLERANCE * peak.max(libm::fabs(deviation)) {
+            return Ok(extremals)
+        }
+    }
+    Err(FilterError::RemezNotConverged(MAX_ITERATIONS))
+}
+
+// local extrema of the error, pruned to n alternating peaks
+fn error_peaks(error: &[f64], n: usize) -> Vec<usize> {
+    let last = error.len() - 1;
+    let is_peak = |i: usize| {
+        let e = error[i];
+        let above = |j: usize| if e > 0.0 { e >= error[j] } else { e <= error[j] };
+        e != 0.0 && (i == 0 || above(i - 1)) && (i == last || above(i + 1))
+    };
+    let mut found: Vec<usize> = (0..=last).filter(|&i| is_peak(i)).collect();
+
+    while found.len() > n {
+        let magnitude = |k: usize| libm::fabs(error[found[k]]);
+        // the smaller of two neighbours with the same sign, else the smaller
+        // end (one extra peak) or the smallest peak
+        let remove = match (1..found.len()).find(|&k| (error[found[k]] > 0.0) == (error[found[k - 1]] > 0.0)) {
+            Some(k) => if magnitude(k) < magnitude(k - 1) { k } else { k - 1 },
+            None if found.len() == n + 1 => if magnitude(found.len() - 1) < magnitude(0) { found.len() - 1 } else { 0 },
+            None => (0..found.len()).fold(0, |smallest, k| if magnitude(k) < magnitude(smallest) { k } else { smallest })
+        };
+        found.remove(remove);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_complex::Complex64;
+
+    const FS: f64 = 48000.0;
+
+    // response with the linear phase delay removed: real for symmetric
+    // taps, imaginary for antisymmetric taps
+    fn zero_phase(taps: &[f64], f: f64) -> Complex64 {
+        let center = (taps.len() - 1) as f64 / 2.0;
+        taps.iter().enumerate().map(|(n, &h)| Complex64::from_polar(h, -2.0 * PI * f / FS * (n as f64 - center))).sum()
+    }
+
+    // local maxima of the error inside a band, on a dense grid
+    fn error_peaks(band: (f64, f64), error: impl Fn(f64) -> f64) -> Vec<f64> {
+        let grid: Vec<f64> = (0..=2000).map(|k| band.0 + (band.1 - band.0) * k as f64 / 2000.0).map(&error).collect();
+        (0..grid.len()).filter(|&i| {
+            (i == 0 || grid[i] >= grid[i - 1]) && (i == grid.len() - 1 || grid[i] >= grid[i + 1])
+        }).map(|i| grid[i]).collect()
+    }
+
+    // alternation theorem: at least n_functions + 1 peaks of the weighted
+    // error reach the deviation (extra peaks, e.g. at a band edge, may be
+    // lower). The grid sampling leaves a small spread
+    fn assert_equiripple(mut peaks: Vec<f64>, n_functions: usize) {
+        peaks.sort_by(|a, b| b.total_cmp(a));
+        assert!(peaks.len() > n_functions, "{} peaks for {} functions", peaks.len(), n_functions);
+        let delta = peaks[0];
+        for &peak in &peaks[..=n_functions] {
+            assert!(peak > 0.97 * delta, "peak {peak}, deviation {delta}");
+        }
+    }
+
+    #[test]
+    fn lowpass_is_equiripple_with_the_weighted_deviations() {
+        let taps = remez(73, &[0.0, 4800.0, 7200.0, 24000.0], &[1.0, 0.0], Some(&[1.0, 10.0]), RemezType::Bandpass, FS).unwrap();
+        assert!((0..73).all(|n| taps[n] == taps[72 - n]));
+
+        let pass = error_peaks((0.0, 4800.0), |f| (zero_phase(&taps, f).re - 1.0).abs());
+        let stop = error_peaks((7200.0, 24000.0), |f| 10.0 * zero_phase(&taps, f).re.abs());
+        // the weight 10 makes the pass band ripple 10 times the stop band ripple
+        let (pass_delta, stop_delta) = (pass.iter().cloned().fold(0.0, f64::max), stop.iter().cloned().fold(0.0, f64::max));
+        assert!((pass_delta / stop_delta - 1.0).abs() < 0.03, "pass band {pass_delta}, weighted stop band {stop_delta}");
+        assert!(20.0 * libm::log10(stop_delta / 10.0) < -40.0);
+        assert_equiripple(pass.into_iter().chain(stop).collect(), 37);
+    }
+
+    #[test]
+    fn hilbert_transformer_is_equiripple() {
+        let taps = remez(63, &[1200.0, 22800.0], &[1.0], None, RemezType::Hilbert, FS).unwrap();
+        // antisymmetric, every other tap about zero (band symmetric about fs / 4)
+        for n in 0..63 {
+            assert!((taps[n] + taps[62 - n]).abs() < 1e-12);
+            if n % 2 == 1 {
+                assert!(taps[n].abs() < 1e-5, "tap {n}: {}", taps[n]);
+            }
+        }
+        // -j over the band
+        let peaks = error_peaks((1200.0, 22800.0), |f| {
+            let h = zero_phase(&taps, f);
+            assert!(h.re.abs() < 1e-9);
+            (h.im + 1.0).abs()
+        });
+        assert!(peaks.iter().all(|&peak| peak < 0.003), "ripple {peaks:?}");
+        assert_equiripple(peaks, 31);
+    }
+}
@@ -0,0 +1,50 @@
+use core::fmt::Debug;
+use core::ops::{Add, Sub, Mul, Div, Neg};
+
+///
+/// SAMPLE TYPE
+/// floating point type used for filter state and processing.
+/// Designs are always computed in f64 and converted with from_f64
+///
+pub trait Sample:
+    Copy
+    + Default
+    + Debug
+    + PartialEq
+    + PartialOrd
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn zero() -> Self {
+        Self::from_f64(0.0)
+    }
+}
+
+impl Sample for f32 {
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Sample for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
@@ -0,0 +1,340 @@
+use super::error::{Result, FilterError};
+#[cfg(feature = "alloc")]
+use super::snapshot::{FilterState, StateReader};
+
+///
+/// DEFAULT CONTROL INTERVAL
+/// number of samples between two coefficient recomputations while a
+/// parameter glides
+///
+pub const DEFAULT_CONTROL_INTERVAL: usize = 32;
+
+///
+/// SMOOTHED PARAMETER
+/// linear ramp from the current value to a target over a number of samples.
+/// Logarithmic parameters (frequency, Q) ramp on a log scale, so that the
+/// glide has a constant speed in octaves
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothedParam {
+    value: f64,
+    target: f64,
+    step: f64,
+    remaining: usize,
+    logarithmic: bool
+}
+
+impl SmoothedParam {
+    ///
+    /// INIT SMOOTHED PARAMETER
+    ///
+    /// Args
+    /// ----
+    ///     value: f64
+    ///         initial value (positive if logarithmic)
+    ///     logarithmic: bool
+    ///         glide on a log scale
+    ///
+    pub fn new(value: f64, logarithmic: bool) -> Self {
+        let mut param = Self { value: 0.0, target: 0.0, step: 0.0, remaining: 0, logarithmic };
+        param.snap(value);
+        param
+    }
+
+    ///
+    /// JUMP TO VALUE
+    /// (no glide)
+    ///
+    pub fn snap(&mut self, value: f64) {
+        self.value = self.scaled(value);
+        self.target = self.value;
+        self.step = 0.0;
+        self.remaining = 0;
+    }
+
+    ///
+    /// STOP THE GLIDE
+    /// (keeps the current value)
+    ///
+    pub fn stop(&mut self) {
+        self.target = self.value;
+        self.step = 0.0;
+        self.remaining = 0;
+    }
+
+    ///
+    /// SET TARGET
+    ///
+    /// Args
+    /// ----
+    ///     target: f64
+    ///         value reached at the end of the glide
+    ///     n_samples: usize
+    ///         glide length in samples (0 = jump)
+    ///
+    pub fn set_target(&mut self, target: f64, n_samples: usize) {
+        if n_samples == 0 {
+            return self.snap(target)
+        }
+        self.target = self.scaled(target);
+        self.step = (self.target - self.value) / n_samples as f64;
+        self.remaining = n_samples;
+    }
+
+    ///
+    /// ADVANCE THE GLIDE
+    ///
+    /// Args
+    /// ----
+    ///     n_samples: usize
+    ///         number of samples to move along the ramp
+    ///
+    /// Return
+    /// ------
+    ///     f64
+    ///         value after n_samples (clamped to the target)
+    ///
+    pub fn advance(&mut self, n_samples: usize) -> f64 {
+        if n_samples >= self.remaining {
+            self.value = self.target;
+            self.remaining = 0;
+        } else {
+            self.value += self.step * n_samples as f64;
+            self.remaining -= n_samples;
+        }
+        self.value()
+    }
+
+    pub fn value(&self) -> f64 {
+        if self.logarithmic { libm::exp(self.value) } else { self.value }
+    }
+
+    pub fn target(&self) -> f64 {
+        if self.logarithmic { libm::exp(self.target) } else { self.target }
+    }
+
+    pub fn is_smoothing(&self) -> bool {
+        self.remaining > 0
+    }
+
+    fn scaled(&self, value: f64) -> f64 {
+        if self.logarithmic { libm::log(value) } else { value }
+    }
+
+    // value, target, step and remaining samples of the ramp (the scale is
+    // part of the filter)
+    #[cfg(feature = "alloc")]
+    pub(crate) fn save_state(&self, state: &mut FilterState) {
+        state.push(self.value);
+        state.push(self.target);
+        state.push(self.step);
+        state.push(self.remaining as f64);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.value = reader.read()?;
+        self.target = reader.read()?;
+        self.step = reader.read()?;
+        self.remaining = reader.read_index(usize::MAX)?;
+        Ok(())
+    }
+}
+
+///
+/// PARAMETER SMOOTHER
+/// glides P design parameters and turns them into C filter coefficients.
+/// The parameters advance (and the coefficients are designed) once every
+/// control interval; in between, the coefficients are linearly interpolated
+/// sample by sample, so the filter never sees a coefficient jump
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Smoother<const P: usize, const C: usize> {
+    params: [SmoothedParam; P],
+    coeffs: [f64; C],
+    coeffs_step: [f64; C],
+    coeffs_target: [f64; C],
+    coeffs_remaining: usize,
+    glide: usize,
+    interval: usize,
+    countdown: usize
+}
+
+impl<const P: usize, const C: usize> Smoother<P, C> {
+    ///
+    /// INIT SMOOTHER
+    /// (glide time 0: targets are applied at once)
+    ///
+    /// Args
+    /// ----
+    ///     params: [SmoothedParam; P]
+    ///         design parameters
+    ///     coeffs: [f64; C]
+    ///         coefficients of the initial parameters
+    ///
+    pub fn new(params: [SmoothedParam; P], coeffs: [f64; C]) -> Self {
+        Self {
+            params,
+            coeffs,
+            coeffs_step: [0.0; C],
+            coeffs_target: coeffs,
+            coeffs_remaining: 0,
+            glide: 0,
+            interval: DEFAULT_CONTROL_INTERVAL,
+            countdown: 0
+        }
+    }
+
+    ///
+    /// SET GLIDE TIME
+    ///
+    /// Args
+    /// ----
+    ///     time: f64
+    ///         glide time in sec. (0.0 = no smoothing)
+    ///     fs: f64
+    ///         sampling rate in Hz
+    ///     control_interval: usize
+    ///         samples between two coefficient recomputations (at least 1)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on negative time
+    ///
+    pub fn set_time(&mut self, time: f64, fs: f64, control_interval: usize) -> Result<()> {
+        if time.is_nan() || time < 0.0 {
+            return Err(FilterError::NegativeGlideTime(time))
+        }
+        self.glide = libm::round(time * fs) as usize;
+        self.interval = control_interval.max(1);
+        Ok(())
+    }
+
+    ///
+    /// JUMP TO PARAMETERS AND COEFFICIENTS
+    /// (stops any glide)
+    ///
+    pub fn snap(&mut self, values: [f64; P], coeffs: [f64; C]) {
+        for (param, value) in self.params.iter_mut().zip(values) {
+            param.snap(value);
+        }
+        self.snap_coeffs(coeffs);
+    }
+
+    ///
+    /// JUMP TO COEFFICIENTS
+    /// (stops any glide, parameters stay at their current values)
+    ///
+    pub fn snap_coeffs(&mut self, coeffs: [f64; C]) {
+        for param in self.params.iter_mut() {
+            param.stop();
+        }
+        self.coeffs = coeffs;
+        self.coeffs_target = coeffs;
+        self.coeffs_remaining = 0;
+        self.countdown = 0;
+    }
+
+    ///
+    /// SET PARAMETER TARGETS
+    ///
+    /// Return
+    /// ------
+    ///     bool
+    ///         false if the glide time is 0 (the caller applies the targets at once)
+    ///
+    pub fn set_targets(&mut self, values: [f64; P]) -> bool {
+        for (param, value) in self.params.iter_mut().zip(values) {
+            param.set_target(value, self.glide);
+        }
+        self.countdown = 0;
+        self.glide > 0
+    }
+
+    pub fn values(&self) -> [f64; P] {
+        core::array::from_fn(|i| self.params[i].value())
+    }
+
+    pub fn targets(&self) -> [f64; P] {
+        core::array::from_fn(|i| self.params[i].target())
+    }
+
+    pub fn is_smoothing(&self) -> bool {
+        self.coeffs_remaining > 0 || self.params.iter().any(SmoothedParam::is_smoothing)
+    }
+
+    ///
+    /// ADVANCE ONE SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     design: FnMut([f64; P]) -> [f64; C]
+    ///         coefficients of a set of parameters, called at control rate
+    ///
+    /// Return
+    /// ------
+    ///     Option<[f64; C]>
+    ///         coefficients for this sample, None when they don't change
+    ///
+    #[inline]
+    pub fn tick(&mut self, mut design: impl FnMut([f64; P]) -> [f64; C]) -> Option<[f64; C]> {
+        if self.countdown == 0 && self.params.iter().any(SmoothedParam::is_smoothing) {
+            let interval = self.interval;
+            let values = core::array::from_fn(|i| self.params[i].advance(interval));
+            let target = design(values);
+            for ((step, current), next) in self.coeffs_step.iter_mut().zip(self.coeffs).zip(target) {
+                *step = (next - current) / interval as f64;
+            }
+            self.coeffs_target = target;
+            self.coeffs_remaining = interval;
+            self.countdown = interval;
+        }
+        self.countdown = self.countdown.saturating_sub(1);
+
+        if self.coeffs_remaining == 0 {
+            return None
+        }
+        self.coeffs_remaining -= 1;
+        if self.coeffs_remaining == 0 {
+            // end the ramp exactly on the designed coefficients
+            self.coeffs = self.coeffs_target;
+        } else {
+            for (coeff, step) in self.coeffs.iter_mut().zip(self.coeffs_step) {
+                *coeff += step;
+            }
+        }
+        Some(self.coeffs)
+    }
+
+    ///
+    /// CURRENT COEFFICIENTS
+    /// (the ones returned by the last tick, snap or snap_coeffs)
+    ///
+    pub fn coeffs(&self) -> [f64; C] {
+        self.coeffs
+    }
+
+    // glide of every parameter, coefficient ramp and control countdown (the
+    // glide time and the control interval are part of the filter)
+    #[cfg(feature = "alloc")]
+    pub(crate) fn save_state(&self, state: &mut FilterState) {
+        self.params.iter().for_each(|param| param.save_state(state));
+        self.coeffs.iter().chain(&self.coeffs_step).chain(&self.coeffs_target).for_each(|&value| state.push(value));
+        state.push(self.coeffs_remaining as f64);
+        state.push(self.countdown as f64);
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        for param in self.params.iter_mut() {
+            param.load_state(reader)?;
+        }
+        for value in self.coeffs.iter_mut().chain(self.coeffs_step.iter_mut()).chain(self.coeffs_target.iter_mut()) {
+            *value = reader.read()?;
+        }
+        self.coeffs_remaining = reader.read_index(usize::MAX)?;
+        self.countdown = reader.read_index(usize::MAX)?;
+        Ok(())
+    }
+}
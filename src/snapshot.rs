@@ -0,0 +1,307 @@
+use alloc::{string::String, vec::Vec};
+use super::{sample::Sample, error::{Result, FilterError}};
+
+///
+/// FILTER STATE
+/// delayed samples of a filter (delay line buffers and write positions,
+/// integrator and section states) as plain f64 values, tagged with the
+/// filter family. f32 samples are stored exactly. The design is not part of
+/// the state, except for Biquad and OnePole: their running glide (parameter
+/// and coefficient ramps) is, with the coefficients it reached
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterState {
+    family: String,
+    values: Vec<f64>
+}
+
+impl FilterState {
+    ///
+    /// INIT FILTER STATE
+    /// (e.g. from values stored with family and values)
+    ///
+    /// Args
+    /// ----
+    ///     family: &str
+    ///         filter family the state belongs to
+    ///     values: Vec<f64>
+    ///         state values, in the order written by the filter
+    ///
+    pub fn new(family: &str, values: Vec<f64>) -> Self {
+        Self { family: String::from(family), values }
+    }
+
+    pub fn family(&self) -> &str {
+        &self.family
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    ///
+    /// ENCODE STATE
+    /// family length (u32), family (utf-8) and values (f64), little endian
+    ///
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.family.len() + 8 * self.values.len());
+        bytes.extend_from_slice(&(self.family.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.family.as_bytes());
+        self.values.iter().for_each(|value| bytes.extend_from_slice(&value.to_le_bytes()));
+        bytes
+    }
+
+    ///
+    /// DECODE STATE
+    /// (layout of to_bytes)
+    ///
+    /// Args
+    /// ----
+    ///     bytes: &[u8]
+    ///         encoded state
+    ///
+    /// Return
+    /// ------
+    ///     Result<FilterState>
+    ///         FilterError on malformed bytes
+    ///
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let malformed = FilterError::InvalidState("malformed bytes");
+        let (length, rest) = bytes.split_first_chunk::<4>().ok_or(malformed.clone())?;
+        let length = u32::from_le_bytes(*length) as usize;
+        if rest.len() < length || !(rest.len() - length).is_multiple_of(8) {
+            return Err(malformed)
+        }
+        let (family, values) = rest.split_at(length);
+        let family = core::str::from_utf8(family).map_err(|_| malformed)?;
+        let values = values.chunks_exact(8).map(|chunk| f64::from_le_bytes(core::array::from_fn(|i| chunk[i]))).collect();
+        Ok(Self::new(family, values))
+    }
+
+    ///
+    /// WRITE ONE VALUE
+    /// (used by Snapshot::save_state)
+    ///
+    pub fn push<T: Sample>(&mut self, value: T) {
+        self.values.push(value.to_f64());
+    }
+
+    pub fn reader(&self) -> StateReader<'_> {
+        StateReader { values: self.values.iter() }
+    }
+}
+
+///
+/// STATE READER
+/// reads the values of a FilterState in the order they were written
+/// (used by Snapshot::load_state)
+///
+pub struct StateReader<'a> {
+    values: core::slice::Iter<'a, f64>
+}
+
+impl StateReader<'_> {
+    ///
+    /// READ ONE VALUE
+    ///
+    /// Return
+    /// ------
+    ///     Result<T>
+    ///         FilterError if the state has no values left
+    ///
+    pub fn read<T: Sample>(&mut self) -> Result<T> {
+        self.values.next().map(|&value| T::from_f64(value)).ok_or(FilterError::InvalidState("too few values"))
+    }
+
+    ///
+    /// READ A STORED LENGTH, POSITION OR COUNT
+    ///
+    /// Args
+    /// ----
+    ///     below: usize
+    ///         upper bound (excluded)
+    ///
+    /// Return
+    /// ------
+    ///     Result<usize>
+    ///         FilterError if no value is left or the value is not an integer in 0..below
+    ///
+    pub fn read_index(&mut self, below: usize) -> Result<usize> {
+        let value: f64 = self.read()?;
+        if value >= 0.0 && value < below as f64 && libm::trunc(value) == value {
+            Ok(value as usize)
+        } else {
+            Err(FilterError::InvalidState("length or position out of range"))
+        }
+    }
+
+    ///
+    /// READ A STORED VALUE THAT MUST MATCH THE FILTER
+    /// (length of a delay line, number of sections, topology)
+    ///
+    pub fn expect(&mut self, expected: usize) -> Result<()> {
+        let value: f64 = self.read()?;
+        if value == expected as f64 { Ok(()) } else { Err(FilterError::InvalidState("different order, length or topology")) }
+    }
+
+    ///
+    /// NUMBER OF VALUES LEFT
+    /// (bound for counts read from the state before allocating)
+    ///
+    pub fn remaining(&self) -> usize {
+        self.values.len()
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.remaining() == 0 { Ok(()) } else { Err(FilterError::InvalidState("too many values")) }
+    }
+}
+
+///
+/// STATE SNAPSHOT
+/// capture the state of a filter as a value and load it back later (seek,
+/// render ahead, roll back). Implemented by every filter and by Multichannel
+///
+pub trait Snapshot: Clone {
+    ///
+    /// FAMILY TAG
+    /// checked on restore
+    ///
+    const FAMILY: &'static str;
+
+    ///
+    /// WRITE EVERY STATE VALUE
+    ///
+    fn save_state(&self, state: &mut FilterState);
+
+    ///
+    /// READ EVERY STATE VALUE
+    /// in the order of save_state
+    ///
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()>;
+
+    ///
+    /// TAKE STATE SNAPSHOT
+    ///
+    /// Return
+    /// ------
+    ///     FilterState
+    ///         delayed samples of the filter
+    ///
+    fn snapshot(&self) -> FilterState {
+        let mut state = FilterState::new(Self::FAMILY, Vec::new());
+        self.save_state(&mut state);
+        state
+    }
+
+    ///
+    /// RESTORE STATE SNAPSHOT
+    /// (all or nothing: the filter is unchanged on error)
+    ///
+    /// Args
+    /// ----
+    ///     state: &FilterState
+    ///         snapshot taken from a filter of the same family, order and topology
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if the state does not match the filter
+    ///
+    fn restore(&mut self, state: &FilterState) -> Result<()> {
+        if state.family() != Self::FAMILY {
+            return Err(FilterError::InvalidState("taken from another filter family"))
+        }
+        let mut filter = self.clone();
+        let mut reader = state.reader();
+        filter.load_state(&mut reader)?;
+        reader.finish()?;
+        *self = filter;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use crate::{biquadeq::Biquad, onepole::OnePole, narrow::Narrow, butter::Butter, multichannel::Multichannel, filter::Filter, filtertype::BiquadTopology};
+
+    const FS: f64 = 48000.0;
+
+    fn signal(n: usize) -> f64 {
+        libm::sin(0.01 * n as f64) + 0.3 * libm::sin(0.7 * n as f64)
+    }
+
+    // run, snapshot, run ahead, restore (through bytes) and run the same
+    // samples again: the output must be identical bit for bit
+    fn assert_resumes_bit_exact<F: Snapshot + Filter<f64>>(mut filter: F) {
+        for n in 0..700 {
+            filter.process_sample(signal(n));
+        }
+        let state = FilterState::from_bytes(&filter.snapshot().to_bytes()).unwrap();
+        let ahead: Vec<f64> = (700..4000).map(|n| filter.process_sample(signal(n))).collect();
+        filter.restore(&state).unwrap();
+        for (n, expected) in (700..4000).zip(ahead) {
+            let y = filter.process_sample(signal(n));
+            assert_eq!(y.to_bits(), expected.to_bits(), "sample {n}: {y} != {expected}");
+        }
+    }
+
+    #[test]
+    fn restore_is_bit_exact() {
+        for topology in [BiquadTopology::DirectForm1, BiquadTopology::DirectForm2, BiquadTopology::TransposedDirectForm2, BiquadTopology::Lattice, BiquadTopology::StateSpace] {
+            let mut bq: Biquad = Biquad::with_topology(FS, topology);
+            bq.design_filter("peq", 1000.0, 2.0, Some(9.0)).unwrap();
+            assert_resumes_bit_exact(bq);
+        }
+        let mut narrow: Narrow = Narrow::new(FS, 3).unwrap();
+        narrow.design_filter("bp", 800.0, 50.0).unwrap();
+        assert_resumes_bit_exact(narrow);
+        let mut butter: Butter = Butter::new(FS, 6);
+        butter.design_filter("bp", 2000.0, Some(500.0)).unwrap();
+        assert_resumes_bit_exact(butter);
+    }
+
+    #[test]
+    fn restore_mid_glide_is_bit_exact() {
+        // the glide (2400 samples) is running when the snapshot is taken
+        // and over when it is restored
+        for topology in [BiquadTopology::DirectForm1, BiquadTopology::Lattice] {
+            let mut bq: Biquad = Biquad::with_topology(FS, topology);
+            bq.design_filter("lp", 200.0, 0.707, None).unwrap();
+            bq.set_glide(0.05, 32).unwrap();
+            bq.set_target(5000.0, 4.0, None).unwrap();
+            assert_resumes_bit_exact(bq);
+        }
+        let mut onepole: OnePole = OnePole::new(FS, 2).unwrap();
+        onepole.design_filter("hp", 100.0).unwrap();
+        onepole.set_glide(0.05, 7).unwrap();
+        onepole.set_target(3000.0).unwrap();
+        assert_resumes_bit_exact(onepole);
+    }
+
+    #[test]
+    fn restore_rejects_mismatched_or_corrupt_states() {
+        let mut bq: Biquad = Biquad::new(FS);
+        let mut state = bq.snapshot();
+        state.values[0] = 0.5;
+        assert!(bq.restore(&state).is_err());
+        assert!(bq.restore(&FilterState::new("onepole", vec![])).is_err());
+
+        // a channel count larger than the payload is rejected before allocating
+        let mut stereo = Multichannel::new(bq, 2);
+        let mut state = stereo.snapshot();
+        state.values[0] = 1e15;
+        assert!(stereo.restore(&state).is_err());
+        assert_eq!(stereo.n_channels(), 2);
+    }
+}
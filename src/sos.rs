@@ -0,0 +1,247 @@
+#![allow(clippy::wrong_self_convention)]
+#![allow(clippy::new_without_default)]
+
+use super::{sample::Sample, biquadeq::Biquad, filtertype::BiquadTopology, iirdesign::Cascade, filter::Filter, error::{Result, FilterError, check_fs}};
+use alloc::vec::Vec;
+use super::freqz::{FrequencyResponse, Section};
+use super::snapshot::{Snapshot, FilterState, StateReader};
+
+///
+/// SECOND ORDER SECTIONS EXPORT
+/// the stored design as rows [b0, b1, b2, a0, a1, a2] normalized to a0 = 1,
+/// the layout of scipy.signal sos arrays (K, 6). Run in order, the rows
+/// give the same response as the filter
+///
+pub trait SecondOrderSections {
+    fn sos(&self) -> Vec<[f64; 6]>;
+}
+
+// FilterError on the first section with a0 = 0 or non finite coefficients
+pub(crate) fn check_sos(sos: &[[f64; 6]]) -> Result<()> {
+    match sos.iter().position(|row| row[3] == 0.0 || row.iter().any(|c| !c.is_finite())) {
+        Some(index) => Err(FilterError::InvalidSection(index)),
+        None => Ok(())
+    }
+}
+
+///
+/// SECOND ORDER SECTIONS CASCADE
+/// K biquad sections with their own state, run in order (transposed direct
+/// form II, as scipy.signal.sosfilt)
+///
+#[derive(Clone)]
+pub struct Sos<T: Sample = f64> {
+    fs: f64,
+    cascade: Cascade<T>
+}
+
+impl<T: Sample> Sos<T> {
+    ///
+    /// INIT SOS CLASS
+    ///
+    /// Args
+    /// ----
+    ///     fs: f64
+    ///         sampling rate (used by freqz)
+    ///     sos: &[[f64; 6]]
+    ///         sections [b0, b1, b2, a0, a1, a2], e.g. the rows of a scipy
+    ///         sos array (normalized by a0 if a0 != 1)
+    ///
+    /// Return
+    /// ------
+    ///     Result<Sos<T>>
+    ///         FilterError on invalid fs, a0 = 0 or non finite coefficients
+    ///
+    pub fn new(fs: f64, sos: &[[f64; 6]]) -> Result<Self> {
+        check_fs(fs)?;
+        let mut filter = Self { fs, cascade: Cascade::new(0) };
+        filter.set_sos(sos)?;
+        Ok(filter)
+    }
+
+    ///
+    /// SOS FROM A DESIGN
+    /// copy of the sections (not of the state) of any filter exporting them
+    ///
+    pub fn from_design<F: SecondOrderSections + FrequencyResponse>(filter: &F) -> Self {
+        let mut cascade = Cascade::new(0);
+        cascade.set_sections(&filter.sos());
+        Self { fs: filter.sampling_rate(), cascade }
+    }
+
+    ///
+    /// SET SECTIONS
+    /// (the state is kept when the number of sections doesn't change)
+    ///
+    /// Args
+    /// ----
+    ///     sos: &[[f64; 6]]
+    ///         sections [b0, b1, b2, a0, a1, a2]
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on a0 = 0 or non finite coefficients (nothing is changed)
+    ///
+    pub fn set_sos(&mut self, sos: &[[f64; 6]]) -> Result<()> {
+        check_sos(sos)?;
+        self.cascade.set_sections(sos);
+        Ok(())
+    }
+
+    pub fn n_sections(&self) -> usize {
+        self.cascade.n_sections()
+    }
+
+    ///
+    /// SECTIONS AS BIQUADS
+    /// one Biquad filter per section (to be run in order), e.g. to use
+    /// another realization
+    ///
+    /// Args
+    /// ----
+    ///     topology: BiquadTopology
+    ///         realization of every biquad
+    ///
+    pub fn biquads(&self, topology: BiquadTopology) -> Vec<Biquad<T>> {
+        self.cascade.biquads(self.fs, topology)
+    }
+
+    ///
+    /// APPLY FILTER SAMPLE BY SAMPLE
+    ///
+    /// Args
+    /// ----
+    ///     sample: T
+    ///         input sample
+    ///
+    /// Return
+    /// ------
+    ///     T
+    ///         filtered sample
+    ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
+        self.cascade.process(sample)
+    }
+
+    ///
+    /// APPLY FILTER ON FRAME OR SIGNAL
+    ///
+    /// Args
+    /// ----
+    ///     frame: &[T]
+    ///         input frame
+    ///
+    /// Return
+    /// ------
+    ///     Vec<T>
+    ///         filtered frame
+    ///
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
+        frame.iter().map(|&x| self.filt_sample(x)).collect()
+    }
+
+    ///
+    /// CLEAR DELAYED SAMPLES CACHE
+    /// set the two state variables of every section to zero
+    ///
+    pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+}
+
+impl<T: Sample> Filter<T> for Sos<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
+        self.cascade.reset();
+    }
+}
+
+impl<T: Sample> FrequencyResponse for Sos<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    fn sections(&self) -> Vec<Section> {
+        self.cascade.to_sections()
+    }
+}
+
+impl<T: Sample> SecondOrderSections for Sos<T> {
+    fn sos(&self) -> Vec<[f64; 6]> {
+        self.cascade.to_sos()
+    }
+}
+
+// section states
+impl<T: Sample> Snapshot for Sos<T> {
+    const FAMILY: &'static str = "sos";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.cascade.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.cascade.load_state(reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{butter::Butter, narrow::Narrow};
+
+    const FS: f64 = 48000.0;
+
+    fn signal(n: usize) -> f64 {
+        if n == 0 { 1.0 } else { libm::sin(0.03 * n as f64) + 0.25 * libm::sin(2.1 * n as f64) }
+    }
+
+    // rows exported by a design, imported by Sos::new and run against the design
+    fn assert_round_trip<F: SecondOrderSections + Filter<f64>>(mut filter: F, tolerance: f64, name: &str) {
+        let rows = filter.sos();
+        let mut sos: Sos = Sos::new(FS, &rows).unwrap();
+        assert_eq!(sos.sos(), rows, "{name}");
+        for n in 0..2000 {
+            let x = signal(n);
+            let (expected, y) = (filter.process_sample(x), sos.filt_sample(x));
+            if tolerance == 0.0 {
+                assert_eq!(y.to_bits(), expected.to_bits(), "{name} sample {n}: {y} != {expected}");
+            } else {
+                assert!((y - expected).abs() <= tolerance, "{name} sample {n}: {y} != {expected}");
+            }
+        }
+    }
+
+    #[test]
+    fn exported_sections_round_trip_bit_exact() {
+        let mut bq: Biquad = Biquad::with_topology(FS, BiquadTopology::TransposedDirectForm2);
+        bq.design_filter("peq", 1000.0, 2.0, Some(6.0)).unwrap();
+        assert_round_trip(bq, 0.0, "biquad");
+
+        for (mode, bw) in [("lp", None), ("bp", Some(400.0))] {
+            let mut butter: Butter = Butter::new(FS, 6);
+            butter.design_filter(mode, 2000.0, bw).unwrap();
+            assert_round_trip(butter, 0.0, mode);
+        }
+    }
+
+    #[test]
+    fn narrow_sections_match_the_filter() {
+        for (mode, order) in [("notch", 1), ("bp", 3)] {
+            let mut narrow: Narrow = Narrow::new(FS, order).unwrap();
+            narrow.design_filter(mode, 1000.0, 100.0).unwrap();
+            assert_round_trip(narrow, 1e-9, mode);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_sections() {
+        let rows = [[1.0, 0.0, 0.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0, 0.0, 0.0], [f64::NAN, 0.0, 0.0, 1.0, 0.0, 0.0]];
+        assert_eq!(Sos::<f64>::new(FS, &rows).err(), Some(FilterError::InvalidSection(1)));
+        assert_eq!(Sos::<f64>::new(FS, &[rows[0], rows[2]]).err(), Some(FilterError::InvalidSection(1)));
+    }
+}
@@ -1,8 +1,13 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use super::{filtertype::{FilterType, TwoZeroTwoPoleFilterType}, coeffstruct::BiquadCoeffs, delayline::DelayLine};
-use pyo3::prelude::*;
+use super::{sample::Sample, filtertype::{FilterType, TwoZeroTwoPoleFilterType}, coeffstruct::BiquadCoeffs, delayline::DelayLine, filter::Filter, error::{Result, FilterError, ModeName, check_fc, check_bw}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
 struct DesignTwoPoleTwoZeroFilter {
     mode: FilterType,
@@ -14,10 +19,10 @@ struct DesignTwoPoleTwoZeroFilter {
 impl DesignTwoPoleTwoZeroFilter {
     fn new(mode: FilterType, fc: f64, fs: f64, bw: f64) -> Self {
 
-        let pi = std::f64::consts::PI;
+        let pi = core::f64::consts::PI;
         let filt_coeffs = BiquadCoeffs::new();
-        let theta_cosine: f64 = (2.0 * pi * fc / fs).cos();
-        let r = (-pi * bw / fs).exp();
+        let theta_cosine: f64 = libm::cos(2.0 * pi * fc / fs);
+        let r = libm::exp(-pi * bw / fs);
 
         Self {
             mode,
@@ -52,19 +57,17 @@ impl DesignTwoPoleTwoZeroFilter {
 }
 
 
-#[pyclass]
-pub struct TwoZeroTwoPole {
+#[derive(Clone)]
+pub struct TwoZeroTwoPole<T: Sample = f64> {
     fs: f64,
-    x1: DelayLine,
-    x2: DelayLine,
-    y1: DelayLine,
-    y2: DelayLine
+    coeffs: BiquadCoeffs<T>,
+    x1: DelayLine<T>,
+    x2: DelayLine<T>,
+    y1: DelayLine<T>,
+    y2: DelayLine<T>
 }
 
-#[pymethods]
-impl TwoZeroTwoPole {
-    #[new]
-
+impl<T: Sample> TwoZeroTwoPole<T> {
     ///
     /// INIT TWOZEROTWOPOLE CLASS
     ///
@@ -73,20 +76,20 @@ impl TwoZeroTwoPole {
     ///     fs: f64
     ///         sampling rate
     ///
-    
-    #[pyo3(text_signature = "(fs: float) -> None")]
     pub fn new(fs: f64) -> Self { 
         Self { 
             fs, 
-            x1: DelayLine::new(1), 
-            x2: DelayLine::new(2), 
-            y1: DelayLine::new(1), 
-            y2: DelayLine::new(2) 
+            coeffs: BiquadCoeffs::with_coeffs((1.0, 0.0, 0.0, 0.0, 0.0, 0.0)),
+            x1: DelayLine::new_unchecked(1), 
+            x2: DelayLine::new_unchecked(2), 
+            y1: DelayLine::new_unchecked(1), 
+            y2: DelayLine::new_unchecked(2) 
         }
     }
 
     ///
     /// GENERATE TWO ZERO/POLE FILTER COEFFICIENTS
+    /// (the design is stored and used by filt_sample/filt_frame)
     ///
     /// Args
     /// ----
@@ -103,50 +106,126 @@ impl TwoZeroTwoPole {
     /// 
     /// Return
     /// ------
-    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///     Result<tuple> -> (f64, f64, f64, f64, f64, f64):
     ///         filter coefficients (b0, b1, b2) or (b0, a1, a2)
+    ///         FilterError on unknown mode, invalid fc or bw
     ///         
-
-    #[pyo3(text_signature = "(mode: str, fc: float, bw: float) -> tuple[float, float, float]")]
-    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: f64) -> (f64, f64, f64, f64, f64, f64) {
+    pub fn design_filter(&mut self, mode: &str, fc: f64, bw: f64) -> Result<(f64, f64, f64, f64, f64, f64)> {
 
         let filt_type = match mode {
             "notch" => FilterType::TwoZeroTwoPoleType(TwoZeroTwoPoleFilterType::Notch),
             "bp" => FilterType::TwoZeroTwoPoleType(TwoZeroTwoPoleFilterType::Bp),
-            _ => {
-                println!("[ERROR] Filt mode not allowed!");
-                std::process::exit(1)
-            }
+            _ => return Err(FilterError::UnknownMode { family: "twozerotwopole", mode: ModeName::new(mode) })
         };
 
+        check_fc(fc, self.fs)?;
+        check_bw(bw)?;
+
         let mut design_filter = DesignTwoPoleTwoZeroFilter::new(filt_type, fc, self.fs, bw);
         design_filter.coeffs();
-        let coeffs = design_filter.filt_coeffs;
-        (coeffs.b0, coeffs.b1, coeffs.b2, coeffs.a0, coeffs.a1, coeffs.a2)
+        self.coeffs = design_filter.filt_coeffs.cast();
+
+        Ok(design_filter.filt_coeffs.get_coeffs())
     
     }
 
+    ///
+    /// SET EXTERNAL FILTER COEFFICIENTS
+    /// override the stored design
+    ///
+    /// Args
+    /// ----
+    ///     coeffs: tuple(f64, f64, f64, f64, f64, f64)
+    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///
+    pub fn set_coeffs(&mut self, coeffs: (f64, f64, f64, f64, f64, f64)) {
+        self.coeffs.set_coeffs(coeffs);
+    }
+
+    ///
+    /// GET FILTER COEFFICIENTS
+    ///
+    /// Return
+    /// ------
+    ///     tuple -> (f64, f64, f64, f64, f64, f64):
+    ///         filter coefficients (b0, b1, b2, a0, a1, a2)
+    ///
+    pub fn coeffs(&self) -> (f64, f64, f64, f64, f64, f64) {
+        self.coeffs.get_coeffs()
+    }
+
+    ///
+    /// SET FILTER STATE
+    /// arbitrary initial state
+    ///
+    /// Args
+    /// ----
+    ///     state: [T; 4]
+    ///         [x[n - 1], x[n - 2], y[n - 1], y[n - 2]]
+    ///
+    pub fn set_state(&mut self, state: [T; 4]) {
+        let [x1, x2, y1, y2] = state;
+        Filter::reset(self);
+        self.x1.write_and_advance(&x1);
+        self.x2.write_and_advance(&x2);
+        self.x2.write_and_advance(&x1);
+        self.y1.write_and_advance(&y1);
+        self.y2.write_and_advance(&y2);
+        self.y2.write_and_advance(&y1);
+    }
+
+    ///
+    /// STEP RESPONSE STEADY STATE
+    /// state of the current coefficients once a unit step has settled
+    /// (as scipy.signal.lfilter_zi), in the layout of set_state.
+    /// Zero state when a pole sits at DC
+    ///
+    /// Return
+    /// ------
+    ///     [T; 4]
+    ///         state for a unit input, scale it by the input level
+    ///
+    pub fn step_state(&self) -> [T; 4] {
+        let (b0, b1, b2, _, a1, a2) = self.coeffs();
+        let gain = (b0 + b1 + b2) / (1.0 + a1 + a2);
+        if !gain.is_finite() {
+            return [T::zero(); 4]
+        }
+        let (x, y) = (T::from_f64(1.0), T::from_f64(gain));
+        [x, x, y, y]
+    }
+
+    ///
+    /// START IN STEADY STATE
+    /// set the state reached by a constant input x0, so that a signal
+    /// starting at x0 (DC offset, mid-file chunk) has no startup transient
+    ///
+    /// Args
+    /// ----
+    ///     x0: T
+    ///         input level, usually the first sample of the signal
+    ///
+    pub fn set_steady_state(&mut self, x0: T) {
+        self.set_state(self.step_state().map(|value| value * x0));
+    }
+
     ///
     /// APPLY FILTER SAMPLE BY SAMPLE
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     ///         input sample
-    ///     coeffs: tuple(f64, f64, f64)
-    ///         filter coefficients (b0, b1, b2) or (bo, a1, a2)
     ///
     /// Return
     /// ------
-    ///     f64
+    ///     T
     ///         filtered sample
     ///
     ///
+    pub fn filt_sample(&mut self, sample: T) -> T {
 
-    #[pyo3(text_signature = "(sample: float, coeffs: tuple[float, float, float, float, float, float]) -> float")]
-    pub fn filt_sample(&mut self, sample: f64, coeffs: (f64, f64, f64, f64, f64, f64)) -> f64 {
-
-        let y = coeffs.0 * sample + coeffs.1 * self.x1.read() + coeffs.2 * self.x2.read() - coeffs.4 * self.y1.read() - coeffs.5 * self.y2.read();
+        let y = self.coeffs.b0 * sample + self.coeffs.b1 * self.x1.read() + self.coeffs.b2 * self.x2.read() - self.coeffs.a1 * self.y1.read() - self.coeffs.a2 * self.y2.read();
 
         self.x1.write_and_advance(&sample);
         self.x2.write_and_advance(&sample);
@@ -161,24 +240,21 @@ impl TwoZeroTwoPole {
     ///
     /// Args
     /// ----
-    ///     frame: Vec<f64>
+    ///     frame: &[f64]
     ///         input frame
-    ///     coeffs: tuple(f64, f64, f64)
-    ///         filter coefficients (b0, b1, b2) or (bo, a1, a2)
     ///
     /// Return
     /// ------
-    ///     Vec<f64>
+    ///     Vec<T>
     ///         filtered frame
     ///
     ///
-
-    #[pyo3(text_signature = "(frame: list[float], coeffs: tuple[float, float, float]) -> list[float]")]
-    pub fn filt_frame(&mut self, frame: Vec<f64>, coeffs: (f64, f64, f64, f64, f64, f64)) -> Vec<f64> {
+    #[cfg(feature = "alloc")]
+    pub fn filt_frame(&mut self, frame: &[T]) -> Vec<T> {
 
         let y = frame
             .iter()
-            .map(|&x| self.filt_sample(x, coeffs))
+            .map(|&x| self.filt_sample(x))
             .collect();
 
         y
@@ -194,12 +270,74 @@ impl TwoZeroTwoPole {
     ///     y[n - 1] = 0.0
     ///     y[n - 2] = 0.0
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
+        self.reset();
+    }
+}
+
+impl<T: Sample> Filter<T> for TwoZeroTwoPole<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        self.filt_sample(sample)
+    }
+
+    fn reset(&mut self) {
         self.x1.clear();
         self.x2.clear();
         self.y1.clear();
         self.y2.clear();
-        println!("[DONE] cache cleared!")
     }
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for TwoZeroTwoPole<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    // a0 is not used by filt_sample (normalized design)
+    fn sections(&self) -> Vec<Section> {
+        let (b0, b1, b2, _, a1, a2) = self.coeffs();
+        vec![Section::new(vec![b0, b1, b2], vec![1.0, a1, a2])]
+    }
+}
+
+// x[n - 1], x[n - 2], y[n - 1], y[n - 2] delay lines
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for TwoZeroTwoPole<T> {
+    const FAMILY: &'static str = "twozerotwopole";
+
+    fn save_state(&self, state: &mut FilterState) {
+        self.x1.save_state(state);
+        self.x2.save_state(state);
+        self.y1.save_state(state);
+        self.y2.save_state(state);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.x1.load_state(reader)?;
+        self.x2.load_state(reader)?;
+        self.y1.load_state(reader)?;
+        self.y2.load_state(reader)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steady_state_gives_a_flat_output_for_dc_input() {
+        let x0 = 0.25;
+        for mode in ["notch", "bp"] {
+            let mut filter: TwoZeroTwoPole = TwoZeroTwoPole::new(48000.0);
+            let (b0, b1, b2, _, a1, a2) = filter.design_filter(mode, 2000.0, 300.0).unwrap();
+            filter.set_steady_state(x0);
+            let level = x0 * (b0 + b1 + b2) / (1.0 + a1 + a2);
+            for n in 0..200 {
+                let y = filter.filt_sample(x0);
+                assert!((y - level).abs() < 1e-12, "{mode} sample {n}: {y} != {level}");
+            }
+        }
+    }
+}
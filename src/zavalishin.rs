@@ -1,41 +1,84 @@
 #![allow(clippy::wrong_self_convention)]
 #![allow(clippy::new_without_default)]
 
-use pyo3::prelude::*;
-use super::filtertype::{FilterType, ZavalishinFilterType};
+use super::{sample::Sample, filtertype::{FilterType, ZavalishinFilterType}, fastmath::{prewarp, MIN_Q}, filter::Filter, error::{Result, FilterError, ModeName, check_fc, check_length, require}};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use super::freqz::{FrequencyResponse, Section};
+#[cfg(feature = "alloc")]
+use super::snapshot::{Snapshot, FilterState, StateReader};
 
-fn filt_sample(sample: &f64, g: f64, _z: f64) -> (f64, f64, f64) {
-    let v = (sample - _z) * g;
+fn filt_sample<T: Sample>(sample: &T, g: T, _z: T) -> (T, T, T) {
+    let v = (*sample - _z) * g;
     let lp = v + _z;
-    let hp = sample - lp;
+    let hp = *sample - lp;
     let z = lp + v;
     (lp, hp, z)
 }
 
-fn filt_sample_svf(sample: &f64, coeffs: (f64, f64, f64), _z: f64, _s: f64) -> (f64, f64, f64, f64) {
-    let hp = (sample - 2.0 * coeffs.2 * _z - coeffs.0 * _z - _s) / coeffs.1;
+fn filt_sample_svf<T: Sample>(sample: &T, coeffs: (T, T, T), _z: T, _s: T) -> (T, T, T, T) {
+    let two = T::from_f64(2.0);
+    let hp = (*sample - two * coeffs.2 * _z - coeffs.0 * _z - _s) / coeffs.1;
     let bp = coeffs.0 * hp + _z;
     let lp = coeffs.0 * bp + _s;
-    let br = sample - 2.0 * coeffs.2 * bp;
+    let br = *sample - two * coeffs.2 * bp;
     (lp, hp, bp, br)
 }
 
 
-#[pyclass]
-pub struct Zavalishin {
-    fs: f64,
-    g: f64,
-    g1: f64,
-    r: f64,
-    z_sample: f64,
-    s_sample: f64,
-    filt_type: Option<FilterType>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZavalishinOutput {
+    LowPass,
+    HighPass,
+    AllPass,
+    BandPass,
+    BandReject
+}
+
+impl ZavalishinOutput {
+    ///
+    /// PARSE OUTPUT NAME
+    ///
+    /// Args
+    /// ----
+    ///     name: &str
+    ///         lp = low pass
+    ///         hp = high pass
+    ///         ap = all pass
+    ///         bp = band pass
+    ///         br = band reject
+    ///
+    /// Return
+    /// ------
+    ///     Result<ZavalishinOutput>
+    ///         FilterError on unknown name
+    ///
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "lp" => Ok(ZavalishinOutput::LowPass),
+            "hp" => Ok(ZavalishinOutput::HighPass),
+            "ap" => Ok(ZavalishinOutput::AllPass),
+            "bp" => Ok(ZavalishinOutput::BandPass),
+            "br" => Ok(ZavalishinOutput::BandReject),
+            _ => Err(FilterError::UnknownMode { family: "zavalishin output", mode: ModeName::new(name) })
+        }
+    }
 }
 
-#[pymethods]
-impl Zavalishin {
-    #[new]
+#[derive(Clone)]
+pub struct Zavalishin<T: Sample = f64> {
+    fs: f64,
+    g: T,
+    g1: T,
+    r: T,
+    z_sample: T,
+    s_sample: T,
+    filt_type: Option<FilterType>,
+    output: ZavalishinOutput
+}
 
+impl<T: Sample> Zavalishin<T> {
     ///
     /// INIT ZAVALISHIN CLASS
     ///
@@ -44,17 +87,17 @@ impl Zavalishin {
     ///     fs: f64
     ///         sampling rate
     ///
-    #[pyo3(text_signature = "(fs: float) -> None")]
     pub fn new(fs: f64) -> Self {
         Self { 
 
             fs, 
-            g: 0.0, 
-            g1: 0.0, 
-            r: 0.0, 
-            z_sample: 0.0, 
-            s_sample: 0.0, 
-            filt_type: None
+            g: T::zero(), 
+            g1: T::zero(), 
+            r: T::zero(), 
+            z_sample: T::zero(), 
+            s_sample: T::zero(), 
+            filt_type: None,
+            output: ZavalishinOutput::LowPass
 
         }
     }
@@ -75,93 +118,90 @@ impl Zavalishin {
     ///     fc_spread: Option<f64>
     ///         frequency spread in Hz (fc + fc_spread = fhigh for SVF)
     ///
-    
-    #[pyo3(text_signature = "(fc: float, fc_spread: float|None) -> None")]
-    pub fn design_filter(&mut self, mode: &str, fc: f64, fc_spread: Option<f64>) {
-        let twopi = 2.0 * std::f64::consts::PI;
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError on unknown mode, invalid fc, missing fc_spread in svf mode
+    ///
+    pub fn design_filter(&mut self, mode: &str, fc: f64, fc_spread: Option<f64>) -> Result<()> {
+        check_fc(fc, self.fs)?;
+        let twopi = 2.0 * core::f64::consts::PI;
         let wc = twopi * fc;
         let ts = 1.0 / self.fs;
-        let mut wa = (2.0 / ts) * (wc * ts / 2.0).tan();
+        let mut wa = (2.0 / ts) * libm::tan(wc * ts / 2.0);
         match mode {
             "zdf" => { 
                 self.filt_type = Some(FilterType::ZavalishinType(ZavalishinFilterType::OnePoleZeroDelay));
-                self.g = wa * ts / 2.0
+                self.g = T::from_f64(wa * ts / 2.0)
             },
             "naive" => { 
                 self.filt_type = Some(FilterType::ZavalishinType(ZavalishinFilterType::NaiveOnePole));
-                self.g = wa * ts
+                self.g = T::from_f64(wa * ts)
             },
             "trap" => { 
                 self.filt_type = Some(FilterType::ZavalishinType(ZavalishinFilterType::TrapIntOnePole));
-                self.g = wa * ts / 2.0
+                self.g = T::from_f64(wa * ts / 2.0)
             },
             "svf" => { 
-                match fc_spread {
-                    Some(spread) => {
-                        self.filt_type = Some(FilterType::ZavalishinType(ZavalishinFilterType::StateVariable));
-                        let w = twopi * (fc + spread);
-                        let w_sqrt = (wc * w).sqrt();
-                        self.r = ((wc + w) / 2.0) / w_sqrt;
-                        wa = (2.0 / ts) * (w_sqrt * ts / 2.0).tan();
-                        self.g = wa * ts / 2.0;
-                        self.g1 = 1.0 + (2.0 * self.r * self.g) + self.g.powf(2.0)
-                    },
-                    None => {
-                        println!("[ERROR] In SVF mode you habe to specify fc_spread!");
-                        std::process::exit(1)
-                    }
-                    
-                }
+                let spread = require(fc_spread, mode, "fc_spread")?;
+                check_fc(fc + spread, self.fs)?;
+                self.filt_type = Some(FilterType::ZavalishinType(ZavalishinFilterType::StateVariable));
+                let w = twopi * (fc + spread);
+                let w_sqrt = libm::sqrt(wc * w);
+                let r = ((wc + w) / 2.0) / w_sqrt;
+                wa = (2.0 / ts) * libm::tan(w_sqrt * ts / 2.0);
+                let g = wa * ts / 2.0;
+                self.r = T::from_f64(r);
+                self.g = T::from_f64(g);
+                self.g1 = T::from_f64(1.0 + (2.0 * r * g) + libm::pow(g, 2.0))
             },
-            _ => {
-                println!("[ERROR] Filter mode not allowed!");
-                std::process::exit(1)
-            }
+            _ => return Err(FilterError::UnknownMode { family: "zavalishin", mode: ModeName::new(mode) })
         }
+        Ok(())
     }
     
     ///
     /// APPLY FILTER SAMPLE BY SAMPLE
+    /// (every output is the input before the first design)
     ///
     /// Args
     /// ----
-    ///     sample: f64
+    ///     sample: T
     ///         sample in
     ///
     /// Return
     /// ------
-    ///     (f64, f64, f64, f64, f64, f64) -> (low_pass, high_pass, allpass, band pass, band reject)
+    ///     (T, T, T, T, T) -> (low_pass, high_pass, allpass, band pass, band reject)
     ///     
     ///
-    
-    #[pyo3(text_signature = "(sample: float) -> tuple[float, float, float, float, float]")]
-    pub fn filt_sample(&mut self, sample: f64) -> (f64, f64, f64, f64, f64) {
+    pub fn filt_sample(&mut self, sample: T) -> (T, T, T, T, T) {
+        let zero = T::zero();
         let (lp, hp, ap, bp, br, z, s) = match &self.filt_type {
             Some(t) => { match t {
                 FilterType::ZavalishinType(ZavalishinFilterType::OnePoleZeroDelay) => {
                     let (_lp, _hp, _z) = filt_sample(&sample, self.g, self.z_sample);
                     let _ap = _lp - _hp;
-                    (_lp, _hp, _ap, 0.0, 0.0, _z, 0.0)
+                    (_lp, _hp, _ap, zero, zero, _z, zero)
                 },
                 FilterType::ZavalishinType(ZavalishinFilterType::NaiveOnePole) => {
                     let (_lp, _hp, _z) = filt_sample(&sample, self.g, self.z_sample);
-                    (_lp, _hp / 2.0, 0.0, 0.0, 0.0, _lp, 0.0)
+                    (_lp, _hp / T::from_f64(2.0), zero, zero, zero, _lp, zero)
                 },
                 FilterType::ZavalishinType(ZavalishinFilterType::TrapIntOnePole) => {
                     let (_lp, _hp, _z) = filt_sample(&sample, self.g, self.z_sample);
-                    (_lp, _hp, 0.0, 0.0, 0.0, _z, 0.0)
+                    (_lp, _hp, zero, zero, zero, _z, zero)
                 },
                 FilterType::ZavalishinType(ZavalishinFilterType::StateVariable) => {
                     let (_lp, _hp, _bp, _br) = filt_sample_svf(&sample, (self.g, self.g1, self.r), self.z_sample, self.s_sample);
                     let _z = self.g * _hp + _bp;
                     let _s = self.g * _bp + _lp;
-                    (_lp, _hp, 0.0, _bp, _br, _z, _s)
+                    (_lp, _hp, zero, _bp, _br, _z, _s)
                     
                 },
-                _ => { (0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0) }
+                _ => { (zero, zero, zero, zero, zero, zero, zero) }
             }
         },
-            None => (0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            None => (sample, sample, sample, sample, sample, zero, zero)
         };
 
         self.z_sample = z;
@@ -170,17 +210,215 @@ impl Zavalishin {
         
     }
 
+    ///
+    /// APPLY FILTER WITH AUDIO-RATE MODULATION
+    /// g (and g1 in svf mode) is recomputed at every sample from fc[n]
+    /// through a tan approximation (no transcendental call per sample),
+    /// fc is clamped to [MIN_FC_RATIO * fs, MAX_FC_RATIO * fs] (NaN to the
+    /// lowest). In svf mode fc is the center frequency and q[n] sets the
+    /// damping r = 1 / (2 * q) (q clamped to at least MIN_Q, NaN to MIN_Q,
+    /// None = keep the designed damping); q is ignored by
+    /// the one pole modes. Writes the output selected by set_output
+    ///
+    /// Args
+    /// ----
+    ///     input: &[T]
+    ///         input frame
+    ///     fc: &[f64]
+    ///         cutoff (center) frequency in Hz per sample (same length as input)
+    ///     q: Option<&[f64]>
+    ///         resonance per sample, svf mode only
+    ///     output: &mut [T]
+    ///         filtered frame (same length as input)
+    ///
+    /// Return
+    /// ------
+    ///     Result<()>
+    ///         FilterError if no design was set or on mismatched lengths
+    ///
+    pub fn filt_modulated(&mut self, input: &[T], fc: &[f64], q: Option<&[f64]>, output: &mut [T]) -> Result<()> {
+        let filt_type = self.filt_type.ok_or(FilterError::MissingDesign)?;
+        check_length(input.len(), fc.len())?;
+        check_length(input.len(), output.len())?;
+        if let Some(q) = q {
+            check_length(input.len(), q.len())?;
+        }
+
+        let mut r = self.r.to_f64();
+        for (n, (&x, y)) in input.iter().zip(output.iter_mut()).enumerate() {
+            let g = prewarp(fc[n], self.fs);
+            match filt_type {
+                FilterType::ZavalishinType(ZavalishinFilterType::NaiveOnePole) => self.g = T::from_f64(2.0 * g),
+                FilterType::ZavalishinType(ZavalishinFilterType::StateVariable) => {
+                    if let Some(q) = q {
+                        r = 0.5 / q[n].max(MIN_Q);
+                        self.r = T::from_f64(r);
+                    }
+                    self.g = T::from_f64(g);
+                    self.g1 = T::from_f64(1.0 + 2.0 * r * g + g * g)
+                },
+                _ => self.g = T::from_f64(g)
+            }
+            *y = self.process_sample(x);
+        }
+        Ok(())
+    }
+
+    ///
+    /// SELECT OUTPUT
+    /// output returned by Filter::process_sample (default low pass)
+    ///
+    /// Args
+    /// ----
+    ///     output: ZavalishinOutput
+    ///         LowPass, HighPass, AllPass, BandPass or BandReject
+    ///
+    pub fn set_output(&mut self, output: ZavalishinOutput) {
+        self.output = output;
+    }
+
     ///
     /// CLEAR DELAYED SAMPLES CACHE
     /// set:
     ///     z_sample = 0.0
     ///     s_sample = 0.0
     ///
-
     pub fn clear_delayed_samples_cache(&mut self) {
-        self.z_sample = 0.0;
-        self.s_sample = 0.0;
-        println!("[DONE] cache cleared!")
+        self.reset();
+    }
+
+}
+
+impl<T: Sample> Filter<T> for Zavalishin<T> {
+    fn process_sample(&mut self, sample: T) -> T {
+        let (lp, hp, ap, bp, br) = self.filt_sample(sample);
+        match self.output {
+            ZavalishinOutput::LowPass => lp,
+            ZavalishinOutput::HighPass => hp,
+            ZavalishinOutput::AllPass => ap,
+            ZavalishinOutput::BandPass => bp,
+            ZavalishinOutput::BandReject => br
+        }
+    }
+
+    fn reset(&mut self) {
+        self.z_sample = T::zero();
+        self.s_sample = T::zero();
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Sample> FrequencyResponse for Zavalishin<T> {
+    fn sampling_rate(&self) -> f64 {
+        self.fs
+    }
+
+    // output selected by set_output (as Filter::process_sample)
+    fn sections(&self) -> Vec<Section> {
+        let g = self.g.to_f64();
+        let zero = Section::new(vec![0.0], vec![1.0]);
+        let section = match (self.filt_type, self.output) {
+            (Some(FilterType::ZavalishinType(ZavalishinFilterType::StateVariable)), output) => {
+                let r = self.r.to_f64();
+                let a = vec![1.0 + 2.0 * r * g + g * g, 2.0 * (g * g - 1.0), 1.0 - 2.0 * r * g + g * g];
+                match output {
+                    ZavalishinOutput::LowPass => Section::new(vec![g * g, 2.0 * g * g, g * g], a),
+                    ZavalishinOutput::HighPass => Section::new(vec![1.0, -2.0, 1.0], a),
+                    ZavalishinOutput::BandPass => Section::new(vec![g, 0.0, -g], a),
+                    ZavalishinOutput::BandReject => Section::new(vec![1.0 + g * g, 2.0 * (g * g - 1.0), 1.0 + g * g], a),
+                    ZavalishinOutput::AllPass => zero
+                }
+            },
+            (Some(FilterType::ZavalishinType(ZavalishinFilterType::NaiveOnePole)), output) => {
+                let a = vec![1.0, g - 1.0];
+                match output {
+                    ZavalishinOutput::LowPass => Section::new(vec![g], a),
+                    ZavalishinOutput::HighPass => Section::new(vec![(1.0 - g) / 2.0, (g - 1.0) / 2.0], a),
+                    _ => zero
+                }
+            },
+            (Some(FilterType::ZavalishinType(mode)), output) => {
+                let a = vec![1.0, 2.0 * g - 1.0];
+                match (mode, output) {
+                    (_, ZavalishinOutput::LowPass) => Section::new(vec![g, g], a),
+                    (_, ZavalishinOutput::HighPass) => Section::new(vec![1.0 - g, g - 1.0], a),
+                    (ZavalishinFilterType::OnePoleZeroDelay, ZavalishinOutput::AllPass) => Section::new(vec![2.0 * g - 1.0, 1.0], a),
+                    _ => zero
+                }
+            },
+            (Some(_), _) => zero,
+            (None, _) => Section::new(vec![1.0], vec![1.0])
+        };
+        vec![section]
+    }
+}
+
+// integrator states
+#[cfg(feature = "alloc")]
+impl<T: Sample> Snapshot for Zavalishin<T> {
+    const FAMILY: &'static str = "zavalishin";
+
+    fn save_state(&self, state: &mut FilterState) {
+        state.push(self.z_sample);
+        state.push(self.s_sample);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader<'_>) -> Result<()> {
+        self.z_sample = reader.read()?;
+        self.s_sample = reader.read()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_input_through_before_design() {
+        let mut filter = Zavalishin::<f64>::new(44100.0);
+        assert_eq!(filter.filt_sample(0.25), (0.25, 0.25, 0.25, 0.25, 0.25));
+        for output in [ZavalishinOutput::LowPass, ZavalishinOutput::HighPass, ZavalishinOutput::AllPass, ZavalishinOutput::BandPass, ZavalishinOutput::BandReject] {
+            filter.set_output(output);
+            assert_eq!(filter.process_sample(-0.5), -0.5);
+        }
+    }
+
+    const N_MODULATED: usize = 1000;
+
+    // exponential sweep from 200 Hz to 6 kHz
+    fn sweep() -> [f64; N_MODULATED] {
+        core::array::from_fn(|n| 200.0 * libm::pow(30.0, n as f64 / N_MODULATED as f64))
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn modulated_matches_per_sample_design() {
+        let fs = 48000.0;
+        let fc = sweep();
+        let input: [f64; N_MODULATED] = core::array::from_fn(|n| libm::sin(0.07 * n as f64) + if n == 0 { 1.0 } else { 0.0 });
+        // svf: the designed damping is kept, the center frequency follows fc
+        let (f_low, spread) = (1000.0, 500.0);
+        let r = (2.0 * f_low + spread) / (2.0 * libm::sqrt(f_low * (f_low + spread)));
+        for (mode, output_type) in [("zdf", ZavalishinOutput::AllPass), ("naive", ZavalishinOutput::LowPass), ("trap", ZavalishinOutput::HighPass), ("svf", ZavalishinOutput::BandPass)] {
+            let mut filter = Zavalishin::<f64>::new(fs);
+            filter.design_filter(mode, f_low, Some(spread)).unwrap();
+            filter.set_output(output_type);
+            let mut output = [0.0; N_MODULATED];
+            filter.filt_modulated(&input, &fc, None, &mut output).unwrap();
+
+            let mut reference = Zavalishin::<f64>::new(fs);
+            reference.set_output(output_type);
+            for n in 0..N_MODULATED {
+                if mode == "svf" {
+                    // band edges with center fc[n] and damping r
+                    let low = fc[n] * (r - libm::sqrt(r * r - 1.0));
+                    reference.design_filter(mode, low, Some(2.0 * fc[n] * libm::sqrt(r * r - 1.0))).unwrap();
+                } else {
+                    reference.design_filter(mode, fc[n], None).unwrap();
+                }
+                let y = reference.process_sample(input[n]);
+                assert!((output[n] - y).abs() < 1e-6, "{mode} sample {n}: {} != {y}", output[n]);
+            }
+        }
+    }
+}